@@ -7,7 +7,7 @@ use std::{
 use client::telemetry::Telemetry;
 use collections::HashMap;
 use command_palette_hooks::{
-    CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptor,
+    CommandInterceptResult, CommandPaletteCommands, CommandPaletteFilter, CommandPaletteInterceptor,
 };
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
@@ -57,7 +57,7 @@ impl CommandPalette {
     ) -> Self {
         let filter = cx.try_global::<CommandPaletteFilter>();
 
-        let commands = cx
+        let mut commands: Vec<Command> = cx
             .available_actions()
             .into_iter()
             .filter_map(|action| {
@@ -76,6 +76,16 @@ impl CommandPalette {
                 })
             })
             .collect();
+        if cx.has_global::<CommandPaletteCommands>() {
+            let extra_commands =
+                cx.update_global(|extra_commands: &mut CommandPaletteCommands, cx| {
+                    extra_commands.commands(cx)
+                });
+            commands.extend(extra_commands.into_iter().map(|command| Command {
+                name: command.name,
+                action: command.action,
+            }));
+        }
 
         let delegate = CommandPaletteDelegate::new(
             cx.view().downgrade(),
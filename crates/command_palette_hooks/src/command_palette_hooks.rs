@@ -1,7 +1,7 @@
 use std::any::TypeId;
 
-use collections::HashSet;
-use gpui::{Action, AppContext, Global};
+use collections::{HashMap, HashSet};
+use gpui::{Action, AnyWindowHandle, AppContext, EntityId, Global, WindowContext};
 
 #[derive(Default)]
 pub struct CommandPaletteFilter {
@@ -22,3 +22,63 @@ pub struct CommandInterceptResult {
     pub string: String,
     pub positions: Vec<usize>,
 }
+
+/// Commands, listed in the command palette along with the actions, e.g. the tasks of the window's project.
+/// The commands are provided anew every time the palette opens, so they are always up to date.
+#[derive(Default)]
+pub struct CommandPaletteCommands {
+    providers: HashMap<AnyWindowHandle, CommandProvider>,
+}
+
+/// Provides the commands for the palette of a window, on behalf of the entity that set it, e.g. the window's workspace.
+struct CommandProvider {
+    owner: EntityId,
+    provide: Box<dyn Fn(&mut WindowContext) -> Vec<PaletteCommand>>,
+}
+
+impl Global for CommandPaletteCommands {}
+
+/// A command, listed in the command palette under its name, dispatching its action once picked.
+pub struct PaletteCommand {
+    pub name: String,
+    pub action: Box<dyn Action>,
+}
+
+impl CommandPaletteCommands {
+    /// Sets the provider of the commands for the palette of the window given, replacing the previous one, if any.
+    /// The provider is owned by the entity given, which should remove it once released.
+    pub fn set_provider(
+        &mut self,
+        window: AnyWindowHandle,
+        owner: EntityId,
+        provider: impl Fn(&mut WindowContext) -> Vec<PaletteCommand> + 'static,
+    ) {
+        self.providers.insert(
+            window,
+            CommandProvider {
+                owner,
+                provide: Box::new(provider),
+            },
+        );
+    }
+
+    /// Forgets the commands of the window given, if they are still provided by the owner given,
+    /// e.g. after the window's workspace is released, as another workspace may have replaced it in the window.
+    pub fn remove_provider(&mut self, window: AnyWindowHandle, owner: EntityId) {
+        if self
+            .providers
+            .get(&window)
+            .is_some_and(|provider| provider.owner == owner)
+        {
+            self.providers.remove(&window);
+        }
+    }
+
+    /// Commands to list in the palette of the window given.
+    pub fn commands(&self, cx: &mut WindowContext) -> Vec<PaletteCommand> {
+        self.providers
+            .get(&cx.window_handle())
+            .map(|provider| (provider.provide)(cx))
+            .unwrap_or_default()
+    }
+}
@@ -68,6 +68,10 @@ pub trait PickerDelegate: Sized + 'static {
     fn render_footer(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
         None
     }
+    /// Text to show in place of the matches, when there are none.
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No matches".into()
+    }
 }
 
 impl<D: PickerDelegate> FocusableView for Picker<D> {
@@ -293,6 +297,13 @@ impl<D: PickerDelegate> Picker<D> {
         cx.notify();
     }
 
+    pub fn refresh_placeholder(&mut self, cx: &mut WindowContext) {
+        let placeholder = self.delegate.placeholder_text(cx);
+        self.editor.update(cx, |editor, cx| {
+            editor.set_placeholder_text(placeholder, cx);
+        });
+    }
+
     pub fn query(&self, cx: &AppContext) -> String {
         self.editor.read(cx).text(cx)
     }
@@ -302,6 +313,13 @@ impl<D: PickerDelegate> Picker<D> {
             .update(cx, |editor, cx| editor.set_text(query, cx));
     }
 
+    /// Selects the whole query, so the next typed text replaces it.
+    pub fn select_query(&self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| {
+            editor.select_all(&editor::actions::SelectAll, cx)
+        });
+    }
+
     fn scroll_to_item_index(&mut self, ix: usize) {
         match &mut self.element_container {
             ElementContainer::List(state) => state.scroll_to_reveal_item(ix),
@@ -401,7 +419,9 @@ impl<D: PickerDelegate> Render for Picker<D> {
                             .inset(true)
                             .spacing(ListItemSpacing::Sparse)
                             .disabled(true)
-                            .child(Label::new("No matches").color(Color::Muted)),
+                            .child(
+                                Label::new(self.delegate.no_matches_text(cx)).color(Color::Muted),
+                            ),
                     ),
                 )
             })
@@ -82,6 +82,14 @@ use std::{
     },
     time::{Duration, Instant},
 };
+use task::{
+    cargo_source::CargoTaskSource,
+    github_workflow_source::GithubWorkflowTaskSource,
+    justfile_source::JustfileTaskSource,
+    makefile_source::MakefileTaskSource,
+    package_json_source::{PackageJsonTaskSource, PackageManager},
+    vscode_source::VsCodeTaskSource,
+};
 use terminals::Terminals;
 use text::{Anchor, BufferId};
 use util::{
@@ -95,9 +103,17 @@ pub use language::Location;
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
 pub use project_core::project_settings;
 pub use project_core::worktree::{self, *};
-pub use task_inventory::Inventory;
+pub use task_inventory::{
+    discover_task_files, group_by_source_kind, usage_blended_score, Inventory, InventoryEvent,
+    LimitedTasks, SourceDescription, TaskRun, TaskSourceError, TaskSourceKind,
+};
 
 const MAX_SERVER_REINSTALL_ATTEMPT_COUNT: u64 = 4;
+const VSCODE_TASKS_RELATIVE_PATH: &str = ".vscode/tasks.json";
+const MAKEFILE_RELATIVE_PATH: &str = "Makefile";
+const JUSTFILE_RELATIVE_PATH: &str = "justfile";
+const CARGO_MANIFEST_FILE_NAME: &str = "Cargo.toml";
+const GITHUB_WORKFLOWS_RELATIVE_PATH: &str = ".github/workflows";
 const SERVER_REINSTALL_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -280,6 +296,7 @@ pub enum Event {
     CollaboratorLeft(proto::PeerId),
     RefreshInlayHints,
     RevealInProjectPanel(ProjectEntryId),
+    BufferSaved(ProjectPath),
 }
 
 pub enum LanguageServerState {
@@ -2372,6 +2389,10 @@ impl Project {
             BufferEvent::Saved => {
                 let file = File::from_dyn(buffer.read(cx).file())?;
                 let worktree_id = file.worktree_id(cx);
+                cx.emit(Event::BufferSaved(ProjectPath {
+                    worktree_id,
+                    path: file.path.clone(),
+                }));
                 let abs_path = file.as_local()?.abs_path(cx);
                 let text_document = lsp::TextDocumentIdentifier {
                     uri: lsp::Url::from_file_path(abs_path).unwrap(),
@@ -6615,6 +6636,10 @@ impl Project {
         })
         .detach();
 
+        if let Some(worktree) = self.worktree_for_id(id_to_remove, cx) {
+            let worktree_abs_path = worktree.read(cx).abs_path();
+            self.remove_worktree_task_sources(id_to_remove, &worktree_abs_path, cx);
+        }
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
                 let id = worktree.read(cx).id();
@@ -6666,6 +6691,7 @@ impl Project {
 
         let handle_id = worktree.entity_id();
         cx.observe_release(worktree, move |this, worktree, cx| {
+            this.remove_worktree_task_sources(worktree.id(), &worktree.abs_path(), cx);
             let _ = this.remove_worktree(worktree.id(), cx);
             cx.update_global::<SettingsStore, _>(|store, cx| {
                 store
@@ -6675,10 +6701,232 @@ impl Project {
         })
         .detach();
 
+        self.add_worktree_task_sources(worktree, cx);
         cx.emit(Event::WorktreeAdded);
         self.metadata_changed(cx);
     }
 
+    fn add_worktree_task_sources(
+        &mut self,
+        worktree: &Model<Worktree>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() || !worktree.is_visible() {
+            return;
+        }
+        let worktree_id = worktree.id();
+        let worktree_abs_path = worktree.abs_path();
+        let fs = self.fs.clone();
+        self.tasks.update(cx, |inventory, cx| {
+            let vscode_tasks_path = worktree_abs_path.join(VSCODE_TASKS_RELATIVE_PATH);
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(vscode_tasks_path.clone()),
+                fs.clone(),
+                move |vscode_tasks_tracker, cx| {
+                    VsCodeTaskSource::new(vscode_tasks_path, vscode_tasks_tracker, cx)
+                },
+                cx,
+            );
+            let makefile_dir = worktree_abs_path.to_path_buf();
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(worktree_abs_path.join(MAKEFILE_RELATIVE_PATH)),
+                fs.clone(),
+                move |makefile_tracker, cx| {
+                    MakefileTaskSource::new(makefile_dir, makefile_tracker, cx)
+                },
+                cx,
+            );
+            let justfile_dir = worktree_abs_path.to_path_buf();
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(worktree_abs_path.join(JUSTFILE_RELATIVE_PATH)),
+                fs.clone(),
+                move |justfile_tracker, cx| {
+                    JustfileTaskSource::new(justfile_dir, justfile_tracker, cx)
+                },
+                cx,
+            );
+        });
+
+        let cargo_fs = fs.clone();
+        let cargo_workspace_dir = worktree_abs_path.clone();
+        cx.spawn(|project, mut cx| async move {
+            let fs = cargo_fs;
+            let root_manifest_path = cargo_workspace_dir.join(CARGO_MANIFEST_FILE_NAME);
+            let Ok(root_manifest) = fs.load(&root_manifest_path).await else {
+                return anyhow::Ok(());
+            };
+            let mut manifest_dirs = vec![(cargo_workspace_dir.to_path_buf(), true)];
+            for member in CargoTaskSource::workspace_members(&root_manifest) {
+                // Only the trailing `*` glob is supported, as the most common way to list the members.
+                let member_dirs = match member.strip_suffix("/*") {
+                    Some(members_parent) => {
+                        let Some(mut entries) = fs
+                            .read_dir(&cargo_workspace_dir.join(members_parent))
+                            .await
+                            .log_err()
+                        else {
+                            continue;
+                        };
+                        let mut member_dirs = Vec::new();
+                        while let Some(entry) = entries.next().await {
+                            member_dirs.extend(entry.log_err());
+                        }
+                        member_dirs
+                    }
+                    None if member.contains(['*', '?', '[']) => continue,
+                    None => vec![cargo_workspace_dir.join(&member)],
+                };
+                for member_dir in member_dirs {
+                    if fs.is_file(&member_dir.join(CARGO_MANIFEST_FILE_NAME)).await {
+                        manifest_dirs.push((member_dir, false));
+                    }
+                }
+            }
+            project.update(&mut cx, |project, cx| {
+                if project.worktree_for_id(worktree_id, cx).is_none() {
+                    return;
+                }
+                project.tasks.update(cx, |inventory, cx| {
+                    for (manifest_dir, workspace_root) in manifest_dirs {
+                        inventory.add_static_source(
+                            TaskSourceKind::Worktree {
+                                id: worktree_id,
+                                abs_path: manifest_dir.join(CARGO_MANIFEST_FILE_NAME),
+                            },
+                            fs.clone(),
+                            move |manifest_tracker, cx| {
+                                CargoTaskSource::new(
+                                    manifest_dir,
+                                    workspace_root,
+                                    manifest_tracker,
+                                    cx,
+                                )
+                            },
+                            cx,
+                        );
+                    }
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+
+        let workflows_fs = fs.clone();
+        let repository_root = worktree_abs_path.clone();
+        cx.spawn(|project, mut cx| async move {
+            let fs = workflows_fs;
+            let workflows_dir = repository_root.join(GITHUB_WORKFLOWS_RELATIVE_PATH);
+            let Ok(mut entries) = fs.read_dir(&workflows_dir).await else {
+                return anyhow::Ok(());
+            };
+            let mut workflow_paths = Vec::new();
+            while let Some(entry) = entries.next().await {
+                let Some(path) = entry.log_err() else {
+                    continue;
+                };
+                let is_workflow = path
+                    .extension()
+                    .is_some_and(|extension| extension == "yml" || extension == "yaml");
+                if is_workflow && fs.is_file(&path).await {
+                    workflow_paths.push(path);
+                }
+            }
+            workflow_paths.sort();
+            project.update(&mut cx, |project, cx| {
+                if project.worktree_for_id(worktree_id, cx).is_none() {
+                    return;
+                }
+                project.tasks.update(cx, |inventory, cx| {
+                    // Every workflow file is a separate source, so a malformed one does not hide the steps of the others.
+                    for workflow_path in workflow_paths {
+                        let workflow_name = workflow_path
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().into_owned())
+                            .unwrap_or_default();
+                        let repository_root = repository_root.to_path_buf();
+                        inventory.add_static_source(
+                            TaskSourceKind::Worktree {
+                                id: worktree_id,
+                                abs_path: workflow_path,
+                            },
+                            fs.clone(),
+                            move |workflow_tracker, cx| {
+                                GithubWorkflowTaskSource::new(
+                                    workflow_name,
+                                    repository_root,
+                                    workflow_tracker,
+                                    cx,
+                                )
+                            },
+                            cx,
+                        );
+                    }
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+
+        cx.spawn(|project, mut cx| async move {
+            let package_json_path = worktree_abs_path.join("package.json");
+            if !fs.is_file(&package_json_path).await {
+                return anyhow::Ok(());
+            }
+            let mut package_manager = PackageManager::default();
+            for candidate in PackageManager::ALL {
+                if fs
+                    .is_file(&worktree_abs_path.join(candidate.lockfile_name()))
+                    .await
+                {
+                    package_manager = candidate;
+                    break;
+                }
+            }
+            project.update(&mut cx, |project, cx| {
+                if project.worktree_for_id(worktree_id, cx).is_none() {
+                    return;
+                }
+                project.tasks.update(cx, |inventory, cx| {
+                    inventory.add_static_source(
+                        TaskSourceKind::Worktree {
+                            id: worktree_id,
+                            abs_path: package_json_path,
+                        },
+                        fs,
+                        move |package_json_tracker, cx| {
+                            PackageJsonTaskSource::new(
+                                package_manager,
+                                worktree_abs_path.to_path_buf(),
+                                package_json_tracker,
+                                cx,
+                            )
+                        },
+                        cx,
+                    );
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn remove_worktree_task_sources(
+        &mut self,
+        worktree_id: WorktreeId,
+        worktree_abs_path: &Path,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.tasks.update(cx, |inventory, cx| {
+            inventory.remove_local_static_source(
+                &worktree_abs_path.join(VSCODE_TASKS_RELATIVE_PATH),
+                cx,
+            );
+            inventory
+                .remove_local_static_source(&worktree_abs_path.join(MAKEFILE_RELATIVE_PATH), cx);
+            inventory
+                .remove_local_static_source(&worktree_abs_path.join(JUSTFILE_RELATIVE_PATH), cx);
+            inventory.remove_worktree_sources(worktree_id, cx);
+        });
+    }
+
     fn update_local_worktree_buffers(
         &mut self,
         worktree_handle: &Model<Worktree>,
@@ -4230,6 +4230,49 @@ async fn test_search_with_exclusions_and_inclusions(cx: &mut gpui::TestAppContex
     );
 }
 
+#[gpui::test]
+async fn test_discovering_task_files(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            ".gitignore": "/node_modules\n/dist\n",
+            "package.json": "{}",
+            "packages": {
+                "app": { "package.json": "{}" },
+                "lib": {
+                    "package.json": "{}",
+                    "src": { "package.json.ts": "" },
+                },
+            },
+            "node_modules": {
+                "eslint": { "package.json": "{}" },
+                "prettier": { "package.json": "{}" },
+            },
+            "dist": { "package.json": "{}" },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let discovered_files = project.update(cx, |project, cx| {
+        let worktree = project.worktrees().next().unwrap();
+        discover_task_files(&worktree.read(cx).snapshot(), "package.json")
+    });
+    assert_eq!(
+        discovered_files,
+        vec![
+            PathBuf::from("/dir/package.json"),
+            PathBuf::from("/dir/packages/app/package.json"),
+            PathBuf::from("/dir/packages/lib/package.json"),
+        ],
+        "Files in the ignored directories should not be discovered"
+    );
+}
+
 #[gpui::test]
 async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4420,6 +4463,133 @@ async fn search(
         .collect())
 }
 
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_running_tasks_capturing_output(cx: &mut gpui::TestAppContext) {
+    use task::{SpawnInTerminal, TaskExitStatus, TaskId, TaskShell};
+    use terminal::terminal_settings::TerminalSettings;
+
+    cx.executor().allow_parking();
+    init_test(cx);
+    cx.update(TerminalSettings::register);
+    let fs = FakeFs::new(cx.executor());
+    let project = Project::test(fs, [], cx).await;
+    let spawn = |command: &str, timeout: Option<Duration>| SpawnInTerminal {
+        env: HashMap::from_iter([("GREETING".to_string(), "hello".to_string())]),
+        timeout,
+        shell: TaskShell::Program("sh".to_string()),
+        ..SpawnInTerminal::new(
+            TaskId(command.to_string()),
+            command.to_string(),
+            command.to_string(),
+        )
+    };
+
+    let output = project
+        .update(cx, |project, cx| {
+            project.run_capturing_output(spawn("echo $GREETING; echo oops >&2; exit 3", None), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(output.exit_status, TaskExitStatus::Failure(Some(3)));
+    assert_eq!(output.stdout, "hello\n");
+    assert_eq!(
+        output.stderr, "oops\n",
+        "Only what the task prints should be captured, with no shell noise"
+    );
+
+    let timed_out = project.update(cx, |project, cx| {
+        project.run_capturing_output(spawn("sleep 10", Some(Duration::from_secs(1))), cx)
+    });
+    cx.run_until_parked();
+    cx.executor().advance_clock(Duration::from_secs(2));
+    let output = timed_out.await.unwrap();
+    assert_eq!(
+        output.exit_status,
+        TaskExitStatus::Failure(None),
+        "Tasks outliving their timeout should be killed, failing without an exit code"
+    );
+    project.update(cx, |project, cx| {
+        assert!(!project
+            .task_inventory()
+            .read(cx)
+            .is_running(&TaskId("sleep 10".to_string())));
+    });
+}
+
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_task_terminal_timeout(cx: &mut gpui::TestAppContext) {
+    use std::{cell::RefCell, rc::Rc};
+    use task::{RevealStrategy, TaskExitStatus, TaskId};
+    use terminal::{terminal_settings::TerminalSettings, SpawnTask};
+
+    cx.executor().allow_parking();
+    init_test(cx);
+    cx.update(TerminalSettings::register);
+    let fs = FakeFs::new(cx.executor());
+    let project = Project::test(fs, [], cx).await;
+    let window = *cx.add_window(|_| ());
+    let task_id = TaskId("sleep".to_string());
+    let terminal = project
+        .update(cx, |project, cx| {
+            project.create_terminal(
+                None,
+                Some(SpawnTask {
+                    id: task_id.clone(),
+                    label: "sleep".to_string(),
+                    command: "sleep".to_string(),
+                    args: vec!["10".to_string()],
+                    env: HashMap::default(),
+                    reuse_key: None,
+                    timeout: Some(Duration::from_secs(1)),
+                    reveal: RevealStrategy::Always,
+                    success_message: None,
+                    failure_message: None,
+                }),
+                window,
+                cx,
+            )
+        })
+        .unwrap();
+    let timed_out = Rc::new(RefCell::new(false));
+    cx.update(|cx| {
+        let timed_out = timed_out.clone();
+        cx.subscribe(&terminal, move |_, event: &terminal::Event, _| {
+            if matches!(event, terminal::Event::TaskTimedOut) {
+                *timed_out.borrow_mut() = true;
+            }
+        })
+        .detach();
+    });
+
+    cx.run_until_parked();
+    assert!(!*timed_out.borrow());
+    cx.executor().advance_clock(Duration::from_secs(2));
+    cx.run_until_parked();
+    assert!(
+        *timed_out.borrow(),
+        "Task outliving its timeout should be reported as timed out"
+    );
+    terminal.update(cx, |terminal, _| {
+        let task = terminal.task().unwrap();
+        assert!(task.completed);
+        assert_eq!(
+            task.exit_status,
+            Some(TaskExitStatus::Failure(None)),
+            "Killed task should fail without an exit code"
+        );
+    });
+    project.update(cx, |project, cx| {
+        let inventory = project.task_inventory().read(cx);
+        assert!(!inventory.is_running(&task_id));
+        assert_eq!(
+            inventory.last_run(&task_id).map(|run| run.exit_status),
+            Some(TaskExitStatus::Failure(None))
+        );
+    });
+}
+
 fn init_test(cx: &mut gpui::TestAppContext) {
     if std::env::var("RUST_LOG").is_ok() {
         env_logger::try_init().ok();
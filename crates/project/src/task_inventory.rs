@@ -1,45 +1,776 @@
 //! Project-wide storage of the tasks available, capable of updating itself from the sources set.
 
-use std::{any::TypeId, path::Path, sync::Arc};
+use std::{
+    any::TypeId,
+    cmp::{self, Reverse},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
-use collections::{HashMap, VecDeque};
-use gpui::{AppContext, Context, Model, ModelContext, Subscription};
-use itertools::Itertools;
-use task::{Task, TaskId, TaskSource};
-use util::{post_inc, NumericPrefixWithSuffix};
+use anyhow::Context as _;
+use collections::{HashMap, HashSet, VecDeque};
+use db::kvp::KEY_VALUE_STORE;
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    StreamExt,
+};
+use gpui::{AppContext, Context, EventEmitter, Model, ModelContext, Subscription};
+use task::{SpawnInTerminal, Task, TaskExitStatus, TaskId, TaskSource};
+use unicase::UniCase;
+use util::{post_inc, NumericPrefixWithSuffix, ResultExt, TryFutureExt};
+
+use crate::{worktree::Snapshot, Fs, WorktreeId};
+
+const TASK_HISTORY_KEY: &str = "TaskHistory";
+const PINNED_TASKS_KEY: &str = "PinnedTasks";
+const DISABLED_SOURCES_KEY: &str = "DisabledTaskSources";
+const DEFAULT_MAX_HISTORY: usize = 5_000;
+const DEFAULT_SCHEDULING_COOLDOWN: Duration = Duration::from_millis(500);
+const TASKS_FILE_WATCH_LATENCY: Duration = Duration::from_millis(100);
+/// Usage bonus of the pinned tasks, see [`Inventory::usage_bonuses`].
+const PINNED_TASK_BONUS: f64 = 1.0;
+/// Usage bonus of the last scheduled task, the earlier scheduled tasks get the smaller ones.
+const RECENT_TASK_BONUS: f64 = 0.5;
+
+/// Changes of the tasks, available in the [`Inventory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryEvent {
+    /// A task source was added, removed, enabled or disabled.
+    SourcesChanged,
+    /// Some of the sources have updated or were asked to re-read their tasks.
+    TasksRefreshed,
+    /// The task has failed, and has to be spawned again, see [`Inventory::retry_on_failure`].
+    /// Attempts are counted from 1, for the first retry.
+    RetryTask { id: TaskId, attempt: u32 },
+}
 
 /// Inventory tracks available tasks for a given project.
 pub struct Inventory {
     sources: Vec<SourceInInventory>,
-    last_scheduled_tasks: VecDeque<TaskId>,
+    last_scheduled_tasks: VecDeque<ScheduledTask>,
+    last_scheduled_cwd: Option<(TaskId, PathBuf)>,
+    max_history: usize,
+    scheduling_cooldown: Duration,
+    pending_serialization: gpui::Task<Option<()>>,
+    pinned_tasks: HashSet<TaskId>,
+    pending_pins_serialization: gpui::Task<Option<()>>,
+    /// Amount of the task instances, currently running in the terminals.
+    running_tasks: HashMap<TaskId, usize>,
+    /// The last finished run of each task, kept while the task stays in the history.
+    last_runs: HashMap<TaskId, TaskRun>,
+    /// Tasks set as the default ones per worktree, `None` for the default task outside of any worktree.
+    default_tasks: HashMap<Option<WorktreeId>, TaskId>,
+    /// The last query, the tasks were looked up by in the tasks modal of the project's workspace.
+    last_query: String,
+    /// Spawned tasks to spawn again on failure, along with the number of the retries made.
+    pending_retries: HashMap<TaskId, (SpawnInTerminal, u32)>,
+    /// Whether the worktree tasks shadow the same named tasks of the other sources, or the other way around.
+    prefer_worktree_tasks: bool,
+    /// Spawned tasks to rerun on the saves of the project files.
+    watched_tasks: HashMap<TaskId, SpawnInTerminal>,
+    /// Keys of the disabled sources, see [`TaskSourceKind::persistence_key`], including the ones not registered currently.
+    disabled_sources: HashSet<String>,
+    /// Keys of the sources, enabled (`true`) or disabled (`false`) in this session, to merge into the persisted state.
+    toggled_sources: HashMap<String, bool>,
+    pending_disabled_sources_serialization: gpui::Task<Option<()>>,
+    /// The scope the state is persisted under, see [`Inventory::load_persisted_state`].
+    persistence_scope: Option<String>,
+    /// Whether the history restored has ids, yet to be checked against the sources, see [`Inventory::prune_restored_history`].
+    pending_history_pruning: bool,
 }
 
 struct SourceInInventory {
     source: Model<Box<dyn TaskSource>>,
     _subscription: Subscription,
     type_id: TypeId,
+    kind: TaskSourceKind,
+    tasks_file: Option<TasksFile>,
+    /// Disabled sources stay registered, but their tasks are not listed.
+    enabled: bool,
+}
+
+impl SourceInInventory {
+    /// Tasks of the source for the path given, along with the files they are attributed to, if any.
+    /// A source that fails to list its tasks is logged and considered to have no tasks, to keep listing the tasks of the other sources.
+    fn tasks_with_origins(
+        &self,
+        path: Option<&Path>,
+        cx: &mut AppContext,
+    ) -> Vec<(Option<PathBuf>, Arc<dyn Task>)> {
+        self.source
+            .update(cx, |source, cx| {
+                source.tasks_for_path_with_origins(path, cx)
+            })
+            .unwrap_or_else(|e| {
+                log::error!(
+                    "Task source {:?} failed to list its tasks: {e:#}",
+                    self.kind
+                );
+                Vec::new()
+            })
+    }
+
+    /// Whether the source is done loading its tasks with no errors, regardless of being enabled.
+    /// A source with its tasks file missing is loaded, as it has no tasks to wait for.
+    fn is_loaded(&self, cx: &AppContext) -> bool {
+        let tasks_file_missing = self
+            .tasks_file
+            .as_ref()
+            .is_some_and(|tasks_file| tasks_file.missing);
+        let source = self.source.read(cx);
+        (tasks_file_missing || !source.is_loading(cx)) && source.error(cx).is_none()
+    }
+
+    /// Tasks of the source for the path given, each with the kind of the source it is attributed to.
+    fn tasks_with_kinds(
+        &self,
+        path: Option<&Path>,
+        cx: &mut AppContext,
+    ) -> Vec<(TaskSourceKind, Arc<dyn Task>)> {
+        self.tasks_with_origins(path, cx)
+            .into_iter()
+            .map(|(origin, task)| match origin {
+                Some(origin) => (self.kind.with_abs_path(origin), task),
+                None => (self.kind.clone(), task),
+            })
+            .collect()
+    }
+}
+
+/// A task from the history, along with the kind of the source it was scheduled from.
+struct ScheduledTask {
+    id: TaskId,
+    /// `None` for the tasks restored from the previous sessions, as the source kinds are not persisted.
+    source_kind: Option<TaskSourceKind>,
+    /// `None` for the tasks restored from the previous sessions, as the times are not persisted.
+    scheduled_at: Option<SystemTime>,
+}
+
+/// The result of a finished task run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskRun {
+    /// When the task process was started.
+    pub started_at: SystemTime,
+    /// How long the task process has been running for.
+    pub duration: Duration,
+    /// How the task process has exited.
+    pub exit_status: TaskExitStatus,
+}
+
+impl TaskRun {
+    /// When the task process has finished.
+    pub fn finished_at(&self) -> SystemTime {
+        self.started_at + self.duration
+    }
+}
+
+/// A file the static source gets its tasks from, that can be reloaded on demand.
+struct TasksFile {
+    abs_path: PathBuf,
+    fs: Arc<dyn Fs>,
+    contents_tx: UnboundedSender<String>,
+    /// Whether the file was found missing on disk: the tasks, parsed from it before, are stale and not listed then.
+    missing: bool,
+    _watcher: gpui::Task<anyhow::Result<()>>,
+}
+
+impl TasksFile {
+    fn reload(&self, cx: &AppContext) {
+        let abs_path = self.abs_path.clone();
+        let fs = self.fs.clone();
+        let contents_tx = self.contents_tx.clone();
+        cx.background_executor()
+            .spawn(async move {
+                let contents = fs.load(&abs_path).await.unwrap_or_default();
+                contents_tx.unbounded_send(contents).ok();
+            })
+            .detach();
+    }
+}
+
+/// Kind of a source the tasks are fetched from, used to display more source information in the UI.
+/// When tasks with the same name come from different kinds of sources, only the ones with the highest precedence are listed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TaskSourceKind {
+    /// bash-like commands spawned by users, not associated with any path
+    UserInput,
+    /// ~/.config/zed/tasks.json - user-level task definitions, available in every project and never filtered by worktree
+    Global(PathBuf),
+    /// Files with task definitions, applicable to any path
+    AbsPath(PathBuf),
+    /// Worktree-specific task definitions, e.g. dynamic language-specific tasks, or .zed/task.json
+    Worktree { id: WorktreeId, abs_path: PathBuf },
+}
+
+/// A problem, reported by a task source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSourceError {
+    /// Kind of the source that reported the problem.
+    pub kind: TaskSourceKind,
+    /// Human readable description of the problem.
+    pub message: String,
+}
+
+/// Description of a registered task source, to tell why its tasks are listed or not, see [`Inventory::describe_sources`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDescription {
+    /// Kind of the source, with the path and the worktree it was registered for.
+    pub kind: TaskSourceKind,
+    /// Type of the source, e.g. the one of [`task::static_source::StaticSource`].
+    pub type_id: TypeId,
+    /// Path of the tasks file, the inventory tracks for the source, if it was added as a static one.
+    pub tasks_file: Option<PathBuf>,
+    /// Whether the tracked tasks file is missing on disk, in which case the source's tasks are not listed.
+    pub tasks_file_missing: bool,
+    /// Whether the source is enabled, the tasks of the disabled sources are not listed.
+    pub enabled: bool,
+    /// Number of the tasks the source has for no path, including the ones shadowed by the other sources.
+    pub task_count: usize,
+    /// Whether the source is still producing its tasks in the background.
+    pub loading: bool,
+    /// The problem, preventing the source from producing its tasks, if any.
+    pub error: Option<String>,
+}
+
+/// Tasks, listed up to a limit, see [`Inventory::list_tasks_limited`].
+#[derive(Clone)]
+pub struct LimitedTasks {
+    /// The first tasks of the full list, in the same order.
+    pub tasks: Vec<(TaskSourceKind, Arc<dyn Task>)>,
+    /// Number of the tasks there were to list, before the ones beyond the limit were dropped.
+    pub total: usize,
+}
+
+impl LimitedTasks {
+    /// Whether some of the tasks were dropped, for being beyond the limit.
+    pub fn is_truncated(&self) -> bool {
+        self.tasks.len() < self.total
+    }
+}
+
+impl TaskSourceKind {
+    /// Path to the file with task definitions, if the source is backed by one.
+    pub fn abs_path(&self) -> Option<&Path> {
+        match self {
+            Self::Global(abs_path) | Self::AbsPath(abs_path) | Self::Worktree { abs_path, .. } => {
+                Some(abs_path.as_path())
+            }
+            Self::UserInput => None,
+        }
+    }
+
+    /// Resolves the working directory of a task from this source.
+    /// Relative directories are resolved against the root of the worktree given, which the source belongs to,
+    /// or against the directory of the task definitions file, if there is no such worktree.
+    pub fn resolve_cwd(&self, cwd: &Path, worktree_root: Option<&Path>) -> PathBuf {
+        if cwd.is_absolute() {
+            return cwd.to_path_buf();
+        }
+        match worktree_root.or_else(|| self.abs_path()?.parent()) {
+            Some(base) => base.join(cwd),
+            None => cwd.to_path_buf(),
+        }
+    }
+
+    /// Worktree the source is associated with, if any.
+    pub fn worktree(&self) -> Option<WorktreeId> {
+        match self {
+            Self::Worktree { id, .. } => Some(*id),
+            Self::UserInput | Self::Global(_) | Self::AbsPath(_) => None,
+        }
+    }
+
+    /// Directory of the task definitions file, for the sources that are not tied to any worktree,
+    /// to tell apart the tasks from files outside of the project.
+    pub fn standalone_directory(&self) -> Option<&Path> {
+        match self {
+            Self::AbsPath(abs_path) => abs_path.parent(),
+            Self::UserInput | Self::Global(_) | Self::Worktree { .. } => None,
+        }
+    }
+
+    /// The same kind of source, but for the definitions file given, to attribute the tasks of a source to the files they are defined in.
+    /// User input has no definitions file, so its tasks with such a file are attributed to that file alone.
+    pub fn with_abs_path(&self, abs_path: PathBuf) -> Self {
+        match self {
+            Self::UserInput | Self::AbsPath(_) => Self::AbsPath(abs_path),
+            Self::Global(_) => Self::Global(abs_path),
+            Self::Worktree { id, .. } => Self::Worktree { id: *id, abs_path },
+        }
+    }
+
+    /// A name of the source kind, to tell the sources apart outside of the UI, e.g. in the exported tasks.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::UserInput => "user_input",
+            Self::Global(_) => "global",
+            Self::AbsPath(_) => "abs_path",
+            Self::Worktree { .. } => "worktree",
+        }
+    }
+
+    /// Identifies the source across the sessions, to persist its state with: the worktree ids change between them,
+    /// but the paths of the tasks files do not.
+    fn persistence_key(&self) -> String {
+        match self.abs_path() {
+            Some(abs_path) => format!("{}:{}", self.kind_name(), abs_path.display()),
+            None => self.kind_name().to_string(),
+        }
+    }
+
+    /// A short human readable description of the source, to display next to its tasks.
+    pub fn label(&self) -> String {
+        match self {
+            Self::UserInput => "User".to_string(),
+            Self::Global(_) => "Global".to_string(),
+            Self::AbsPath(abs_path) | Self::Worktree { abs_path, .. } => abs_path
+                .file_name()
+                .unwrap_or(abs_path.as_os_str())
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
+    /// Tasks of the more specific sources shadow the same named tasks of the generic ones.
+    /// Unless the worktree tasks are preferred, they are shadowed by the same named tasks of any other source instead.
+    fn precedence(&self, prefer_worktree_tasks: bool) -> u8 {
+        let precedence = match self {
+            Self::UserInput => 0,
+            Self::Global(_) => 1,
+            Self::AbsPath(_) => 2,
+            Self::Worktree { .. } => 3,
+        };
+        match self {
+            _ if prefer_worktree_tasks => precedence,
+            Self::Worktree { .. } => 0,
+            _ => precedence + 1,
+        }
+    }
 }
 
+impl EventEmitter<InventoryEvent> for Inventory {}
+
 impl Inventory {
     pub(crate) fn new(cx: &mut AppContext) -> Model<Self> {
         cx.new_model(|_| Self {
             sources: Vec::new(),
             last_scheduled_tasks: VecDeque::new(),
+            last_scheduled_cwd: None,
+            max_history: DEFAULT_MAX_HISTORY,
+            scheduling_cooldown: DEFAULT_SCHEDULING_COOLDOWN,
+            pending_serialization: gpui::Task::ready(None),
+            pinned_tasks: HashSet::default(),
+            pending_pins_serialization: gpui::Task::ready(None),
+            running_tasks: HashMap::default(),
+            last_runs: HashMap::default(),
+            default_tasks: HashMap::default(),
+            last_query: String::new(),
+            pending_retries: HashMap::default(),
+            prefer_worktree_tasks: true,
+            disabled_sources: HashSet::default(),
+            toggled_sources: HashMap::default(),
+            pending_disabled_sources_serialization: gpui::Task::ready(None),
+            watched_tasks: HashMap::default(),
+            persistence_scope: None,
+            pending_history_pruning: false,
         })
     }
 
+    /// Restores the state persisted for the scope given, e.g. the workspace of the project, and persists the further changes under it.
+    /// Until then, nothing is restored or persisted, so the projects, opened in the other windows, keep their states apart.
+    /// Should be called once, when the project's worktrees are added, for their sources to be registered before.
+    pub fn load_persisted_state(&mut self, scope: String, cx: &mut ModelContext<Self>) {
+        let history_key = scoped_key(TASK_HISTORY_KEY, &scope);
+        let pins_key = scoped_key(PINNED_TASKS_KEY, &scope);
+        let disabled_sources_key = scoped_key(DISABLED_SOURCES_KEY, &scope);
+        self.persistence_scope = Some(scope);
+        cx.spawn(|inventory, mut cx| async move {
+            let serialized_history = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(&history_key) })
+                .await
+                .log_err()
+                .flatten();
+            if let Some(serialized_history) = serialized_history {
+                let history = serde_json::from_str::<Vec<TaskId>>(&serialized_history)?;
+                inventory.update(&mut cx, |inventory, cx| {
+                    inventory.restore_history(history);
+                    inventory.prune_restored_history(cx);
+                    cx.notify();
+                })?;
+            }
+            let serialized_pins = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(&pins_key) })
+                .await
+                .log_err()
+                .flatten();
+            if let Some(serialized_pins) = serialized_pins {
+                // Pins of the tasks missing from all sources are kept, but never listed.
+                let pins = serde_json::from_str::<Vec<TaskId>>(&serialized_pins)?;
+                inventory.update(&mut cx, |inventory, cx| {
+                    inventory.pinned_tasks.extend(pins);
+                    cx.notify();
+                })?;
+            }
+            let serialized_disabled_sources = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(&disabled_sources_key) })
+                .await
+                .log_err()
+                .flatten();
+            let disabled_sources = serialized_disabled_sources
+                .map(|serialized| serde_json::from_str::<Vec<String>>(&serialized))
+                .transpose()?
+                .unwrap_or_default();
+            inventory.update(&mut cx, |inventory, cx| {
+                inventory.restore_disabled_sources(disabled_sources, cx);
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     /// Registers a new tasks source, that would be fetched for available tasks.
-    pub fn add_source(&mut self, source: Model<Box<dyn TaskSource>>, cx: &mut ModelContext<Self>) {
-        let _subscription = cx.observe(&source, |_, _, cx| {
+    pub fn add_source(
+        &mut self,
+        kind: TaskSourceKind,
+        source: Model<Box<dyn TaskSource>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.push_source(kind, source, None, cx);
+    }
+
+    fn push_source(
+        &mut self,
+        kind: TaskSourceKind,
+        source: Model<Box<dyn TaskSource>>,
+        tasks_file: Option<TasksFile>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let _subscription = cx.observe(&source, |inventory, _, cx| {
+            inventory.prune_restored_history(cx);
+            cx.emit(InventoryEvent::TasksRefreshed);
             cx.notify();
         });
         let type_id = source.read(cx).type_id();
+        let enabled = !self.disabled_sources.contains(&kind.persistence_key());
         let source = SourceInInventory {
             source,
             _subscription,
             type_id,
+            kind,
+            tasks_file,
+            enabled,
         };
         self.sources.push(source);
+        cx.emit(InventoryEvent::SourcesChanged);
+        cx.notify();
+    }
+
+    /// Registers a new tasks source, backed by the tasks file of the source kind given, e.g. [`task::static_source::StaticSource::new`].
+    /// The source gets the file contents each time the file changes on disk, until the source is removed.
+    /// A tasks file can back only one source: adding another source for the same file does nothing.
+    pub fn add_static_source(
+        &mut self,
+        kind: TaskSourceKind,
+        fs: Arc<dyn Fs>,
+        create_source: impl FnOnce(
+            UnboundedReceiver<String>,
+            &mut AppContext,
+        ) -> Model<Box<dyn TaskSource>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(abs_path) = kind.abs_path() else {
+            log::error!("Cannot add a static tasks source without a tasks file path: {kind:?}");
+            return;
+        };
+        let abs_path = abs_path.to_path_buf();
+        // The check and the insertion below happen in the same inventory update, so no other source can get in between.
+        let already_added = self.sources.iter().any(|source| {
+            source
+                .tasks_file
+                .as_ref()
+                .is_some_and(|tasks_file| tasks_file.abs_path == abs_path)
+        });
+        if already_added {
+            log::debug!("Tasks file {abs_path:?} already has a source, skipping {kind:?}");
+            return;
+        }
+        let (contents_tx, contents_rx) = mpsc::unbounded();
+        let _watcher = cx.spawn({
+            let abs_path = abs_path.clone();
+            let fs = fs.clone();
+            let contents_tx = contents_tx.clone();
+            |inventory, mut cx| async move {
+                let events = fs.watch(&abs_path, TASKS_FILE_WATCH_LATENCY).await;
+                futures::pin_mut!(events);
+                loop {
+                    let contents = fs.load(&abs_path).await.ok();
+                    let missing = contents.is_none();
+                    if let Some(contents) = contents {
+                        if contents_tx.unbounded_send(contents).is_err() {
+                            break;
+                        }
+                    }
+                    inventory.update(&mut cx, |inventory, cx| {
+                        inventory.tasks_file_checked(&abs_path, missing, cx)
+                    })?;
+                    if events.next().await.is_none() {
+                        break;
+                    }
+                }
+                anyhow::Ok(())
+            }
+        });
+        let source = create_source(contents_rx, cx);
+        let tasks_file = TasksFile {
+            abs_path,
+            fs,
+            contents_tx,
+            missing: false,
+            _watcher,
+        };
+        self.push_source(kind, source, Some(tasks_file), cx);
+    }
+
+    fn tasks_file_checked(&mut self, abs_path: &Path, missing: bool, cx: &mut ModelContext<Self>) {
+        let mut changed = false;
+        for tasks_file in self
+            .sources
+            .iter_mut()
+            .filter_map(|source| source.tasks_file.as_mut())
+            .filter(|tasks_file| tasks_file.abs_path == abs_path)
+        {
+            changed |= tasks_file.missing != missing;
+            tasks_file.missing = missing;
+        }
+        if changed {
+            self.prune_restored_history(cx);
+            cx.emit(InventoryEvent::TasksRefreshed);
+            cx.notify();
+        }
+    }
+
+    /// Sources, whose tasks can be listed: the disabled ones and the ones with tasks files that went missing on disk are skipped.
+    fn available_sources(&self) -> impl Iterator<Item = &SourceInInventory> {
+        self.sources.iter().filter(|source| {
+            source.enabled
+                && !source
+                    .tasks_file
+                    .as_ref()
+                    .is_some_and(|tasks_file| tasks_file.missing)
+        })
+    }
+
+    /// Enables or disables the sources of the kind given, e.g. to hide the tasks of a noisy source, without removing it.
+    /// The state is persisted by the tasks file paths of the sources, so the sources registered later for the same files,
+    /// e.g. when the project is reopened, are enabled or disabled the same way.
+    pub fn set_source_enabled(
+        &mut self,
+        kind: &TaskSourceKind,
+        enabled: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let key = kind.persistence_key();
+        let changed = if enabled {
+            self.disabled_sources.remove(&key)
+        } else {
+            self.disabled_sources.insert(key.clone())
+        };
+        for source in &mut self.sources {
+            if &source.kind == kind {
+                source.enabled = enabled;
+            }
+        }
+        // Before the persisted state is loaded, the toggle may change it, even with no changes to the current state.
+        if self.toggled_sources.insert(key, enabled) != Some(enabled) {
+            self.serialize_disabled_sources(cx);
+        }
+        if !changed {
+            return;
+        }
+        cx.emit(InventoryEvent::SourcesChanged);
+        cx.notify();
+    }
+
+    /// Disables the sources, persisted as disabled, unless they were enabled or disabled in this session already.
+    fn restore_disabled_sources(
+        &mut self,
+        disabled_sources: Vec<String>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.disabled_sources.extend(
+            disabled_sources
+                .into_iter()
+                .filter(|key| !self.toggled_sources.contains_key(key)),
+        );
+        let mut changed = false;
+        for source in &mut self.sources {
+            let enabled = !self
+                .disabled_sources
+                .contains(&source.kind.persistence_key());
+            changed |= source.enabled != enabled;
+            source.enabled = enabled;
+        }
+        // The sources, toggled before the persisted state was loaded, are not persisted yet.
+        if !self.toggled_sources.is_empty() {
+            self.serialize_disabled_sources(cx);
+        }
+        if changed {
+            cx.emit(InventoryEvent::SourcesChanged);
+            cx.notify();
+        }
+    }
+
+    /// Applies the sources, toggled in this session, to the persisted state under the persistence scope, if it is set.
+    /// The persisted state is read again for that, to keep the changes, made to it elsewhere, for the other sources.
+    fn serialize_disabled_sources(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(scope) = self.persistence_scope.as_deref() else {
+            return;
+        };
+        let disabled_sources_key = scoped_key(DISABLED_SOURCES_KEY, scope);
+        let toggled_sources = self.toggled_sources.clone();
+        self.pending_disabled_sources_serialization = cx.background_executor().spawn(
+            async move {
+                let mut disabled_sources = match KEY_VALUE_STORE.read_kvp(&disabled_sources_key)? {
+                    Some(serialized) => serde_json::from_str::<Vec<String>>(&serialized)?,
+                    None => Vec::new(),
+                };
+                disabled_sources.retain(|key| !toggled_sources.contains_key(key));
+                disabled_sources.extend(
+                    toggled_sources
+                        .into_iter()
+                        .filter(|(_, enabled)| !enabled)
+                        .map(|(key, _)| key),
+                );
+                disabled_sources.sort();
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        disabled_sources_key,
+                        serde_json::to_string(&disabled_sources)?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Whether the sources of the kind given are enabled, which all sources are, unless disabled explicitly.
+    pub fn is_source_enabled(&self, kind: &TaskSourceKind) -> bool {
+        !self.disabled_sources.contains(&kind.persistence_key())
+    }
+
+    /// Makes all sources, backed by tasks files, re-read their files from disk.
+    /// Task history is preserved.
+    pub fn refresh_sources(&mut self, cx: &mut ModelContext<Self>) {
+        for tasks_file in self
+            .sources
+            .iter()
+            .filter_map(|source| source.tasks_file.as_ref())
+        {
+            tasks_file.reload(cx);
+        }
+        cx.emit(InventoryEvent::TasksRefreshed);
+        cx.notify();
+    }
+
+    /// Makes the sources, backed by the tasks file at the given path, re-read it from disk.
+    /// Task history is preserved.
+    pub fn refresh_source_for_path(&mut self, abs_path: &Path, cx: &mut ModelContext<Self>) {
+        for tasks_file in self
+            .sources
+            .iter()
+            .filter_map(|source| source.tasks_file.as_ref())
+            .filter(|tasks_file| tasks_file.abs_path == abs_path)
+        {
+            tasks_file.reload(cx);
+        }
+        cx.emit(InventoryEvent::TasksRefreshed);
+        cx.notify();
+    }
+
+    /// Removes the sources, backed by the tasks config file at the given path, stopping the file tracking.
+    pub fn remove_local_static_source(&mut self, abs_path: &Path, cx: &mut ModelContext<Self>) {
+        self.remove_sources_where(|source| source.kind.abs_path() == Some(abs_path), cx);
+    }
+
+    /// Removes the sources of the kind given, including the ones not backed by any file, e.g. [`TaskSourceKind::UserInput`].
+    pub fn remove_source_by_kind(&mut self, kind: &TaskSourceKind, cx: &mut ModelContext<Self>) {
+        self.remove_sources_where(|source| &source.kind == kind, cx);
+    }
+
+    /// Removes all sources of the type given, e.g. to disable the tasks of a particular tool.
+    pub fn remove_source<T: TaskSource>(&mut self, cx: &mut ModelContext<Self>) {
+        let target_type_id = TypeId::of::<T>();
+        self.remove_sources_where(|source| source.type_id == target_type_id, cx);
+    }
+
+    fn remove_sources_where(
+        &mut self,
+        should_remove: impl Fn(&SourceInInventory) -> bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let sources_before = self.sources.len();
+        self.sources.retain(|source| !should_remove(source));
+        if self.sources.len() != sources_before {
+            cx.emit(InventoryEvent::SourcesChanged);
+            cx.notify();
+        }
+    }
+
+    /// Removes all sources of the [`TaskSourceKind::Worktree`] kind, associated with the worktree given,
+    /// along with the history entries of their tasks.
+    pub fn remove_worktree_sources(
+        &mut self,
+        worktree_id: WorktreeId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let (removed_sources, sources) = std::mem::take(&mut self.sources)
+            .into_iter()
+            .partition::<Vec<_>, _>(|source| source.kind.worktree() == Some(worktree_id));
+        self.sources = sources;
+        if removed_sources.is_empty() {
+            return;
+        }
+
+        let task_ids = |sources: &[SourceInInventory], cx: &mut ModelContext<Self>| {
+            sources
+                .iter()
+                .flat_map(|source| {
+                    source
+                        .tasks_with_origins(source.kind.abs_path(), cx)
+                        .into_iter()
+                        .map(|(_, task)| task.id().clone())
+                })
+                .collect::<HashSet<_>>()
+        };
+        let removed_task_ids = task_ids(&removed_sources, cx);
+        let remaining_task_ids = task_ids(&self.sources, cx);
+        self.last_scheduled_tasks
+            .retain(|scheduled_task| match &scheduled_task.source_kind {
+                Some(source_kind) => source_kind.worktree() != Some(worktree_id),
+                // Restored tasks are only known by their ids, so they are kept while any remaining source has such task.
+                None => {
+                    !removed_task_ids.contains(&scheduled_task.id)
+                        || remaining_task_ids.contains(&scheduled_task.id)
+                }
+            });
+        let last_scheduled_id = self
+            .last_scheduled_tasks
+            .back()
+            .map(|scheduled_task| &scheduled_task.id);
+        if self
+            .last_scheduled_cwd
+            .as_ref()
+            .is_some_and(|(id, _)| Some(id) != last_scheduled_id)
+        {
+            self.last_scheduled_cwd = None;
+        }
+        self.serialize_history(cx);
+        cx.emit(InventoryEvent::SourcesChanged);
         cx.notify();
     }
 
@@ -58,35 +789,170 @@ impl Inventory {
         )
     }
 
+    /// Number of the registered sources, including the ones that currently have no tasks.
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Number of the tasks, [`Self::list_tasks`] would return for no path and no worktree, without the hidden ones.
+    /// All tasks of the sources are collected for that, same as when listing them, only their sorting is skipped:
+    /// callers, that list the tasks anyway, should count the listed ones instead.
+    pub fn task_count(&self, cx: &mut AppContext) -> usize {
+        self.list_tasks_of_sources(None, false, worktree_filter(None), false, Some(0), cx)
+            .total
+    }
+
+    /// Whether any of the sources is still producing its tasks in the background.
+    /// Observers of the inventory are notified when the sources are done.
+    pub fn is_loading(&self, cx: &AppContext) -> bool {
+        self.sources
+            .iter()
+            .any(|source| source.source.read(cx).is_loading(cx))
+    }
+
+    /// Describes every registered source, in the order of registration, including the ones whose tasks are not listed.
+    pub fn describe_sources(&self, cx: &mut AppContext) -> Vec<SourceDescription> {
+        self.sources
+            .iter()
+            .map(|source| {
+                let task_count = source.tasks_with_origins(None, cx).len();
+                let source_model = source.source.read(cx);
+                SourceDescription {
+                    kind: source.kind.clone(),
+                    type_id: source.type_id,
+                    tasks_file: source
+                        .tasks_file
+                        .as_ref()
+                        .map(|tasks_file| tasks_file.abs_path.clone()),
+                    tasks_file_missing: source
+                        .tasks_file
+                        .as_ref()
+                        .is_some_and(|tasks_file| tasks_file.missing),
+                    enabled: source.enabled,
+                    task_count,
+                    loading: source_model.is_loading(cx),
+                    error: source_model.error(cx),
+                }
+            })
+            .collect()
+    }
+
+    /// Collects the problems, preventing the sources from producing their tasks, e.g. malformed task files.
+    pub fn source_errors(&self, cx: &AppContext) -> Vec<TaskSourceError> {
+        self.available_sources()
+            .filter_map(|source| {
+                Some(TaskSourceError {
+                    kind: source.kind.clone(),
+                    message: source.source.read(cx).error(cx)?,
+                })
+            })
+            .collect()
+    }
+
     /// Pulls its sources to list runanbles for the path given (up to the source to decide what to return for no path).
+    /// Same named tasks from different kinds of sources are deduplicated, leaving the ones from the sources with the highest [`TaskSourceKind`] precedence.
+    /// If a worktree is given, sources of the other worktrees are skipped.
+    /// Pinned tasks are listed first, regardless of their usage.
+    /// Tasks with the same usage are sorted by their explicit [`Task::order`] first, then by name.
+    /// Sources, whose tasks files are missing on disk, are skipped.
+    /// [`Task::hidden`] tasks are skipped too, unless requested, e.g. to look a task up by its name to run it.
     pub fn list_tasks(
+        &self,
+        path: Option<&Path>,
+        worktree: Option<WorktreeId>,
+        lru: bool,
+        include_hidden: bool,
+        cx: &mut AppContext,
+    ) -> Vec<(TaskSourceKind, Arc<dyn Task>)> {
+        self.list_tasks_of_sources(
+            path,
+            lru,
+            worktree_filter(worktree),
+            include_hidden,
+            None,
+            cx,
+        )
+        .tasks
+    }
+
+    /// Same as [`Self::list_tasks`], but only the first tasks, up to the limit given, are listed.
+    /// The rest of the tasks are not sorted, which saves work for the consumers that need a few top tasks out of many.
+    /// The number of all tasks is returned too, to tell the tasks beyond the limit from the missing ones.
+    pub fn list_tasks_limited(
+        &self,
+        path: Option<&Path>,
+        worktree: Option<WorktreeId>,
+        lru: bool,
+        limit: usize,
+        cx: &mut AppContext,
+    ) -> LimitedTasks {
+        self.list_tasks_of_sources(path, lru, worktree_filter(worktree), false, Some(limit), cx)
+    }
+
+    /// Same as [`Self::list_tasks`], but only the sources of the kinds matching the predicate are pulled,
+    /// e.g. to list the user input tasks only, or the tasks of a particular worktree.
+    /// Same named tasks are deduplicated among the matching sources only.
+    pub fn list_tasks_for_kind(
+        &self,
+        path: Option<&Path>,
+        lru: bool,
+        kind_predicate: impl Fn(&TaskSourceKind) -> bool,
+        cx: &mut AppContext,
+    ) -> Vec<(TaskSourceKind, Arc<dyn Task>)> {
+        self.list_tasks_of_sources(path, lru, kind_predicate, false, None, cx)
+            .tasks
+    }
+
+    fn list_tasks_of_sources(
         &self,
         path: Option<&Path>,
         lru: bool,
+        source_filter: impl Fn(&TaskSourceKind) -> bool,
+        include_hidden: bool,
+        limit: Option<usize>,
         cx: &mut AppContext,
-    ) -> Vec<Arc<dyn Task>> {
+    ) -> LimitedTasks {
         let mut lru_score = 0_u32;
         let tasks_by_usage = if lru {
-            self.last_scheduled_tasks
-                .iter()
-                .rev()
-                .fold(HashMap::default(), |mut tasks, id| {
-                    tasks.entry(id).or_insert_with(|| post_inc(&mut lru_score));
+            self.last_scheduled_tasks.iter().rev().fold(
+                HashMap::default(),
+                |mut tasks, scheduled_task| {
                     tasks
-                })
+                        .entry(&scheduled_task.id)
+                        .or_insert_with(|| post_inc(&mut lru_score));
+                    tasks
+                },
+            )
         } else {
             HashMap::default()
         };
         let not_used_score = post_inc(&mut lru_score);
 
-        self.sources
-            .iter()
-            .flat_map(|source| {
-                source
-                    .source
-                    .update(cx, |source, cx| source.tasks_for_path(path, cx))
+        let tasks = self
+            .available_sources()
+            .filter(|source| source_filter(&source.kind))
+            .flat_map(|source| source.tasks_with_kinds(path, cx))
+            .collect::<Vec<_>>();
+        let highest_precedence_by_name = tasks.iter().fold(
+            HashMap::<String, u8>::default(),
+            |mut precedences, (kind, task)| {
+                let highest_precedence = precedences.entry(task.name().to_string()).or_default();
+                *highest_precedence =
+                    (*highest_precedence).max(kind.precedence(self.prefer_worktree_tasks));
+                precedences
+            },
+        );
+
+        let mut tasks = tasks
+            .into_iter()
+            .filter(|(kind, task)| {
+                highest_precedence_by_name.get(task.name())
+                    == Some(&kind.precedence(self.prefer_worktree_tasks))
             })
-            .map(|task| {
+            // Hidden tasks still shadow the same named tasks of the sources with the lower precedence,
+            // so the tasks found by name are the same regardless of the hidden ones being listed.
+            .filter(|(_, task)| include_hidden || !task.hidden())
+            .map(|(kind, task)| {
                 let usages = if lru {
                     tasks_by_usage
                         .get(&task.id())
@@ -95,178 +961,3514 @@ impl Inventory {
                 } else {
                     not_used_score
                 };
-                (task, usages)
-            })
-            .sorted_unstable_by(|(task_a, usages_a), (task_b, usages_b)| {
-                usages_a.cmp(usages_b).then({
-                    NumericPrefixWithSuffix::from_numeric_prefixed_str(task_a.name())
-                        .cmp(&NumericPrefixWithSuffix::from_numeric_prefixed_str(
-                            task_b.name(),
-                        ))
-                        .then(task_a.name().cmp(task_b.name()))
-                })
+                let not_pinned = !self.pinned_tasks.contains(task.id());
+                (kind, task, (not_pinned, usages))
             })
-            .map(|(task, _)| task)
-            .collect()
+            .collect::<Vec<_>>();
+        let total = tasks.len();
+        if let Some(limit) = limit.filter(|&limit| limit < tasks.len()) {
+            // Move the first tasks to the front, leaving the rest unsorted.
+            if let Some(last_listed) = limit.checked_sub(1) {
+                tasks.select_nth_unstable_by(last_listed, compare_listed_tasks);
+            }
+            tasks.truncate(limit);
+        }
+        tasks.sort_by(compare_listed_tasks);
+        LimitedTasks {
+            tasks: tasks
+                .into_iter()
+                .map(|(kind, task, _)| (kind, task))
+                .collect(),
+            total,
+        }
     }
 
-    /// Returns the last scheduled task, if any of the sources contains one with the matching id.
-    pub fn last_scheduled_task(&self, cx: &mut AppContext) -> Option<Arc<dyn Task>> {
-        self.last_scheduled_tasks.back().and_then(|id| {
-            // TODO straighten the `Path` story to understand what has to be passed here: or it will break in the future.
-            self.list_tasks(None, false, cx)
-                .into_iter()
-                .find(|task| task.id() == id)
-        })
+    /// Same as [`Self::list_tasks`], but the tasks are sorted strictly by the time they were last scheduled, most recent first.
+    /// Tasks restored from the previous sessions go after the ones scheduled in the current one,
+    /// never scheduled tasks go last, in the [`Self::list_tasks`] order.
+    pub fn list_tasks_by_recency(
+        &self,
+        path: Option<&Path>,
+        worktree: Option<WorktreeId>,
+        cx: &mut AppContext,
+    ) -> Vec<(TaskSourceKind, Arc<dyn Task>)> {
+        let mut tasks = self.list_tasks(path, worktree, false, false, cx);
+        let last_scheduled = self
+            .last_scheduled_tasks
+            .iter()
+            .enumerate()
+            .map(|(position, scheduled_task)| {
+                (&scheduled_task.id, (scheduled_task.scheduled_at, position))
+            })
+            .collect::<HashMap<_, _>>();
+        tasks.sort_by_key(|(_, task)| Reverse(last_scheduled.get(task.id()).copied()));
+        tasks
     }
 
-    /// Registers task "usage" as being scheduled – to be used for LRU sorting when listing all tasks.
-    pub fn task_scheduled(&mut self, id: TaskId) {
-        self.last_scheduled_tasks.push_back(id);
-        if self.last_scheduled_tasks.len() > 5_000 {
-            self.last_scheduled_tasks.pop_front();
-        }
+    /// Describes every task, [`Self::list_tasks`] would list, including the hidden ones, as a JSON array, e.g. for the external tools.
+    /// Each task is described by its name, id and source, along with its command, working directory and env,
+    /// as far as the task describes them: the fields the task has no values for are omitted.
+    pub fn export_tasks(&self, cx: &mut AppContext) -> serde_json::Value {
+        let tasks = self
+            .list_tasks(None, None, false, true, cx)
+            .into_iter()
+            .map(|(source_kind, task)| {
+                let mut exported = serde_json::Map::new();
+                exported.insert("name".to_string(), task.name().into());
+                exported.insert("id".to_string(), task.id().0.clone().into());
+                let mut source = serde_json::Map::new();
+                source.insert("kind".to_string(), source_kind.kind_name().into());
+                if let Some(abs_path) = source_kind.abs_path() {
+                    source.insert(
+                        "path".to_string(),
+                        abs_path.to_string_lossy().into_owned().into(),
+                    );
+                }
+                exported.insert("source".to_string(), source.into());
+                if let Some(spawn_in_terminal) = task.exec(None) {
+                    exported.insert("command".to_string(), spawn_in_terminal.command.into());
+                    if !spawn_in_terminal.args.is_empty() {
+                        exported.insert("args".to_string(), spawn_in_terminal.args.into());
+                    }
+                    if !spawn_in_terminal.env.is_empty() {
+                        let env = spawn_in_terminal
+                            .env
+                            .into_iter()
+                            .map(|(key, value)| (key, value.into()))
+                            .collect::<serde_json::Map<_, _>>();
+                        exported.insert("env".to_string(), env.into());
+                    }
+                }
+                if let Some(cwd) = task.cwd() {
+                    exported.insert("cwd".to_string(), cwd.to_string_lossy().into_owned().into());
+                }
+                if task.hidden() {
+                    exported.insert("hidden".to_string(), true.into());
+                }
+                serde_json::Value::Object(exported)
+            })
+            .collect();
+        serde_json::Value::Array(tasks)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::path::PathBuf;
+    /// Sets the task to run by default in the worktree given, without picking it from the list, or unsets it for `None`.
+    /// The task set overrides the tasks declared as default in their definitions.
+    pub fn set_default_task(
+        &mut self,
+        worktree: Option<WorktreeId>,
+        id: Option<TaskId>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match id {
+            Some(id) => self.default_tasks.insert(worktree, id),
+            None => self.default_tasks.remove(&worktree),
+        };
+        cx.notify();
+    }
 
-    use gpui::TestAppContext;
+    /// Whether the task given is set as the default one for the worktree given, see [`Self::set_default_task`].
+    pub fn is_default_task(&self, worktree: Option<WorktreeId>, id: &TaskId) -> bool {
+        self.default_tasks.get(&worktree) == Some(id)
+    }
 
-    use super::*;
+    /// The task to run by default in the worktree given: the one set with [`Self::set_default_task`], while it is listed,
+    /// otherwise the first listed task declared as default in its definition, see [`Task::is_default`].
+    pub fn default_task(
+        &self,
+        worktree: Option<WorktreeId>,
+        cx: &mut AppContext,
+    ) -> Option<(TaskSourceKind, Arc<dyn Task>)> {
+        let tasks = self.list_tasks(None, worktree, false, true, cx);
+        let default_id = self.default_tasks.get(&worktree);
+        let set_task = tasks
+            .iter()
+            .find(|(_, task)| Some(task.id()) == default_id)
+            .cloned();
+        set_task.or_else(|| tasks.into_iter().find(|(_, task)| task.is_default()))
+    }
 
-    #[gpui::test]
-    fn test_task_list_sorting(cx: &mut TestAppContext) {
+    /// Looks up the task with the exact name given, among the tasks listed for the worktree given.
+    /// Same named tasks are resolved the same way [`Self::list_tasks`] does: the source with the highest precedence wins.
+    pub fn task_by_name(
+        &self,
+        name: &str,
+        worktree: Option<WorktreeId>,
+        cx: &mut AppContext,
+    ) -> Option<(TaskSourceKind, Arc<dyn Task>)> {
+        self.list_tasks(None, worktree, false, true, cx)
+            .into_iter()
+            .find(|(_, task)| task.name() == name)
+    }
+
+    /// Returns the last scheduled task and its source kind, if any of the sources contains one with the matching id.
+    /// The task is looked up in the source it was scheduled from, using the source's path, so it does not depend on the currently active path.
+    /// History entries that no longer match any task (e.g. restored from a previous session) are skipped.
+    pub fn last_scheduled_task(
+        &self,
+        cx: &mut AppContext,
+    ) -> Option<(TaskSourceKind, Arc<dyn Task>)> {
+        let mut listed_tasks = None::<HashMap<TaskId, (TaskSourceKind, Arc<dyn Task>)>>;
+        self.last_scheduled_tasks
+            .iter()
+            .rev()
+            .find_map(|scheduled_task| match &scheduled_task.source_kind {
+                Some(source_kind) => self.task_from_source(source_kind, &scheduled_task.id, cx),
+                None => listed_tasks
+                    .get_or_insert_with(|| {
+                        self.list_tasks(None, None, false, true, cx)
+                            .into_iter()
+                            .map(|(kind, task)| (task.id().clone(), (kind, task)))
+                            .collect()
+                    })
+                    .get(&scheduled_task.id)
+                    .cloned(),
+            })
+    }
+
+    fn task_from_source(
+        &self,
+        source_kind: &TaskSourceKind,
+        id: &TaskId,
+        cx: &mut AppContext,
+    ) -> Option<(TaskSourceKind, Arc<dyn Task>)> {
+        self.available_sources()
+            // Tasks may be attributed to other files than their sources', but never to other worktrees.
+            .filter(|source| source.kind.worktree() == source_kind.worktree())
+            .find_map(|source| {
+                source
+                    .tasks_with_kinds(source.kind.abs_path(), cx)
+                    .into_iter()
+                    .find(|(kind, task)| kind == source_kind && task.id() == id)
+            })
+    }
+
+    /// Lists the tasks that have to be run before the task given, in the order of running:
+    /// every task goes after its own dependencies, and each one is listed once.
+    /// Dependencies are looked up by name among all tasks available; unknown dependencies and cycles are reported as errors.
+    pub fn resolve_dependencies(
+        &self,
+        task: &dyn Task,
+        cx: &mut AppContext,
+    ) -> anyhow::Result<Vec<(TaskSourceKind, Arc<dyn Task>)>> {
+        let dependencies = task.dependencies();
+        if dependencies.is_empty() {
+            return Ok(Vec::new());
+        }
+        let tasks_by_name = self
+            .list_tasks(None, None, false, true, cx)
+            .into_iter()
+            .map(|(kind, task)| (task.name().to_string(), (kind, task)))
+            .collect::<HashMap<_, _>>();
+        let mut resolved = Vec::new();
+        let mut visited = HashSet::default();
+        let mut dependency_path = vec![task.name().to_string()];
+        for dependency in dependencies {
+            resolve_dependency(
+                &dependency,
+                &tasks_by_name,
+                &mut dependency_path,
+                &mut visited,
+                &mut resolved,
+            )?;
+        }
+        Ok(resolved)
+    }
+
+    /// Returns the working directory, the task given was spawned in, if it was the last scheduled task.
+    pub fn last_scheduled_cwd(&self, id: &TaskId) -> Option<&Path> {
+        self.last_scheduled_cwd
+            .as_ref()
+            .filter(|(last_id, _)| last_id == id)
+            .map(|(_, cwd)| cwd.as_path())
+    }
+
+    /// Registers task "usage" as being scheduled – to be used for LRU sorting when listing all tasks.
+    /// The updated history is persisted, to be restored in the next session.
+    pub fn task_scheduled(
+        &mut self,
+        source_kind: TaskSourceKind,
+        id: TaskId,
+        cwd: Option<PathBuf>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.last_scheduled_cwd = cwd.map(|cwd| (id.clone(), cwd));
+        self.last_scheduled_tasks.push_back(ScheduledTask {
+            id,
+            source_kind: Some(source_kind),
+            scheduled_at: Some(SystemTime::now()),
+        });
+        self.truncate_history();
+        self.serialize_history(cx);
+    }
+
+    /// Whether the task given was the last one scheduled, in the same directory, less than the scheduling cooldown ago.
+    /// Launching it again is likely an accident, e.g. a double key press.
+    pub fn is_recently_scheduled(&self, id: &TaskId, cwd: Option<&Path>) -> bool {
+        let Some(last_scheduled) = self.last_scheduled_tasks.back() else {
+            return false;
+        };
+        if &last_scheduled.id != id || self.last_scheduled_cwd(id) != cwd {
+            return false;
+        }
+        last_scheduled
+            .scheduled_at
+            .and_then(|scheduled_at| scheduled_at.elapsed().ok())
+            .is_some_and(|elapsed| elapsed < self.scheduling_cooldown)
+    }
+
+    /// Sets the time after scheduling a task, during which its repeated launches in the same directory are considered accidental.
+    pub fn set_scheduling_cooldown(&mut self, scheduling_cooldown: Duration) {
+        self.scheduling_cooldown = scheduling_cooldown;
+    }
+
+    /// Sets whether the worktree tasks are listed instead of the same named tasks of the other sources, which is the default,
+    /// or the other way around, e.g. for the users keeping their own variants of the project tasks globally.
+    pub fn set_prefer_worktree_tasks(
+        &mut self,
+        prefer_worktree_tasks: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self.prefer_worktree_tasks != prefer_worktree_tasks {
+            self.prefer_worktree_tasks = prefer_worktree_tasks;
+            cx.notify();
+        }
+    }
+
+    /// Removes all usages of the task given from the history, so it is listed as if it was never scheduled.
+    /// The updated history is persisted, to be restored in the next session.
+    pub fn forget_task(&mut self, id: &TaskId, cx: &mut ModelContext<Self>) {
+        self.last_scheduled_tasks
+            .retain(|scheduled_task| &scheduled_task.id != id);
+        self.last_runs.remove(id);
+        if self
+            .last_scheduled_cwd
+            .as_ref()
+            .is_some_and(|(last_id, _)| last_id == id)
+        {
+            self.last_scheduled_cwd = None;
+        }
+        self.serialize_history(cx);
+        cx.notify();
+    }
+
+    /// Removes all tasks from the history, so the tasks are listed as if none was ever scheduled.
+    /// The persisted history is cleared too.
+    pub fn clear_history(&mut self, cx: &mut ModelContext<Self>) {
+        self.last_scheduled_tasks.clear();
+        self.last_runs.clear();
+        self.last_scheduled_cwd = None;
+        self.serialize_history(cx);
+        cx.notify();
+    }
+
+    /// Pins the task given to the top of the task list, or unpins it if it was pinned already.
+    /// The pinned tasks are persisted along with the history, to be restored in the next session.
+    pub fn toggle_pinned(&mut self, id: TaskId, cx: &mut ModelContext<Self>) {
+        if !self.pinned_tasks.remove(&id) {
+            self.pinned_tasks.insert(id);
+        }
+        if let Some(scope) = self.persistence_scope.as_deref() {
+            let pins_key = scoped_key(PINNED_TASKS_KEY, scope);
+            let pins = self.pinned_tasks.iter().cloned().collect::<Vec<_>>();
+            self.pending_pins_serialization = cx.background_executor().spawn(
+                async move {
+                    KEY_VALUE_STORE
+                        .write_kvp(pins_key, serde_json::to_string(&pins)?)
+                        .await?;
+                    anyhow::Ok(())
+                }
+                .log_err(),
+            );
+        }
+        cx.notify();
+    }
+
+    /// Whether the task given is pinned to the top of the task list.
+    pub fn is_pinned(&self, id: &TaskId) -> bool {
+        self.pinned_tasks.contains(id)
+    }
+
+    /// Bonuses to the fuzzy match scores of the pinned and, if `lru` is set, the recently scheduled tasks,
+    /// for those to rank above the other tasks, matching the query equally well. See [`usage_blended_score`].
+    /// Pinned tasks get the highest bonus, the recent ones get the lower bonuses, the earlier they were scheduled.
+    /// Tasks neither pinned nor scheduled get no bonus and are left out.
+    pub fn usage_bonuses(&self, lru: bool) -> HashMap<TaskId, f64> {
+        let mut bonuses = HashMap::default();
+        if lru {
+            let mut recency_rank = 0_u32;
+            for scheduled_task in self.last_scheduled_tasks.iter().rev() {
+                bonuses.entry(scheduled_task.id.clone()).or_insert_with(|| {
+                    RECENT_TASK_BONUS / f64::from(post_inc(&mut recency_rank) + 1)
+                });
+            }
+        }
+        for id in &self.pinned_tasks {
+            bonuses.insert(id.clone(), PINNED_TASK_BONUS);
+        }
+        bonuses
+    }
+
+    /// Registers a new instance of the task given, started in a terminal.
+    pub fn task_started(&mut self, id: TaskId, cx: &mut ModelContext<Self>) {
+        *self.running_tasks.entry(id).or_default() += 1;
+        cx.notify();
+    }
+
+    /// Deregisters an instance of the task given, after its process exited or its terminal was closed.
+    pub fn task_finished(&mut self, id: &TaskId, cx: &mut ModelContext<Self>) {
+        if let Some(instances) = self.running_tasks.get_mut(id) {
+            *instances -= 1;
+            if *instances == 0 {
+                self.running_tasks.remove(id);
+            }
+            cx.notify();
+        }
+    }
+
+    /// Whether any instance of the task given is currently running.
+    pub fn is_running(&self, id: &TaskId) -> bool {
+        self.running_tasks.contains_key(id)
+    }
+
+    /// Records how an instance of the task given, started at the time given, has exited.
+    /// Tasks to retry on failure are asked to be spawned again with [`InventoryEvent::RetryTask`],
+    /// if they have exited with a non-zero code and have retries left.
+    pub fn task_exited(
+        &mut self,
+        id: TaskId,
+        exit_status: TaskExitStatus,
+        started_at: SystemTime,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let retry_attempt =
+            match exit_status {
+                TaskExitStatus::Failure(Some(_)) => self.pending_retries.get_mut(&id).and_then(
+                    |(spawn_in_terminal, retries_made)| {
+                        (*retries_made < spawn_in_terminal.retries).then(|| {
+                            *retries_made += 1;
+                            *retries_made
+                        })
+                    },
+                ),
+                _ => None,
+            };
+        if retry_attempt.is_none() {
+            self.pending_retries.remove(&id);
+        }
+        if let Some(attempt) = retry_attempt {
+            cx.emit(InventoryEvent::RetryTask {
+                id: id.clone(),
+                attempt,
+            });
+        }
+        let duration = started_at.elapsed().unwrap_or_default();
+        self.last_runs.insert(
+            id,
+            TaskRun {
+                started_at,
+                duration,
+                exit_status,
+            },
+        );
+        cx.notify();
+    }
+
+    /// The last finished run of the task given, if any has finished with a known status.
+    /// Runs of the tasks, evicted from the history, are not remembered.
+    pub fn last_run(&self, id: &TaskId) -> Option<TaskRun> {
+        self.last_runs.get(id).copied()
+    }
+
+    /// How the last finished instance of the task given has exited, if any has finished with a known status.
+    pub fn last_exit_status(&self, id: &TaskId) -> Option<TaskExitStatus> {
+        self.last_run(id).map(|run| run.exit_status)
+    }
+
+    /// Remembers the task spawned, to spawn it again if it fails, up to its [`SpawnInTerminal::retries`] times.
+    /// Spawning the task anew starts counting its retries over.
+    pub fn retry_on_failure(&mut self, spawn_in_terminal: SpawnInTerminal) {
+        if spawn_in_terminal.retries == 0 {
+            self.pending_retries.remove(&spawn_in_terminal.id);
+        } else {
+            self.pending_retries
+                .insert(spawn_in_terminal.id.clone(), (spawn_in_terminal, 0));
+        }
+    }
+
+    /// The task to spawn again, after [`InventoryEvent::RetryTask`] was emitted for it.
+    pub fn task_to_retry(&self, id: &TaskId) -> Option<SpawnInTerminal> {
+        let (spawn_in_terminal, _) = self.pending_retries.get(id)?;
+        Some(spawn_in_terminal.clone())
+    }
+
+    /// Starts watching the task spawned, to rerun it on the saves of the project files, until [`Self::unwatch_task`] is called.
+    /// Watching the task again replaces the command to rerun, e.g. with the newly substituted variables.
+    /// Returns whether the task was not watched before.
+    pub fn watch_task(
+        &mut self,
+        spawn_in_terminal: SpawnInTerminal,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        let newly_watched = self
+            .watched_tasks
+            .insert(spawn_in_terminal.id.clone(), spawn_in_terminal)
+            .is_none();
+        cx.notify();
+        newly_watched
+    }
+
+    /// Stops rerunning the task given on the saves, returns whether it was watched.
+    pub fn unwatch_task(&mut self, id: &TaskId, cx: &mut ModelContext<Self>) -> bool {
+        let watched = self.watched_tasks.remove(id).is_some();
+        if watched {
+            cx.notify();
+        }
+        watched
+    }
+
+    /// Stops watching every task, returns the number of the tasks that were watched.
+    pub fn unwatch_all(&mut self, cx: &mut ModelContext<Self>) -> usize {
+        let watched = self.watched_tasks.len();
+        self.watched_tasks.clear();
+        if watched > 0 {
+            cx.notify();
+        }
+        watched
+    }
+
+    /// Whether the task given is rerun on the saves of the project files.
+    pub fn is_watched(&self, id: &TaskId) -> bool {
+        self.watched_tasks.contains_key(id)
+    }
+
+    /// Whether any task is rerun on the saves of the project files.
+    pub fn has_watched_tasks(&self) -> bool {
+        !self.watched_tasks.is_empty()
+    }
+
+    /// Tasks to rerun after the project files are saved, ordered by their labels.
+    pub fn watched_tasks(&self) -> Vec<SpawnInTerminal> {
+        let mut watched_tasks = self.watched_tasks.values().cloned().collect::<Vec<_>>();
+        watched_tasks.sort_by(|a, b| a.label.cmp(&b.label));
+        watched_tasks
+    }
+
+    /// The last query, the tasks were looked up by, empty if there was none.
+    pub fn last_query(&self) -> &str {
+        &self.last_query
+    }
+
+    /// Remembers the query the tasks were looked up by, to start the next lookup with.
+    pub fn set_last_query(&mut self, query: String) {
+        self.last_query = query;
+    }
+
+    /// Sets the maximum amount of scheduled tasks remembered for LRU sorting, evicting the oldest entries if needed.
+    pub fn set_max_history(&mut self, max_history: usize) {
+        self.max_history = max_history;
+        self.truncate_history();
+    }
+
+    fn truncate_history(&mut self) {
+        let mut evicted = false;
+        while self.last_scheduled_tasks.len() > self.max_history {
+            self.last_scheduled_tasks.pop_front();
+            evicted = true;
+        }
+        if evicted {
+            let scheduled_ids = self
+                .last_scheduled_tasks
+                .iter()
+                .map(|scheduled_task| &scheduled_task.id)
+                .collect::<HashSet<_>>();
+            self.last_runs.retain(|id, _| scheduled_ids.contains(id));
+        }
+    }
+
+    /// Drops the restored history ids, that none of the sources has, once all sources have loaded their tasks.
+    /// Sources with their tasks files missing count as loaded, the ones with errors postpone the pruning,
+    /// as their tasks may be listed again after the errors are fixed.
+    /// The tasks of the disabled sources are kept.
+    fn prune_restored_history(&mut self, cx: &mut AppContext) {
+        if !self.pending_history_pruning {
+            return;
+        }
+        if !self.sources.iter().all(|source| source.is_loaded(cx)) {
+            return;
+        }
+        self.pending_history_pruning = false;
+        let known_ids = self
+            .sources
+            .iter()
+            .flat_map(|source| source.tasks_with_origins(None, cx))
+            .map(|(_, task)| task.id().clone())
+            .collect::<HashSet<_>>();
+        self.last_scheduled_tasks.retain(|scheduled_task| {
+            scheduled_task.source_kind.is_some() || known_ids.contains(&scheduled_task.id)
+        });
+    }
+
+    /// Prepends the history from the previous session to the current one.
+    fn restore_history(&mut self, history: Vec<TaskId>) {
+        let current_history = std::mem::take(&mut self.last_scheduled_tasks);
+        self.last_scheduled_tasks = history
+            .into_iter()
+            .map(|id| ScheduledTask {
+                id,
+                source_kind: None,
+                scheduled_at: None,
+            })
+            .chain(current_history)
+            .collect();
+        self.pending_history_pruning = true;
+        self.truncate_history();
+    }
+
+    /// Stores the history under the persistence scope, if it is set.
+    fn serialize_history(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(scope) = self.persistence_scope.as_deref() else {
+            return;
+        };
+        let history_key = scoped_key(TASK_HISTORY_KEY, scope);
+        let history = self
+            .last_scheduled_tasks
+            .iter()
+            .map(|scheduled_task| scheduled_task.id.clone())
+            .collect::<Vec<_>>();
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(history_key, serde_json::to_string(&history)?)
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+}
+
+/// The key to persist the value of the key given under, for the scope given.
+fn scoped_key(key: &str, scope: &str) -> String {
+    format!("{key}:{scope}")
+}
+
+/// Finds the files with the name given in the worktree, for the sources that define their tasks in many files across it.
+/// The worktree's ignored entries (e.g. the `.gitignore`d `node_modules`) and the entries outside of it are skipped,
+/// so the dependencies' files do not add their tasks.
+pub fn discover_task_files(worktree: &Snapshot, file_name: &str) -> Vec<PathBuf> {
+    worktree
+        .files(false, 0)
+        .filter(|entry| {
+            !entry.is_external && entry.path.file_name() == Some(std::ffi::OsStr::new(file_name))
+        })
+        .map(|entry| worktree.abs_path().join(&entry.path))
+        .collect()
+}
+
+/// The fuzzy match score of a task, raised by its usage bonus from [`Inventory::usage_bonuses`] times the weight given.
+/// With any positive weight, of the tasks matching equally well, the pinned and the recently used ones score higher.
+pub fn usage_blended_score(match_score: f64, usage_bonus: f64, weight: f64) -> f64 {
+    match_score + usage_bonus * weight.max(0.0)
+}
+
+/// Groups the items by their source kinds, for displaying them in sections.
+/// Groups are ordered by their first items, and items keep their relative order within each group.
+pub fn group_by_source_kind<T>(
+    items: impl IntoIterator<Item = (TaskSourceKind, T)>,
+) -> Vec<(TaskSourceKind, Vec<T>)> {
+    let mut groups = Vec::<(TaskSourceKind, Vec<T>)>::new();
+    let mut group_indices = HashMap::default();
+    for (kind, item) in items {
+        let group_ix = *group_indices.entry(kind.clone()).or_insert_with(|| {
+            groups.push((kind, Vec::new()));
+            groups.len() - 1
+        });
+        groups[group_ix].1.push(item);
+    }
+    groups
+}
+
+fn resolve_dependency(
+    name: &str,
+    tasks_by_name: &HashMap<String, (TaskSourceKind, Arc<dyn Task>)>,
+    dependency_path: &mut Vec<String>,
+    visited: &mut HashSet<String>,
+    resolved: &mut Vec<(TaskSourceKind, Arc<dyn Task>)>,
+) -> anyhow::Result<()> {
+    if dependency_path.iter().any(|dependent| dependent == name) {
+        dependency_path.push(name.to_string());
+        anyhow::bail!("Task dependency cycle: {}", dependency_path.join(" -> "));
+    }
+    if !visited.insert(name.to_string()) {
+        return Ok(());
+    }
+    let (kind, task) = tasks_by_name.get(name).with_context(|| {
+        format!(
+            "Task {:?} depends on an unknown task {name:?}",
+            dependency_path
+                .last()
+                .map(String::as_str)
+                .unwrap_or_default()
+        )
+    })?;
+    dependency_path.push(name.to_string());
+    for dependency in task.dependencies() {
+        resolve_dependency(
+            &dependency,
+            tasks_by_name,
+            dependency_path,
+            visited,
+            resolved,
+        )?;
+    }
+    dependency_path.pop();
+    resolved.push((kind.clone(), task.clone()));
+    Ok(())
+}
+
+/// A task being listed, along with its source and its usage: whether it is not pinned and how recently it was used.
+type ListedTask = (TaskSourceKind, Arc<dyn Task>, (bool, u32));
+
+/// Keeps the sources of the worktree given, if any, and the sources not tied to any worktree.
+fn worktree_filter(worktree: Option<WorktreeId>) -> impl Fn(&TaskSourceKind) -> bool {
+    move |kind| {
+        let source_worktree = kind.worktree();
+        worktree.is_none() || source_worktree.is_none() || source_worktree == worktree
+    }
+}
+
+fn compare_listed_tasks(
+    (kind_a, task_a, usages_a): &ListedTask,
+    (kind_b, task_b, usages_b): &ListedTask,
+) -> cmp::Ordering {
+    let order_a = task_a.order();
+    let order_b = task_b.order();
+    usages_a
+        .cmp(usages_b)
+        .then((order_a.is_none(), order_a).cmp(&(order_b.is_none(), order_b)))
+        .then_with(|| match (task_a.source_order(), task_b.source_order()) {
+            (None, None) => compare_task_names(task_a.as_ref(), task_b.as_ref()),
+            (None, Some(_)) => cmp::Ordering::Less,
+            (Some(_), None) => cmp::Ordering::Greater,
+            // Tasks of the sources with a natural order are grouped by their sources, to keep that order.
+            (Some(source_order_a), Some(source_order_b)) => (kind_a.abs_path(), kind_a.worktree())
+                .cmp(&(kind_b.abs_path(), kind_b.worktree()))
+                .then(source_order_a.cmp(&source_order_b))
+                .then_with(|| compare_task_names(task_a.as_ref(), task_b.as_ref())),
+        })
+        // Same named tasks from different sources are ordered by their sources, to pick the same duplicate every time.
+        .then_with(|| kind_a.abs_path().cmp(&kind_b.abs_path()))
+        .then_with(|| kind_a.worktree().cmp(&kind_b.worktree()))
+}
+
+fn compare_task_names(task_a: &dyn Task, task_b: &dyn Task) -> cmp::Ordering {
+    NumericPrefixWithSuffix::from_numeric_prefixed_str(task_a.name())
+        .cmp(&NumericPrefixWithSuffix::from_numeric_prefixed_str(
+            task_b.name(),
+        ))
+        // Names that differ in case only are listed next to each other.
+        .then_with(|| UniCase::new(task_a.name()).cmp(&UniCase::new(task_b.name())))
+        .then(task_a.name().cmp(task_b.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, path::PathBuf, rc::Rc, time::Duration};
+
+    use gpui::TestAppContext;
+    use task::static_source::StaticSource;
+
+    use crate::FakeFs;
+
+    use super::*;
+
+    #[gpui::test]
+    fn test_task_list_sorting(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let initial_tasks = list_task_names(&inventory, None, true, cx);
+        assert!(
+            initial_tasks.is_empty(),
+            "No tasks expected for empty inventory, but got {initial_tasks:?}"
+        );
+        let initial_tasks = list_task_names(&inventory, None, false, cx);
+        assert!(
+            initial_tasks.is_empty(),
+            "No tasks expected for empty inventory, but got {initial_tasks:?}"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["3_task".to_string()], cx),
+                cx,
+            );
+        });
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    vec![
+                        "1_task".to_string(),
+                        "2_task".to_string(),
+                        "1_a_task".to_string(),
+                    ],
+                    cx,
+                ),
+                cx,
+            );
+        });
+
+        let expected_initial_state = [
+            "1_a_task".to_string(),
+            "1_task".to_string(),
+            "2_task".to_string(),
+            "3_task".to_string(),
+        ];
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            &expected_initial_state,
+            "Task list without lru sorting, should be sorted alphanumerically"
+        );
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            &expected_initial_state,
+            "Tasks with equal amount of usages should be sorted alphanumerically"
+        );
+
+        register_task_used(&inventory, "2_task", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            &expected_initial_state,
+            "Task list without lru sorting, should be sorted alphanumerically"
+        );
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "2_task".to_string(),
+                "1_a_task".to_string(),
+                "1_task".to_string(),
+                "3_task".to_string()
+            ],
+        );
+
+        register_task_used(&inventory, "1_task", cx);
+        register_task_used(&inventory, "1_task", cx);
+        register_task_used(&inventory, "1_task", cx);
+        register_task_used(&inventory, "3_task", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            &expected_initial_state,
+            "Task list without lru sorting, should be sorted alphanumerically"
+        );
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "3_task".to_string(),
+                "1_task".to_string(),
+                "2_task".to_string(),
+                "1_a_task".to_string(),
+            ],
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["10_hello".to_string(), "11_hello".to_string()], cx),
+                cx,
+            );
+        });
+        let expected_updated_state = [
+            "1_a_task".to_string(),
+            "1_task".to_string(),
+            "2_task".to_string(),
+            "3_task".to_string(),
+            "10_hello".to_string(),
+            "11_hello".to_string(),
+        ];
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            &expected_updated_state,
+            "Task list without lru sorting, should be sorted alphanumerically"
+        );
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "3_task".to_string(),
+                "1_task".to_string(),
+                "2_task".to_string(),
+                "1_a_task".to_string(),
+                "10_hello".to_string(),
+                "11_hello".to_string(),
+            ],
+        );
+
+        register_task_used(&inventory, "11_hello", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            &expected_updated_state,
+            "Task list without lru sorting, should be sorted alphanumerically"
+        );
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "11_hello".to_string(),
+                "3_task".to_string(),
+                "1_task".to_string(),
+                "2_task".to_string(),
+                "1_a_task".to_string(),
+                "10_hello".to_string(),
+            ],
+        );
+    }
+
+    #[gpui::test]
+    fn test_restored_history(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["1_task".to_string(), "2_task".to_string()], cx),
+                cx,
+            );
+        });
+        register_task_used(&inventory, "2_task", cx);
+
+        inventory.update(cx, |inventory, _| {
+            inventory.restore_history(vec![
+                TaskId("task_0_1_task".to_string()),
+                TaskId("removed_task".to_string()),
+            ]);
+        });
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec!["2_task".to_string(), "1_task".to_string()],
+            "Restored history should precede the current session's one"
+        );
+
+        inventory.update(cx, |inventory, _| {
+            inventory.restore_history(vec![TaskId("removed_task".to_string())]);
+            inventory.last_scheduled_tasks.push_back(ScheduledTask {
+                id: TaskId("another_removed_task".to_string()),
+                source_kind: Some(TaskSourceKind::UserInput),
+                scheduled_at: Some(SystemTime::now()),
+            });
+        });
+        let last_task = inventory.update(cx, |inventory, cx| {
+            inventory
+                .last_scheduled_task(cx)
+                .map(|(_, task)| task.name().to_string())
+        });
+        assert_eq!(
+            last_task.as_deref(),
+            Some("2_task"),
+            "Unknown ids from the history should be skipped"
+        );
+    }
+
+    #[gpui::test]
+    fn test_last_scheduled_task_lookup_uses_its_source(cx: &mut TestAppContext) {
+        let worktree_tasks_path = PathBuf::from("/worktree/.zed/tasks.json");
+        let worktree_source_kind = TaskSourceKind::Worktree {
+            id: WorktreeId::from_usize(0),
+            abs_path: worktree_tasks_path.clone(),
+        };
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["global_task".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                worktree_source_kind.clone(),
+                TestSource::new_scoped(
+                    vec!["worktree_task".to_string()],
+                    Some(worktree_tasks_path.clone()),
+                    cx,
+                ),
+                cx,
+            );
+        });
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["global_task".to_string()],
+            "Worktree task should not be listed for an unrelated path"
+        );
+
+        register_task_used(&inventory, "global_task", cx);
+        inventory.update(cx, |inventory, cx| {
+            inventory.task_scheduled(
+                worktree_source_kind.clone(),
+                TaskId("task_0_worktree_task".to_string()),
+                None,
+                cx,
+            );
+        });
+        let last_task = inventory.update(cx, |inventory, cx| {
+            inventory
+                .last_scheduled_task(cx)
+                .map(|(kind, task)| (kind, task.name().to_string()))
+        });
+        assert_eq!(
+            last_task,
+            Some((worktree_source_kind, "worktree_task".to_string())),
+            "Last scheduled task should be found in its source, regardless of the active path"
+        );
+    }
+
+    #[gpui::test]
+    fn test_listing_tasks_by_recency(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    vec![
+                        "recent_build".to_string(),
+                        "recent_deploy".to_string(),
+                        "recent_lint".to_string(),
+                        "recent_test".to_string(),
+                    ],
+                    cx,
+                ),
+                cx,
+            );
+            inventory.restore_history(vec![TaskId("task_2_recent_lint".to_string())]);
+        });
+        register_task_used(&inventory, "recent_test", cx);
+        register_task_used(&inventory, "recent_build", cx);
+        register_task_used(&inventory, "recent_test", cx);
+
+        let task_names_by_recency = inventory.update(cx, |inventory, cx| {
+            inventory
+                .list_tasks_by_recency(None, None, cx)
+                .into_iter()
+                .map(|(_, task)| task.name().to_string())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            task_names_by_recency,
+            vec![
+                "recent_test".to_string(),
+                "recent_build".to_string(),
+                "recent_lint".to_string(),
+                "recent_deploy".to_string(),
+            ],
+            "Tasks should be sorted by their last run, restored ones after the current session's"
+        );
+    }
+
+    #[gpui::test]
+    fn test_history_limit(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_max_history(2);
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    vec![
+                        "1_task".to_string(),
+                        "2_task".to_string(),
+                        "3_task".to_string(),
+                    ],
+                    cx,
+                ),
+                cx,
+            );
+        });
+
+        register_task_used(&inventory, "3_task", cx);
+        register_task_used(&inventory, "2_task", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "2_task".to_string(),
+                "3_task".to_string(),
+                "1_task".to_string(),
+            ],
+        );
+
+        register_task_used(&inventory, "1_task", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "1_task".to_string(),
+                "2_task".to_string(),
+                "3_task".to_string(),
+            ],
+            "The oldest task should be evicted from the history, falling back to alphanumeric sorting"
+        );
+
+        register_task_used(&inventory, "1_task", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "1_task".to_string(),
+                "2_task".to_string(),
+                "3_task".to_string(),
+            ],
+        );
+        inventory.update(cx, |inventory, _| inventory.set_max_history(1));
+        register_task_used(&inventory, "3_task", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "3_task".to_string(),
+                "1_task".to_string(),
+                "2_task".to_string(),
+            ],
+            "Shrinking the limit should evict the history accordingly"
+        );
+    }
+
+    #[gpui::test]
+    fn test_listing_limited_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    (1..=20)
+                        .rev()
+                        .map(|i| format!("{i}_task"))
+                        .collect::<Vec<_>>(),
+                    cx,
+                ),
+                cx,
+            );
+        });
+        register_task_used(&inventory, "17_task", cx);
+        register_task_used(&inventory, "5_task", cx);
+
+        let all_tasks = list_task_names(&inventory, None, true, cx);
+        let limited_task_names = |limit: usize, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                inventory
+                    .list_tasks_limited(None, None, true, limit, cx)
+                    .tasks
+                    .into_iter()
+                    .map(|(_, task)| task.name().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            limited_task_names(4, cx),
+            vec![
+                "5_task".to_string(),
+                "17_task".to_string(),
+                "1_task".to_string(),
+                "2_task".to_string(),
+            ],
+            "Limited list should contain the first tasks of the full list, in the same order"
+        );
+        assert_eq!(limited_task_names(4, cx), all_tasks[..4]);
+        assert_eq!(limited_task_names(100, cx), all_tasks);
+        assert!(limited_task_names(0, cx).is_empty());
+
+        let truncation = |limit: usize, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let limited_tasks = inventory.list_tasks_limited(None, None, true, limit, cx);
+                (limited_tasks.is_truncated(), limited_tasks.total)
+            })
+        };
+        assert_eq!(
+            truncation(4, cx),
+            (true, 20),
+            "Limited list should report the number of all tasks"
+        );
+        assert_eq!(truncation(20, cx), (false, 20));
+        assert_eq!(truncation(100, cx), (false, 20));
+    }
+
+    #[gpui::test]
+    fn test_mixed_case_task_sorting(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    vec![
+                        "build".to_string(),
+                        "Test".to_string(),
+                        "Build".to_string(),
+                        "deploy".to_string(),
+                        "Éclair".to_string(),
+                        "éclair".to_string(),
+                        "2_Lint".to_string(),
+                        "10_lint".to_string(),
+                        "2_format".to_string(),
+                    ],
+                    cx,
+                ),
+                cx,
+            );
+        });
+
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec![
+                "Build".to_string(),
+                "build".to_string(),
+                "deploy".to_string(),
+                "Test".to_string(),
+                "Éclair".to_string(),
+                "éclair".to_string(),
+                "2_format".to_string(),
+                "2_Lint".to_string(),
+                "10_lint".to_string(),
+            ],
+            "Numeric prefixes should be compared first, then the names case-insensitively, then case-sensitively"
+        );
+    }
+
+    #[gpui::test]
+    fn test_same_named_tasks_sorting(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree_1 = WorktreeId::from_usize(1);
+        let worktree_2 = WorktreeId::from_usize(2);
+        inventory.update(cx, |inventory, cx| {
+            for kind in [
+                TaskSourceKind::Worktree {
+                    id: worktree_2,
+                    abs_path: PathBuf::from("/project/.zed/tasks.json"),
+                },
+                TaskSourceKind::Worktree {
+                    id: worktree_1,
+                    abs_path: PathBuf::from("/project/.zed/tasks.json"),
+                },
+                TaskSourceKind::Worktree {
+                    id: worktree_1,
+                    abs_path: PathBuf::from("/another_project/.zed/tasks.json"),
+                },
+            ] {
+                inventory.add_source(kind, TestSource::new(vec!["build".to_string()], cx), cx);
+            }
+        });
+
+        let listed_kinds = inventory.update(cx, |inventory, cx| {
+            inventory
+                .list_tasks(None, None, true, false, cx)
+                .into_iter()
+                .map(|(kind, _)| kind)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            listed_kinds,
+            vec![
+                TaskSourceKind::Worktree {
+                    id: worktree_1,
+                    abs_path: PathBuf::from("/another_project/.zed/tasks.json"),
+                },
+                TaskSourceKind::Worktree {
+                    id: worktree_1,
+                    abs_path: PathBuf::from("/project/.zed/tasks.json"),
+                },
+                TaskSourceKind::Worktree {
+                    id: worktree_2,
+                    abs_path: PathBuf::from("/project/.zed/tasks.json"),
+                },
+            ],
+            "Tasks with equal names should be ordered by their source paths, then by their worktrees"
+        );
+    }
+
+    #[gpui::test]
+    fn test_source_ordered_tasks_sorting(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/project/Makefile")),
+                TestSource::new_ordered(
+                    vec!["make test".to_string(), "make build".to_string()],
+                    cx,
+                ),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/project/justfile")),
+                TestSource::new_ordered(vec!["just lint".to_string(), "just fmt".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["zz".to_string(), "aa".to_string()], cx),
+                cx,
+            );
+        });
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "aa".to_string(),
+                "zz".to_string(),
+                "make test".to_string(),
+                "make build".to_string(),
+                "just lint".to_string(),
+                "just fmt".to_string(),
+            ],
+            "Tasks without a source order should be sorted alphanumerically, followed by the others, grouped by source in their source order"
+        );
+
+        register_task_used(&inventory, "make build", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "make build".to_string(),
+                "aa".to_string(),
+                "zz".to_string(),
+                "make test".to_string(),
+                "just lint".to_string(),
+                "just fmt".to_string(),
+            ],
+            "Used tasks should still be listed first"
+        );
+    }
+
+    #[gpui::test]
+    fn test_tasks_attributed_to_their_files(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree = WorktreeId::from_usize(1);
+        let source_kind = TaskSourceKind::Worktree {
+            id: worktree,
+            abs_path: PathBuf::from("/project"),
+        };
+        let api_kind = TaskSourceKind::Worktree {
+            id: worktree,
+            abs_path: PathBuf::from("/project/api/Makefile"),
+        };
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                source_kind.clone(),
+                DiscoveringTestSource::new(
+                    [("build", Some("/project/api/Makefile")), ("test", None)],
+                    cx,
+                ),
+                cx,
+            );
+        });
+
+        let listed = inventory.update(cx, |inventory, cx| {
+            inventory
+                .list_tasks(None, Some(worktree), false, false, cx)
+                .into_iter()
+                .map(|(kind, task)| (kind, task.name().to_string()))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            listed,
+            vec![
+                (api_kind.clone(), "build".to_string()),
+                (source_kind.clone(), "test".to_string()),
+            ],
+            "Tasks with a file of their own should be attributed to it, the rest to their source"
+        );
+
+        register_task_used(&inventory, "build", cx);
+        let last_scheduled = inventory.update(cx, |inventory, cx| {
+            inventory
+                .last_scheduled_task(cx)
+                .map(|(kind, task)| (kind, task.name().to_string()))
+        });
+        assert_eq!(
+            last_scheduled,
+            Some((api_kind, "build".to_string())),
+            "Attributed tasks should be found again by their attributed kinds"
+        );
+    }
+
+    #[gpui::test]
+    fn test_failing_source(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["build".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                cx.new_model(|_| Box::new(FailingTestSource) as Box<dyn TaskSource>),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/other_tasks.json")),
+                TestSource::new(vec!["test".to_string()], cx),
+                cx,
+            );
+        });
+
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["build".to_string(), "test".to_string()],
+            "A failing source should not prevent other sources' tasks from being listed"
+        );
+        register_task_used(&inventory, "test", cx);
+        inventory.update(cx, |inventory, cx| {
+            assert_eq!(inventory.task_count(cx), 2);
+            assert_eq!(
+                inventory
+                    .last_scheduled_task(cx)
+                    .map(|(_, task)| task.name().to_string()),
+                Some("test".to_string()),
+                "Tasks should still be looked up after the failure"
+            );
+            assert_eq!(
+                inventory
+                    .source_errors(cx)
+                    .into_iter()
+                    .map(|error| (error.kind, error.message))
+                    .collect::<Vec<_>>(),
+                vec![(
+                    TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                    "Failed to list tasks".to_string()
+                )],
+                "The failure should be shown as the source error"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_default_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree_1 = WorktreeId::from_usize(1);
+        let worktree_2 = WorktreeId::from_usize(2);
+        inventory.update(cx, |inventory, cx| {
+            for (worktree, task_names) in [
+                (worktree_1, vec!["build".to_string(), "test".to_string()]),
+                (worktree_2, vec!["lint".to_string()]),
+            ] {
+                inventory.add_source(
+                    TaskSourceKind::Worktree {
+                        id: worktree,
+                        abs_path: PathBuf::from(format!("/project_{worktree:?}/tasks.json")),
+                    },
+                    TestSource::new(task_names, cx),
+                    cx,
+                );
+            }
+        });
+        let default_task_name = |worktree: Option<WorktreeId>, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let (_, task) = inventory.default_task(worktree, cx)?;
+                Some(task.name().to_string())
+            })
+        };
+        assert_eq!(default_task_name(Some(worktree_1), cx), None);
+
+        let test_id = inventory.update(cx, |inventory, cx| {
+            let (_, task) = inventory
+                .task_by_name("test", Some(worktree_1), cx)
+                .unwrap();
+            task.id().clone()
+        });
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_default_task(Some(worktree_1), Some(test_id.clone()), cx);
+        });
+        assert_eq!(
+            default_task_name(Some(worktree_1), cx),
+            Some("test".to_string())
+        );
+        assert_eq!(
+            default_task_name(Some(worktree_2), cx),
+            None,
+            "Default tasks should be set per worktree"
+        );
+        inventory.update(cx, |inventory, _| {
+            assert!(inventory.is_default_task(Some(worktree_1), &test_id));
+            assert!(!inventory.is_default_task(Some(worktree_2), &test_id));
+        });
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_default_task(Some(worktree_1), None, cx);
+        });
+        assert_eq!(default_task_name(Some(worktree_1), cx), None);
+    }
+
+    #[gpui::test]
+    fn test_listing_tasks_for_kind(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree_1 = WorktreeId::from_usize(1);
+        let worktree_2 = WorktreeId::from_usize(2);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string(), "build".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::Worktree {
+                    id: worktree_1,
+                    abs_path: PathBuf::from("/worktree_1/.zed/tasks.json"),
+                },
+                TestSource::new(vec!["test".to_string(), "build".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::Worktree {
+                    id: worktree_2,
+                    abs_path: PathBuf::from("/worktree_2/.zed/tasks.json"),
+                },
+                TestSource::new(vec!["lint".to_string()], cx),
+                cx,
+            );
+        });
+        register_task_used(&inventory, "echo", cx);
+
+        let list_names = |kind_predicate: fn(&TaskSourceKind) -> bool, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                inventory
+                    .list_tasks_for_kind(None, true, kind_predicate, cx)
+                    .into_iter()
+                    .map(|(_, task)| task.name().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            list_names(|kind| kind == &TaskSourceKind::UserInput, cx),
+            vec!["echo".to_string(), "build".to_string()],
+            "Tasks of the user input source should not be shadowed by the worktree ones filtered out, \
+            and should keep the usage-based sorting"
+        );
+        assert_eq!(
+            list_names(
+                |kind| kind.worktree() == Some(WorktreeId::from_usize(1)),
+                cx
+            ),
+            vec!["build".to_string(), "test".to_string()],
+        );
+        assert_eq!(
+            list_names(|kind| kind.worktree().is_some(), cx),
+            vec!["build".to_string(), "lint".to_string(), "test".to_string()],
+        );
+    }
+
+    #[gpui::test]
+    fn test_scheduling_cooldown(cx: &mut TestAppContext) {
+        let cooldown = Duration::from_millis(50);
+        let inventory = cx.update(Inventory::new);
+        let build = TaskId("build".to_string());
+        let test = TaskId("test".to_string());
+        let project_dir = Path::new("/project");
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_scheduling_cooldown(cooldown);
+            assert!(!inventory.is_recently_scheduled(&build, Some(project_dir)));
+
+            inventory.task_scheduled(
+                TaskSourceKind::UserInput,
+                build.clone(),
+                Some(project_dir.to_path_buf()),
+                cx,
+            );
+            assert!(
+                inventory.is_recently_scheduled(&build, Some(project_dir)),
+                "Same task in the same directory should be in the cooldown right after scheduling"
+            );
+            assert!(
+                !inventory.is_recently_scheduled(&build, Some(Path::new("/other"))),
+                "Same task in another directory is not a repeated launch"
+            );
+            assert!(!inventory.is_recently_scheduled(&build, None));
+            assert!(!inventory.is_recently_scheduled(&test, Some(project_dir)));
+
+            inventory.task_scheduled(TaskSourceKind::UserInput, test.clone(), None, cx);
+            assert!(inventory.is_recently_scheduled(&test, None));
+            assert!(
+                !inventory.is_recently_scheduled(&build, Some(project_dir)),
+                "Only the last scheduled task should be in the cooldown"
+            );
+        });
+
+        std::thread::sleep(cooldown * 2);
+        inventory.update(cx, |inventory, _| {
+            assert!(
+                !inventory.is_recently_scheduled(&test, None),
+                "Task should be scheduled normally after the cooldown"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_forgetting_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    vec![
+                        "1_task".to_string(),
+                        "2_task".to_string(),
+                        "3_task".to_string(),
+                    ],
+                    cx,
+                ),
+                cx,
+            );
+        });
+        register_task_used(&inventory, "3_task", cx);
+        register_task_used(&inventory, "2_task", cx);
+        register_task_used(&inventory, "3_task", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "3_task".to_string(),
+                "2_task".to_string(),
+                "1_task".to_string(),
+            ],
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            let (_, task) = inventory
+                .list_tasks(None, None, false, false, cx)
+                .into_iter()
+                .find(|(_, task)| task.name() == "3_task")
+                .unwrap();
+            inventory.forget_task(task.id(), cx);
+        });
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "2_task".to_string(),
+                "1_task".to_string(),
+                "3_task".to_string(),
+            ],
+            "Forgotten task should fall back to its alphanumeric position, after the used ones"
+        );
+        assert_eq!(
+            inventory
+                .update(cx, |inventory, cx| inventory.last_scheduled_task(cx))
+                .map(|(_, task)| task.name().to_string()),
+            Some("2_task".to_string()),
+        );
+
+        inventory.update(cx, |inventory, cx| inventory.clear_history(cx));
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "1_task".to_string(),
+                "2_task".to_string(),
+                "3_task".to_string(),
+            ],
+            "Tasks should be listed alphanumerically after the history is cleared"
+        );
+        assert!(inventory
+            .update(cx, |inventory, cx| inventory.last_scheduled_task(cx))
+            .is_none());
+    }
+
+    #[gpui::test]
+    fn test_resolving_task_dependencies(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new_with_dependencies(
+                    [
+                        ("codegen", vec![]),
+                        ("build", vec!["codegen"]),
+                        ("lint", vec!["codegen"]),
+                        ("test", vec!["build", "lint"]),
+                        ("ping", vec!["pong"]),
+                        ("pong", vec!["ping"]),
+                        ("deploy", vec!["upload"]),
+                    ]
+                    .map(|(name, dependencies)| {
+                        (
+                            name.to_string(),
+                            dependencies.into_iter().map(str::to_string).collect(),
+                        )
+                    }),
+                    None,
+                    cx,
+                ),
+                cx,
+            );
+        });
+        let resolve = |task_name: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let (_, task) = inventory
+                    .list_tasks(None, None, false, false, cx)
+                    .into_iter()
+                    .find(|(_, task)| task.name() == task_name)
+                    .unwrap();
+                inventory
+                    .resolve_dependencies(task.as_ref(), cx)
+                    .map(|dependencies| {
+                        dependencies
+                            .into_iter()
+                            .map(|(_, task)| task.name().to_string())
+                            .collect::<Vec<_>>()
+                    })
+                    .map_err(|e| e.to_string())
+            })
+        };
+
+        assert_eq!(resolve("codegen", cx), Ok(Vec::new()));
+        assert_eq!(resolve("build", cx), Ok(vec!["codegen".to_string()]));
+        assert_eq!(
+            resolve("test", cx),
+            Ok(vec![
+                "codegen".to_string(),
+                "build".to_string(),
+                "lint".to_string()
+            ]),
+            "Shared dependencies should be run once, before all of their dependents"
+        );
+        assert_eq!(
+            resolve("ping", cx),
+            Err("Task dependency cycle: ping -> pong -> ping".to_string())
+        );
+        assert_eq!(
+            resolve("deploy", cx),
+            Err("Task \"deploy\" depends on an unknown task \"upload\"".to_string())
+        );
+    }
+
+    #[gpui::test]
+    fn test_hidden_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            let source = cx.new_model(|_| {
+                Box::new(TestSource {
+                    tasks: [("codegen", vec![], true), ("build", vec!["codegen"], false)]
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, (name, dependencies, hidden))| TestTask {
+                            id: TaskId(format!("task_{i}_{name}")),
+                            name: name.to_string(),
+                            dependencies: dependencies.into_iter().map(str::to_string).collect(),
+                            source_order: None,
+                            hidden,
+                        })
+                        .collect(),
+                    path_scope: None,
+                }) as Box<dyn TaskSource>
+            });
+            inventory.add_source(TaskSourceKind::UserInput, source, cx);
+        });
+
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["build".to_string()],
+            "Hidden tasks should not be listed by default"
+        );
+        inventory.update(cx, |inventory, cx| {
+            let all_task_names = inventory
+                .list_tasks(None, None, false, true, cx)
+                .into_iter()
+                .map(|(_, task)| task.name().to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                all_task_names,
+                vec!["build".to_string(), "codegen".to_string()]
+            );
+            assert_eq!(
+                inventory.task_count(cx),
+                1,
+                "Hidden tasks should not be counted, same as they are not listed"
+            );
+
+            let (_, hidden_task) = inventory
+                .task_by_name("codegen", None, cx)
+                .expect("Hidden tasks should be found by their names");
+            assert_eq!(hidden_task.id(), &TaskId("task_0_codegen".to_string()));
+
+            let (_, build_task) = inventory.task_by_name("build", None, cx).unwrap();
+            let dependencies = inventory
+                .resolve_dependencies(build_task.as_ref(), cx)
+                .unwrap()
+                .into_iter()
+                .map(|(_, task)| task.name().to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                dependencies,
+                vec!["codegen".to_string()],
+                "Hidden tasks should be resolved as dependencies"
+            );
+        });
+    }
+
+    #[test]
+    fn test_resolving_task_cwd() {
+        let worktree_source = TaskSourceKind::Worktree {
+            id: WorktreeId::from_usize(1),
+            abs_path: PathBuf::from("/project/crates/app/Cargo.toml"),
+        };
+        assert_eq!(
+            worktree_source.resolve_cwd(Path::new("scripts"), Some(Path::new("/project"))),
+            PathBuf::from("/project/scripts"),
+            "Relative directories should be resolved against the worktree root"
+        );
+        assert_eq!(
+            worktree_source.resolve_cwd(Path::new("scripts"), None),
+            PathBuf::from("/project/crates/app/scripts"),
+            "Without the worktree, the directory of the task definitions file should be used"
+        );
+        assert_eq!(
+            worktree_source.resolve_cwd(Path::new("/tmp/build"), Some(Path::new("/project"))),
+            PathBuf::from("/tmp/build"),
+            "Absolute directories should be used as is"
+        );
+
+        let abs_path_source = TaskSourceKind::AbsPath(PathBuf::from("/tools/tasks.json"));
+        assert_eq!(
+            abs_path_source.resolve_cwd(Path::new("../out"), None),
+            PathBuf::from("/tools/../out")
+        );
+        assert_eq!(
+            TaskSourceKind::UserInput.resolve_cwd(Path::new("out"), None),
+            PathBuf::from("out"),
+            "Directories of the sources without files or worktrees cannot be resolved"
+        );
+    }
+
+    #[test]
+    fn test_grouping_by_source_kind() {
+        let makefile = TaskSourceKind::AbsPath(PathBuf::from("/project/Makefile"));
+        let groups = group_by_source_kind([
+            (TaskSourceKind::UserInput, "echo"),
+            (makefile.clone(), "make build"),
+            (TaskSourceKind::UserInput, "ls"),
+            (makefile.clone(), "make test"),
+        ]);
+        assert_eq!(
+            groups,
+            vec![
+                (TaskSourceKind::UserInput, vec!["echo", "ls"]),
+                (makefile, vec!["make build", "make test"]),
+            ]
+        );
+        assert!(group_by_source_kind(Vec::<(TaskSourceKind, &str)>::new()).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_pinned_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    vec![
+                        "1_pinnable".to_string(),
+                        "2_pinnable".to_string(),
+                        "3_pinnable".to_string(),
+                    ],
+                    cx,
+                ),
+                cx,
+            );
+        });
+        let toggle_pinned = |task_name: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let (_, task) = inventory
+                    .list_tasks(None, None, false, false, cx)
+                    .into_iter()
+                    .find(|(_, task)| task.name() == task_name)
+                    .unwrap();
+                inventory.toggle_pinned(task.id().clone(), cx);
+            })
+        };
+
+        register_task_used(&inventory, "2_pinnable", cx);
+        toggle_pinned("3_pinnable", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "3_pinnable".to_string(),
+                "2_pinnable".to_string(),
+                "1_pinnable".to_string(),
+            ],
+            "Pinned tasks should be listed above the recently used ones"
+        );
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec![
+                "3_pinnable".to_string(),
+                "1_pinnable".to_string(),
+                "2_pinnable".to_string(),
+            ],
+            "Pinned tasks should be listed first even without the LRU sorting"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.toggle_pinned(TaskId("missing_pinnable".to_string()), cx);
+        });
+        toggle_pinned("3_pinnable", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "2_pinnable".to_string(),
+                "1_pinnable".to_string(),
+                "3_pinnable".to_string(),
+            ],
+            "Unpinned task should return to its regular position, pins of missing tasks ignored"
+        );
+    }
+
+    #[gpui::test]
+    fn test_pins_persisted_per_scope(cx: &mut TestAppContext) {
+        let open_project = |scope: &str, cx: &mut TestAppContext| {
+            let inventory = cx.update(Inventory::new);
+            inventory.update(cx, |inventory, cx| {
+                inventory.add_source(
+                    TaskSourceKind::UserInput,
+                    TestSource::new(vec!["build".to_string(), "lint".to_string()], cx),
+                    cx,
+                );
+                inventory.load_persisted_state(scope.to_string(), cx);
+            });
+            cx.run_until_parked();
+            inventory
+        };
+        let pinned_ids = |inventory: &Model<Inventory>, cx: &mut TestAppContext| {
+            inventory.read_with(cx, |inventory, _| {
+                let mut pinned_ids = inventory
+                    .pinned_tasks
+                    .iter()
+                    .map(|id| id.0.clone())
+                    .collect::<Vec<_>>();
+                pinned_ids.sort();
+                pinned_ids
+            })
+        };
+
+        let first_project = open_project("pins_scope_first", cx);
+        let second_project = open_project("pins_scope_second", cx);
+        first_project.update(cx, |inventory, cx| {
+            inventory.toggle_pinned(TaskId("task_0_build".to_string()), cx);
+        });
+        second_project.update(cx, |inventory, cx| {
+            inventory.toggle_pinned(TaskId("task_1_lint".to_string()), cx);
+        });
+        cx.run_until_parked();
+
+        assert_eq!(
+            pinned_ids(&open_project("pins_scope_first", cx), cx),
+            vec!["task_0_build".to_string()],
+            "Pins of the other projects should not be restored"
+        );
+        assert_eq!(
+            pinned_ids(&open_project("pins_scope_second", cx), cx),
+            vec!["task_1_lint".to_string()],
+        );
+    }
+
+    #[gpui::test]
+    fn test_usage_blended_scores(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    vec![
+                        "build".to_string(),
+                        "build".to_string(),
+                        "build".to_string(),
+                        "build".to_string(),
+                    ],
+                    cx,
+                ),
+                cx,
+            );
+        });
+        let ranked_ids = |lru: bool, weight: f64, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let bonuses = inventory.usage_bonuses(lru);
+                let mut scored = inventory
+                    .list_tasks(None, None, false, false, cx)
+                    .into_iter()
+                    .map(|(_, task)| {
+                        let bonus = bonuses.get(task.id()).copied().unwrap_or_default();
+                        (task.id().0.clone(), usage_blended_score(0.5, bonus, weight))
+                    })
+                    .collect::<Vec<_>>();
+                scored.sort_by(|(id_a, score_a), (id_b, score_b)| {
+                    score_b.total_cmp(score_a).then_with(|| id_a.cmp(id_b))
+                });
+                scored.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+            })
+        };
+
+        inventory.update(cx, |inventory, cx| {
+            for id in ["task_1_build", "task_2_build"] {
+                inventory.task_scheduled(
+                    TaskSourceKind::UserInput,
+                    TaskId(id.to_string()),
+                    None,
+                    cx,
+                );
+            }
+            inventory.toggle_pinned(TaskId("task_3_build".to_string()), cx);
+        });
+        assert_eq!(
+            ranked_ids(true, 0.1, cx),
+            vec![
+                "task_3_build".to_string(),
+                "task_2_build".to_string(),
+                "task_1_build".to_string(),
+                "task_0_build".to_string(),
+            ],
+            "Of the equally matching tasks, pinned ones should rank first, then the most recently used ones"
+        );
+        assert_eq!(
+            ranked_ids(false, 0.1, cx),
+            vec![
+                "task_3_build".to_string(),
+                "task_0_build".to_string(),
+                "task_1_build".to_string(),
+                "task_2_build".to_string(),
+            ],
+            "Without the LRU ordering, only the pinned tasks should get the bonus"
+        );
+        assert_eq!(
+            ranked_ids(true, 0.0, cx),
+            vec![
+                "task_0_build".to_string(),
+                "task_1_build".to_string(),
+                "task_2_build".to_string(),
+                "task_3_build".to_string(),
+            ],
+            "Zero weight should leave the match scores as they are"
+        );
+    }
+
+    #[gpui::test]
+    fn test_same_named_tasks_from_different_sources(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string(), "build".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                TestSource::new(vec!["build".to_string(), "test".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::Worktree {
+                    id: WorktreeId::from_usize(0),
+                    abs_path: PathBuf::from("/worktree/.zed/tasks.json"),
+                },
+                TestSource::new(vec!["test".to_string(), "lint".to_string()], cx),
+                cx,
+            );
+        });
+
+        let listed_tasks = inventory.update(cx, |inventory, cx| {
+            inventory
+                .list_tasks(None, None, false, false, cx)
+                .into_iter()
+                .map(|(kind, task)| (kind.label(), task.name().to_string(), task.id().0.clone()))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            listed_tasks,
+            vec![
+                (
+                    "tasks.json".to_string(),
+                    "build".to_string(),
+                    "task_0_build".to_string()
+                ),
+                (
+                    "User".to_string(),
+                    "echo".to_string(),
+                    "task_0_echo".to_string()
+                ),
+                (
+                    "tasks.json".to_string(),
+                    "lint".to_string(),
+                    "task_1_lint".to_string()
+                ),
+                (
+                    "tasks.json".to_string(),
+                    "test".to_string(),
+                    "task_0_test".to_string()
+                ),
+            ],
+            "Same named tasks should be listed once, from the source with the highest precedence"
+        );
+    }
+
+    #[gpui::test]
+    fn test_preferring_worktree_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree_kind = TaskSourceKind::Worktree {
+            id: WorktreeId::from_usize(0),
+            abs_path: PathBuf::from("/worktree/.zed/tasks.json"),
+        };
+        let global_kind = TaskSourceKind::Global(PathBuf::from("/config/tasks.json"));
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                global_kind.clone(),
+                TestSource::new(vec!["build".to_string(), "format".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                worktree_kind.clone(),
+                TestSource::new(vec!["build".to_string()], cx),
+                cx,
+            );
+        });
+        let listed_tasks = |cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                inventory
+                    .list_tasks(None, None, true, false, cx)
+                    .into_iter()
+                    .map(|(kind, task)| (kind, task.name().to_string()))
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            listed_tasks(cx),
+            vec![
+                (worktree_kind.clone(), "build".to_string()),
+                (global_kind.clone(), "format".to_string()),
+            ],
+            "Worktree tasks should be preferred by default"
+        );
+        assert_eq!(
+            inventory.update(cx, |inventory, cx| inventory.task_count(cx)),
+            2
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_prefer_worktree_tasks(false, cx);
+        });
+        assert_eq!(
+            listed_tasks(cx),
+            vec![
+                (global_kind.clone(), "build".to_string()),
+                (global_kind.clone(), "format".to_string()),
+            ],
+            "Global tasks should be preferred, if the worktree ones are not"
+        );
+        inventory.update(cx, |inventory, cx| {
+            let (kind, _) = inventory.task_by_name("build", None, cx).unwrap();
+            assert_eq!(kind, global_kind);
+            assert_eq!(inventory.task_count(cx), 2);
+        });
+    }
+
+    #[gpui::test]
+    fn test_looking_up_tasks_by_name(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree_1 = WorktreeId::from_usize(1);
+        let worktree_2 = WorktreeId::from_usize(2);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string(), "build".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::Worktree {
+                    id: worktree_1,
+                    abs_path: PathBuf::from("/worktree_1/.zed/tasks.json"),
+                },
+                TestSource::new(vec!["build".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::Worktree {
+                    id: worktree_2,
+                    abs_path: PathBuf::from("/worktree_2/.zed/tasks.json"),
+                },
+                TestSource::new(vec!["build".to_string()], cx),
+                cx,
+            );
+        });
+
+        inventory.update(cx, |inventory, cx| {
+            let (kind, task) = inventory.task_by_name("echo", None, cx).unwrap();
+            assert_eq!(kind, TaskSourceKind::UserInput);
+            assert_eq!(task.name(), "echo");
+
+            let (kind, _) = inventory
+                .task_by_name("build", Some(worktree_2), cx)
+                .unwrap();
+            assert_eq!(
+                kind.worktree(),
+                Some(worktree_2),
+                "Tasks from the other worktrees should not be looked up"
+            );
+            let (kind, _) = inventory.task_by_name("build", None, cx).unwrap();
+            assert!(
+                kind.worktree().is_some(),
+                "Worktree tasks have higher precedence than the user input ones"
+            );
+
+            assert!(inventory.task_by_name("bui", None, cx).is_none());
+            assert!(inventory.task_by_name("unknown", None, cx).is_none());
+        });
+    }
+
+    #[gpui::test]
+    fn test_tracking_running_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let build = TaskId("build".to_string());
+        let test = TaskId("test".to_string());
+        inventory.update(cx, |inventory, cx| {
+            assert!(!inventory.is_running(&build));
+
+            inventory.task_started(build.clone(), cx);
+            inventory.task_started(build.clone(), cx);
+            assert!(inventory.is_running(&build));
+            assert!(!inventory.is_running(&test));
+
+            inventory.task_finished(&build, cx);
+            assert!(
+                inventory.is_running(&build),
+                "Task should be running while any of its instances runs"
+            );
+            inventory.task_finished(&build, cx);
+            assert!(!inventory.is_running(&build));
+
+            inventory.task_finished(&test, cx);
+            assert!(
+                !inventory.is_running(&test),
+                "Finishing a task that never started should be a no-op"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_recording_exit_statuses(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let build = TaskId("build".to_string());
+        let test = TaskId("test".to_string());
+        inventory.update(cx, |inventory, cx| {
+            assert_eq!(inventory.last_exit_status(&build), None);
+
+            let started_at = SystemTime::now();
+            inventory.task_exited(
+                build.clone(),
+                TaskExitStatus::Failure(Some(101)),
+                started_at,
+                cx,
+            );
+            inventory.task_exited(test.clone(), TaskExitStatus::Success, started_at, cx);
+            assert_eq!(
+                inventory.last_exit_status(&build),
+                Some(TaskExitStatus::Failure(Some(101)))
+            );
+            assert_eq!(
+                inventory.last_exit_status(&test),
+                Some(TaskExitStatus::Success)
+            );
+
+            inventory.task_exited(build.clone(), TaskExitStatus::Success, started_at, cx);
+            assert_eq!(
+                inventory.last_exit_status(&build),
+                Some(TaskExitStatus::Success),
+                "Only the last exit status should be remembered"
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_last_runs(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(
+                    vec!["build".to_string(), "test".to_string(), "lint".to_string()],
+                    cx,
+                ),
+                cx,
+            );
+        });
+        let task_id = |name: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                inventory
+                    .list_tasks(None, None, false, false, cx)
+                    .into_iter()
+                    .find(|(_, task)| task.name() == name)
+                    .map(|(_, task)| task.id().clone())
+                    .unwrap()
+            })
+        };
+        let build = task_id("build", cx);
+        let test = task_id("test", cx);
+        register_task_used(&inventory, "build", cx);
+        register_task_used(&inventory, "test", cx);
+
+        let started_at = SystemTime::now() - Duration::from_secs(90);
+        inventory.update(cx, |inventory, cx| {
+            assert_eq!(inventory.last_run(&build), None);
+            inventory.task_exited(
+                build.clone(),
+                TaskExitStatus::Failure(Some(1)),
+                started_at,
+                cx,
+            );
+            inventory.task_exited(test.clone(), TaskExitStatus::Success, started_at, cx);
+
+            let build_run = inventory.last_run(&build).unwrap();
+            assert_eq!(build_run.started_at, started_at);
+            assert_eq!(build_run.exit_status, TaskExitStatus::Failure(Some(1)));
+            assert!(build_run.duration >= Duration::from_secs(90));
+            assert!(build_run.finished_at() <= SystemTime::now());
+
+            inventory.set_max_history(1);
+            assert_eq!(
+                inventory.last_run(&build),
+                None,
+                "Runs of the tasks evicted from the history should be forgotten"
+            );
+            assert!(inventory.last_run(&test).is_some());
+
+            inventory.forget_task(&test, cx);
+            assert_eq!(inventory.last_run(&test), None);
+        });
+    }
+
+    #[gpui::test]
+    fn test_watching_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let spawn = |id: &str, command: &str| SpawnInTerminal {
+            reuse_key: Some(id.to_string()),
+            ..SpawnInTerminal::new(TaskId(id.to_string()), id.to_string(), command.to_string())
+        };
+        let watched_commands = |cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, _| {
+                inventory
+                    .watched_tasks()
+                    .into_iter()
+                    .map(|spawn| spawn.command)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        inventory.update(cx, |inventory, cx| {
+            assert!(inventory.watch_task(spawn("test", "cargo test"), cx));
+            assert!(inventory.watch_task(spawn("lint", "cargo clippy"), cx));
+            assert!(
+                !inventory.watch_task(spawn("test", "cargo test -- unit"), cx),
+                "Watching the task again should not be reported as a new watch"
+            );
+            assert!(inventory.is_watched(&TaskId("test".to_string())));
+        });
+        assert_eq!(
+            watched_commands(cx),
+            vec!["cargo clippy".to_string(), "cargo test -- unit".to_string()],
+            "Watching the task again should replace its command"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            assert!(inventory.unwatch_task(&TaskId("lint".to_string()), cx));
+            assert!(!inventory.unwatch_task(&TaskId("lint".to_string()), cx));
+        });
+        assert_eq!(watched_commands(cx), vec!["cargo test -- unit".to_string()]);
+
+        inventory.update(cx, |inventory, cx| {
+            assert_eq!(inventory.unwatch_all(cx), 1);
+            assert!(!inventory.is_watched(&TaskId("test".to_string())));
+            assert_eq!(inventory.unwatch_all(cx), 0);
+        });
+    }
+
+    #[gpui::test]
+    fn test_retrying_failed_tasks(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let events = events.clone();
+            cx.subscribe(&inventory, move |_, event: &InventoryEvent, _| {
+                events.borrow_mut().push(event.clone());
+            })
+        });
+        let id = TaskId("flaky_test".to_string());
+        let spawn_in_terminal = SpawnInTerminal {
+            retries: 2,
+            ..SpawnInTerminal::new(
+                id.clone(),
+                "flaky test".to_string(),
+                "cargo test --test network".to_string(),
+            )
+        };
+        let started_at = SystemTime::now();
+
+        // A command that fails once, then succeeds.
+        inventory.update(cx, |inventory, cx| {
+            inventory.retry_on_failure(spawn_in_terminal.clone());
+            inventory.task_exited(id.clone(), TaskExitStatus::Failure(Some(1)), started_at, cx);
+            assert_eq!(
+                inventory.task_to_retry(&id).map(|spawn| spawn.command),
+                Some("cargo test --test network".to_string())
+            );
+            inventory.task_exited(id.clone(), TaskExitStatus::Success, started_at, cx);
+            assert!(
+                inventory.task_to_retry(&id).is_none(),
+                "Successful attempt should stop the retries"
+            );
+        });
+        assert_eq!(
+            events.borrow_mut().drain(..).collect::<Vec<_>>(),
+            vec![InventoryEvent::RetryTask {
+                id: id.clone(),
+                attempt: 1
+            }]
+        );
+
+        // A command that keeps failing.
+        inventory.update(cx, |inventory, cx| {
+            inventory.retry_on_failure(spawn_in_terminal.clone());
+            for _ in 0..3 {
+                inventory.task_exited(id.clone(), TaskExitStatus::Failure(Some(1)), started_at, cx);
+            }
+            assert!(inventory.task_to_retry(&id).is_none());
+        });
+        assert_eq!(
+            events.borrow_mut().drain(..).collect::<Vec<_>>(),
+            vec![
+                InventoryEvent::RetryTask {
+                    id: id.clone(),
+                    attempt: 1
+                },
+                InventoryEvent::RetryTask {
+                    id: id.clone(),
+                    attempt: 2
+                },
+            ],
+            "Task should be retried no more than its retries allow"
+        );
+
+        // A command, terminated without an exit code.
+        inventory.update(cx, |inventory, cx| {
+            inventory.retry_on_failure(spawn_in_terminal);
+            inventory.task_exited(id.clone(), TaskExitStatus::Failure(None), started_at, cx);
+            assert!(inventory.task_to_retry(&id).is_none());
+        });
+        assert!(
+            events.borrow().is_empty(),
+            "Killed or timed out tasks should not be retried"
+        );
+    }
+
+    #[gpui::test]
+    fn test_abs_path_tasks_outside_of_worktrees(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree = WorktreeId::from_usize(1);
+        let scripts_kind = TaskSourceKind::AbsPath(PathBuf::from("/home/user/scripts/tasks.json"));
+        let worktree_kind = TaskSourceKind::Worktree {
+            id: worktree,
+            abs_path: PathBuf::from("/project/.zed/tasks.json"),
+        };
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                scripts_kind.clone(),
+                TestSource::new(vec!["backup".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                worktree_kind.clone(),
+                TestSource::new(vec!["build".to_string()], cx),
+                cx,
+            );
+        });
+
+        let listed_kinds = inventory.update(cx, |inventory, cx| {
+            inventory
+                .list_tasks(None, Some(worktree), false, false, cx)
+                .into_iter()
+                .map(|(kind, task)| (kind, task.name().to_string()))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            listed_kinds,
+            vec![
+                (scripts_kind.clone(), "backup".to_string()),
+                (worktree_kind.clone(), "build".to_string()),
+            ],
+            "Tasks from the files outside of the worktrees should be listed when filtering by worktree"
+        );
+        assert_eq!(
+            scripts_kind.standalone_directory(),
+            Some(Path::new("/home/user/scripts"))
+        );
+        assert_eq!(worktree_kind.standalone_directory(), None);
+        assert_eq!(TaskSourceKind::UserInput.standalone_directory(), None);
+    }
+
+    #[gpui::test]
+    fn test_describing_sources(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let worktree = WorktreeId::from_usize(1);
+        let worktree_kind = TaskSourceKind::Worktree {
+            id: worktree,
+            abs_path: PathBuf::from("/project/.zed/tasks.json"),
+        };
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                worktree_kind.clone(),
+                TestSource::new(vec!["build".to_string(), "test".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                cx.new_model(|_| Box::new(FailingTestSource) as Box<dyn TaskSource>),
+                cx,
+            );
+
+            let descriptions = inventory.describe_sources(cx);
+            assert_eq!(descriptions.len(), inventory.source_count());
+            assert_eq!(
+                descriptions
+                    .iter()
+                    .map(|description| (description.kind.clone(), description.task_count))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (TaskSourceKind::UserInput, 1),
+                    (worktree_kind.clone(), 2),
+                    (TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")), 0),
+                ],
+                "Every source should be described, including the ones without tasks"
+            );
+            assert_eq!(descriptions[1].type_id, TypeId::of::<TestSource>());
+            assert_eq!(descriptions[2].type_id, TypeId::of::<FailingTestSource>());
+            assert!(descriptions
+                .iter()
+                .all(|description| description.tasks_file.is_none()
+                    && !description.tasks_file_missing
+                    && !description.loading));
+            assert_eq!(
+                descriptions
+                    .iter()
+                    .map(|description| description.error.as_deref())
+                    .collect::<Vec<_>>(),
+                vec![None, None, Some("Failed to list tasks")]
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_task_and_source_counts(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            assert_eq!(inventory.source_count(), 0);
+            assert_eq!(inventory.task_count(cx), 0);
+
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string(), "build".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                TestSource::new(vec!["build".to_string(), "test".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/other_tasks.json")),
+                TestSource::new(Vec::new(), cx),
+                cx,
+            );
+            assert_eq!(inventory.source_count(), 3);
+            assert_eq!(
+                inventory.task_count(cx),
+                inventory.list_tasks(None, None, false, false, cx).len(),
+                "Same named tasks should be counted once, as they are listed"
+            );
+            assert_eq!(inventory.task_count(cx), 3);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_removing_sources_by_kind_and_type(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file("/project/tasks.json", "[]".to_string())
+            .await;
+        let inventory = cx.update(Inventory::new);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let events = events.clone();
+            cx.subscribe(&inventory, move |_, event: &InventoryEvent, _| {
+                events.borrow_mut().push(event.clone());
+            })
+        });
+        let global_kind = TaskSourceKind::Global(PathBuf::from("/config/tasks.json"));
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                global_kind.clone(),
+                TestSource::new(vec!["backup".to_string()], cx),
+                cx,
+            );
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/project/tasks.json")),
+                fs.clone(),
+                |tracker, cx| StaticSource::new(PathBuf::from("/project/tasks.json"), tracker, cx),
+                cx,
+            );
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        events.borrow_mut().clear();
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.remove_source_by_kind(&TaskSourceKind::UserInput, cx);
+        });
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["backup".to_string()]
+        );
+        assert_eq!(
+            events.borrow_mut().drain(..).collect::<Vec<_>>(),
+            vec![InventoryEvent::SourcesChanged]
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.remove_source::<TestSource>(cx);
+            assert_eq!(
+                inventory.source_count(),
+                1,
+                "Only the sources of the other types should be left"
+            );
+            assert!(inventory.source::<StaticSource>().is_some());
+            inventory.remove_source_by_kind(&global_kind, cx);
+        });
+        assert_eq!(
+            events.borrow_mut().drain(..).collect::<Vec<_>>(),
+            vec![InventoryEvent::SourcesChanged],
+            "Removing no sources should not be reported"
+        );
+    }
+
+    #[gpui::test]
+    fn test_disabling_sources(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let events = events.clone();
+            cx.subscribe(&inventory, move |_, event: &InventoryEvent, _| {
+                events.borrow_mut().push(event.clone());
+            })
+        });
+        let makefile_kind = TaskSourceKind::Worktree {
+            id: WorktreeId::from_usize(0),
+            abs_path: PathBuf::from("/disabled_sources_project/Makefile"),
+        };
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                makefile_kind.clone(),
+                TestSource::new(vec!["build".to_string(), "clean".to_string()], cx),
+                cx,
+            );
+        });
+        events.borrow_mut().clear();
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_source_enabled(&makefile_kind, false, cx);
+            inventory.set_source_enabled(&makefile_kind, false, cx);
+        });
+        assert_eq!(
+            events.borrow_mut().drain(..).collect::<Vec<_>>(),
+            vec![InventoryEvent::SourcesChanged],
+            "Disabling a source should be reported once"
+        );
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["echo".to_string()],
+            "Tasks of the disabled source should not be listed"
+        );
+        inventory.update(cx, |inventory, cx| {
+            assert!(!inventory.is_source_enabled(&makefile_kind));
+            assert!(inventory.task_by_name("build", None, cx).is_none());
+            assert_eq!(inventory.task_count(cx), 1);
+            assert_eq!(
+                inventory
+                    .describe_sources(cx)
+                    .into_iter()
+                    .map(|description| (description.kind, description.enabled))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (TaskSourceKind::UserInput, true),
+                    (makefile_kind.clone(), false),
+                ],
+                "Disabled sources should stay registered"
+            );
+        });
+
+        // Worktree ids change between the sessions, the paths of the sources do not.
+        let reopened_makefile_kind = TaskSourceKind::Worktree {
+            id: WorktreeId::from_usize(1),
+            abs_path: PathBuf::from("/disabled_sources_project/Makefile"),
+        };
+        inventory.update(cx, |inventory, cx| {
+            inventory.remove_source_by_kind(&makefile_kind, cx);
+            inventory.add_source(
+                reopened_makefile_kind.clone(),
+                TestSource::new(vec!["build".to_string()], cx),
+                cx,
+            );
+        });
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["echo".to_string()],
+            "Sources, registered again for the same path, should stay disabled"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_source_enabled(&reopened_makefile_kind, true, cx);
+        });
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["build".to_string(), "echo".to_string()],
+            "Re-enabled source should have its tasks listed again"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_disabled_sources_persisted_per_scope(cx: &mut TestAppContext) {
+        let makefile_kind = TaskSourceKind::AbsPath(PathBuf::from("/persisted_project/Makefile"));
+        let justfile_kind = TaskSourceKind::AbsPath(PathBuf::from("/persisted_project/justfile"));
+        let vscode_kind =
+            TaskSourceKind::AbsPath(PathBuf::from("/persisted_project/.vscode/tasks.json"));
+        KEY_VALUE_STORE
+            .write_kvp(
+                scoped_key(DISABLED_SOURCES_KEY, "disabled_scope_first"),
+                serde_json::to_string(&[
+                    justfile_kind.persistence_key(),
+                    vscode_kind.persistence_key(),
+                ])
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let open_project = |scope: &str, cx: &mut TestAppContext| {
+            let inventory = cx.update(Inventory::new);
+            inventory.update(cx, |inventory, cx| {
+                inventory.load_persisted_state(scope.to_string(), cx);
+            });
+            inventory
+        };
+        let enabled_kinds = |inventory: &Model<Inventory>, cx: &mut TestAppContext| {
+            inventory.read_with(cx, |inventory, _| {
+                [&makefile_kind, &justfile_kind, &vscode_kind]
+                    .map(|kind| inventory.is_source_enabled(kind))
+            })
+        };
+
+        let first_project = open_project("disabled_scope_first", cx);
+        first_project.update(cx, |inventory, cx| {
+            inventory.set_source_enabled(&makefile_kind, false, cx);
+            inventory.set_source_enabled(&vscode_kind, true, cx);
+        });
+        let second_project = open_project("disabled_scope_second", cx);
+        cx.run_until_parked();
+        assert_eq!(
+            enabled_kinds(&first_project, cx),
+            [false, false, true],
+            "Sources, toggled before the persisted states were loaded, should keep their states"
+        );
+        assert_eq!(enabled_kinds(&second_project, cx), [true, true, true]);
+
+        second_project.update(cx, |inventory, cx| {
+            inventory.set_source_enabled(&justfile_kind, false, cx);
+        });
+        cx.run_until_parked();
+        let reopened_first_project = open_project("disabled_scope_first", cx);
+        let reopened_second_project = open_project("disabled_scope_second", cx);
+        cx.run_until_parked();
+        assert_eq!(
+            enabled_kinds(&reopened_first_project, cx),
+            [false, false, true],
+            "Toggled sources should be merged into the persisted states"
+        );
+        assert_eq!(
+            enabled_kinds(&reopened_second_project, cx),
+            [true, false, true],
+            "Projects should not overwrite the source states of each other"
+        );
+    }
+
+    #[gpui::test]
+    fn test_history_of_disabled_sources(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let makefile_kind = TaskSourceKind::Worktree {
+            id: WorktreeId::from_usize(0),
+            abs_path: PathBuf::from("/history_project/Makefile"),
+        };
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                makefile_kind.clone(),
+                TestSource::new(vec!["build".to_string(), "clean".to_string()], cx),
+                cx,
+            );
+        });
+        register_task_used(&inventory, "build", cx);
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_source_enabled(&makefile_kind, false, cx);
+        });
+        register_task_used(&inventory, "echo", cx);
+        inventory.update(cx, |inventory, cx| {
+            inventory.restore_history(vec![
+                TaskId("task_1_clean".to_string()),
+                TaskId("another_removed_task".to_string()),
+            ]);
+            inventory.prune_restored_history(cx);
+        });
+        assert_eq!(
+            history_ids(&inventory, cx),
+            vec![
+                "task_1_clean".to_string(),
+                "task_0_build".to_string(),
+                "task_0_echo".to_string()
+            ],
+            "Restored tasks of the disabled sources should stay in the history, restored tasks of no source should be dropped"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.set_source_enabled(&makefile_kind, true, cx);
+        });
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec!["echo".to_string(), "build".to_string(), "clean".to_string()],
+            "Re-enabled source should have its task history back"
+        );
+    }
+
+    #[gpui::test]
+    fn test_history_persisted_per_scope(cx: &mut TestAppContext) {
+        let open_projects = |cx: &mut TestAppContext| {
+            ["build", "lint"].map(|task_name| {
+                let inventory = cx.update(Inventory::new);
+                inventory.update(cx, |inventory, cx| {
+                    inventory.add_source(
+                        TaskSourceKind::UserInput,
+                        TestSource::new(vec![task_name.to_string(), "test".to_string()], cx),
+                        cx,
+                    );
+                    inventory.load_persisted_state(format!("history_scope_{task_name}"), cx);
+                });
+                inventory
+            })
+        };
+
+        let [build_inventory, lint_inventory] = open_projects(cx);
+        cx.run_until_parked();
+        register_task_used(&build_inventory, "build", cx);
+        register_task_used(&lint_inventory, "test", cx);
+        register_task_used(&lint_inventory, "lint", cx);
+        cx.run_until_parked();
+        drop((build_inventory, lint_inventory));
+
+        let [build_inventory, lint_inventory] = open_projects(cx);
+        cx.run_until_parked();
+        assert_eq!(
+            history_ids(&build_inventory, cx),
+            vec!["task_0_build".to_string()],
+            "Projects should not lose their history to the other projects"
+        );
+        assert_eq!(
+            history_ids(&lint_inventory, cx),
+            vec!["task_1_test".to_string(), "task_0_lint".to_string()],
+        );
+
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["lint".to_string()], cx),
+                cx,
+            );
+            inventory.load_persisted_state("history_scope_lint".to_string(), cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            history_ids(&inventory, cx),
+            vec!["task_0_lint".to_string()],
+            "Restored ids, unknown to all loaded sources, should be dropped"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_exporting_tasks(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/project/tasks.json",
+            r#"[{"label": "build", "command": "cargo", "args": ["build"], "cwd": "crates", "env": {"RUST_LOG": "info"}}]"#
+                .to_string(),
+        )
+        .await;
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/project/tasks.json")),
+                fs.clone(),
+                |tracker, cx| StaticSource::new(PathBuf::from("/project/tasks.json"), tracker, cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string()], cx),
+                cx,
+            );
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+
+        inventory.update(cx, |inventory, cx| {
+            let build_id = inventory
+                .task_by_name("build", None, cx)
+                .map(|(_, task)| task.id().0.clone())
+                .unwrap();
+            assert_eq!(
+                inventory.export_tasks(cx),
+                serde_json::json!([
+                    {
+                        "name": "build",
+                        "id": build_id,
+                        "source": {"kind": "abs_path", "path": "/project/tasks.json"},
+                        "command": "cargo",
+                        "args": ["build"],
+                        "env": {"RUST_LOG": "info"},
+                        "cwd": "crates",
+                    },
+                    {
+                        "name": "echo",
+                        "id": "task_0_echo",
+                        "source": {"kind": "user_input"},
+                    },
+                ]),
+                "Fields, the tasks cannot describe, should be omitted"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_adding_static_source_twice(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/project/.zed/tasks.json",
+            r#"[{"label": "build", "command": "cargo build"}]"#.to_string(),
+        )
+        .await;
+        let inventory = cx.update(Inventory::new);
+        let tasks_path = PathBuf::from("/project/.zed/tasks.json");
+        let created_sources = Rc::new(RefCell::new(0));
+        inventory.update(cx, |inventory, cx| {
+            for kind in [
+                TaskSourceKind::AbsPath(tasks_path.clone()),
+                TaskSourceKind::AbsPath(tasks_path.clone()),
+                TaskSourceKind::Worktree {
+                    id: WorktreeId::from_usize(1),
+                    abs_path: tasks_path.clone(),
+                },
+            ] {
+                let created_sources = created_sources.clone();
+                let tasks_path = tasks_path.clone();
+                inventory.add_static_source(
+                    kind,
+                    fs.clone(),
+                    move |tracker, cx| {
+                        *created_sources.borrow_mut() += 1;
+                        StaticSource::new(tasks_path, tracker, cx)
+                    },
+                    cx,
+                );
+            }
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+
+        assert_eq!(
+            *created_sources.borrow(),
+            1,
+            "Sources for the already added tasks file should not be created"
+        );
+        inventory.update(cx, |inventory, _| assert_eq!(inventory.source_count(), 1));
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["build".to_string()]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_inventory_events(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "build", "command": "cargo build"}]"#.to_string(),
+        )
+        .await;
+        let inventory = cx.update(Inventory::new);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let events = events.clone();
+            cx.subscribe(&inventory, move |_, event: &InventoryEvent, _| {
+                events.borrow_mut().push(event.clone());
+            })
+        });
+
+        let source_kind = TaskSourceKind::AbsPath(PathBuf::from("/tasks.json"));
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_static_source(
+                source_kind.clone(),
+                fs.clone(),
+                |tracker, cx| StaticSource::new(PathBuf::from("/tasks.json"), tracker, cx),
+                cx,
+            );
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        let received_events = events.borrow_mut().drain(..).collect::<Vec<_>>();
+        assert_eq!(
+            received_events.first(),
+            Some(&InventoryEvent::SourcesChanged)
+        );
+        assert!(
+            received_events.contains(&InventoryEvent::TasksRefreshed),
+            "Source producing its tasks should be reported, got: {received_events:?}"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.refresh_sources(cx);
+        });
+        assert_eq!(
+            events.borrow().first(),
+            Some(&InventoryEvent::TasksRefreshed)
+        );
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        events.borrow_mut().clear();
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.remove_local_static_source(Path::new("/tasks.json"), cx);
+            inventory.remove_local_static_source(Path::new("/missing.json"), cx);
+        });
+        assert_eq!(
+            events.borrow().as_slice(),
+            &[InventoryEvent::SourcesChanged],
+            "Only the actual removals should be reported"
+        );
+    }
+
+    #[gpui::test]
+    fn test_worktree_filtering(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                TestSource::new(vec!["echo".to_string()], cx),
+                cx,
+            );
+            inventory.add_source(
+                TaskSourceKind::Global(PathBuf::from("/config/tasks.json")),
+                TestSource::new(vec!["format".to_string()], cx),
+                cx,
+            );
+            for (worktree_id, task_name) in [(0, "build"), (1, "deploy")] {
+                inventory.add_source(
+                    TaskSourceKind::Worktree {
+                        id: WorktreeId::from_usize(worktree_id),
+                        abs_path: PathBuf::from(format!("/worktree_{worktree_id}/.zed/tasks.json")),
+                    },
+                    TestSource::new(vec![task_name.to_string()], cx),
+                    cx,
+                );
+            }
+        });
+
+        let task_names = |worktree: Option<WorktreeId>, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                inventory
+                    .list_tasks(None, worktree, false, false, cx)
+                    .into_iter()
+                    .map(|(_, task)| task.name().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            task_names(None, cx),
+            vec![
+                "build".to_string(),
+                "deploy".to_string(),
+                "echo".to_string(),
+                "format".to_string()
+            ],
+        );
+        assert_eq!(
+            task_names(Some(WorktreeId::from_usize(1)), cx),
+            vec![
+                "deploy".to_string(),
+                "echo".to_string(),
+                "format".to_string()
+            ],
+            "Sources of the other worktrees should be skipped, the ones without worktrees kept"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.remove_worktree_sources(WorktreeId::from_usize(1), cx);
+        });
+        assert_eq!(
+            task_names(Some(WorktreeId::from_usize(1)), cx),
+            vec!["echo".to_string(), "format".to_string()],
+            "Removing worktree sources should keep the global one"
+        );
+    }
+
+    #[gpui::test]
+    fn test_removed_worktree_history(cx: &mut TestAppContext) {
+        let inventory = cx.update(Inventory::new);
+        let add_worktree_source = |worktree_id: usize,
+                                   task_names: &[&str],
+                                   cx: &mut TestAppContext| {
+            let task_names = task_names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>();
+            inventory.update(cx, |inventory, cx| {
+                inventory.add_source(
+                    TaskSourceKind::Worktree {
+                        id: WorktreeId::from_usize(worktree_id),
+                        abs_path: PathBuf::from(format!("/worktree_{worktree_id}/.zed/tasks.json")),
+                    },
+                    TestSource::new(task_names, cx),
+                    cx,
+                );
+            });
+        };
+        add_worktree_source(0, &["a_build", "b_lint"], cx);
+        add_worktree_source(1, &["c_deploy", "d_release"], cx);
+        inventory.update(cx, |inventory, _| {
+            inventory.restore_history(vec![
+                TaskId("task_0_a_build".to_string()),
+                TaskId("task_1_d_release".to_string()),
+            ]);
+        });
+        register_task_used(&inventory, "b_lint", cx);
+        register_task_used(&inventory, "c_deploy", cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "c_deploy".to_string(),
+                "b_lint".to_string(),
+                "d_release".to_string(),
+                "a_build".to_string(),
+            ],
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.remove_worktree_sources(WorktreeId::from_usize(1), cx);
+        });
+        add_worktree_source(1, &["c_deploy", "d_release"], cx);
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec![
+                "b_lint".to_string(),
+                "a_build".to_string(),
+                "c_deploy".to_string(),
+                "d_release".to_string(),
+            ],
+            "History of the removed worktree should be purged, both scheduled and restored tasks"
+        );
+        assert_eq!(
+            inventory
+                .update(cx, |inventory, cx| inventory.last_scheduled_task(cx))
+                .map(|(_, task)| task.name().to_string()),
+            Some("b_lint".to_string()),
+        );
+    }
+
+    #[gpui::test]
+    async fn test_static_source_reloads_on_file_changes(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "build", "command": "cargo build"}]"#.to_string(),
+        )
+        .await;
         let inventory = cx.update(Inventory::new);
-        let initial_tasks = list_task_names(&inventory, None, true, cx);
-        assert!(
-            initial_tasks.is_empty(),
-            "No tasks expected for empty inventory, but got {initial_tasks:?}"
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                fs.clone(),
+                |tracker, cx| StaticSource::new(PathBuf::from("/tasks.json"), tracker, cx),
+                cx,
+            );
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["build".to_string()]
         );
-        let initial_tasks = list_task_names(&inventory, None, false, cx);
-        assert!(
-            initial_tasks.is_empty(),
-            "No tasks expected for empty inventory, but got {initial_tasks:?}"
+
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "test", "command": "cargo test"}]"#.to_string(),
+        )
+        .await;
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "build", "command": "cargo build"}, {"label": "test", "command": "cargo test"}]"#.to_string(),
+        )
+        .await;
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["build".to_string(), "test".to_string()],
+            "Tasks should be reloaded after the file changes"
         );
 
         inventory.update(cx, |inventory, cx| {
-            inventory.add_source(TestSource::new(vec!["3_task".to_string()], cx), cx);
+            inventory.remove_local_static_source(Path::new("/tasks.json"), cx);
         });
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "lint", "command": "cargo clippy"}]"#.to_string(),
+        )
+        .await;
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert!(
+            list_task_names(&inventory, None, false, cx).is_empty(),
+            "Removed source should not provide any tasks"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_missing_tasks_file(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "build", "command": "cargo build"}]"#.to_string(),
+        )
+        .await;
+        let inventory = cx.update(Inventory::new);
         inventory.update(cx, |inventory, cx| {
-            inventory.add_source(
-                TestSource::new(
-                    vec![
-                        "1_task".to_string(),
-                        "2_task".to_string(),
-                        "1_a_task".to_string(),
-                    ],
-                    cx,
-                ),
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                fs.clone(),
+                |tracker, cx| StaticSource::new(PathBuf::from("/tasks.json"), tracker, cx),
                 cx,
             );
         });
-
-        let expected_initial_state = [
-            "1_a_task".to_string(),
-            "1_task".to_string(),
-            "2_task".to_string(),
-            "3_task".to_string(),
-        ];
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
         assert_eq!(
             list_task_names(&inventory, None, false, cx),
-            &expected_initial_state,
-            "Task list without lru sorting, should be sorted alphanumerically"
+            vec!["build".to_string()]
+        );
+
+        fs.remove_file(Path::new("/tasks.json"), Default::default())
+            .await
+            .unwrap();
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert!(
+            list_task_names(&inventory, None, false, cx).is_empty(),
+            "Tasks of the deleted file should not be listed"
         );
+        inventory.update(cx, |inventory, cx| {
+            assert_eq!(inventory.task_count(cx), 0);
+            assert_eq!(
+                inventory.source_count(),
+                1,
+                "Source should be kept, to pick up the file if it is created again"
+            );
+        });
+
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "test", "command": "cargo test"}]"#.to_string(),
+        )
+        .await;
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
         assert_eq!(
-            list_task_names(&inventory, None, true, cx),
-            &expected_initial_state,
-            "Tasks with equal amount of usages should be sorted alphanumerically"
+            list_task_names(&inventory, None, false, cx),
+            vec!["test".to_string()],
+            "Tasks should be listed again after the file is recreated"
         );
+    }
+
+    #[gpui::test]
+    async fn test_explicit_task_order(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/tasks.json",
+            r#"[
+                {"label": "ordered_lint", "command": "cargo clippy"},
+                {"label": "ordered_test", "command": "cargo test", "order": 2},
+                {"label": "1_ordered_setup", "command": "./setup.sh"},
+                {"label": "ordered_build", "command": "cargo build", "order": -1},
+                {"label": "ordered_deploy", "command": "./deploy.sh", "order": 2}
+            ]"#
+            .to_string(),
+        )
+        .await;
+        let inventory = cx.update(Inventory::new);
+        cx.run_until_parked();
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                fs.clone(),
+                |tracker, cx| StaticSource::new(PathBuf::from("/tasks.json"), tracker, cx),
+                cx,
+            );
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
 
-        register_task_used(&inventory, "2_task", cx);
         assert_eq!(
             list_task_names(&inventory, None, false, cx),
-            &expected_initial_state,
-            "Task list without lru sorting, should be sorted alphanumerically"
+            vec![
+                "ordered_build".to_string(),
+                "ordered_deploy".to_string(),
+                "ordered_test".to_string(),
+                "ordered_lint".to_string(),
+                "1_ordered_setup".to_string(),
+            ],
+            "Ordered tasks should go first, by their order and then by name, unordered ones should be sorted as before"
         );
+
+        register_task_used(&inventory, "ordered_lint", cx);
         assert_eq!(
             list_task_names(&inventory, None, true, cx),
             vec![
-                "2_task".to_string(),
-                "1_a_task".to_string(),
-                "1_task".to_string(),
-                "3_task".to_string()
+                "ordered_lint".to_string(),
+                "ordered_build".to_string(),
+                "ordered_deploy".to_string(),
+                "ordered_test".to_string(),
+                "1_ordered_setup".to_string(),
             ],
+            "Recently used tasks should still be listed before the ordered ones"
         );
+    }
 
-        register_task_used(&inventory, "1_task", cx);
-        register_task_used(&inventory, "1_task", cx);
-        register_task_used(&inventory, "1_task", cx);
-        register_task_used(&inventory, "3_task", cx);
+    #[gpui::test]
+    async fn test_static_task_ids_after_reload(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/tasks.json",
+            r#"[
+                {"label": "build", "command": "cargo build"},
+                {"label": "test", "command": "cargo test"},
+                {"label": "test", "command": "cargo test --release"}
+            ]"#
+            .to_string(),
+        )
+        .await;
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(PathBuf::from("/tasks.json")),
+                fs.clone(),
+                |tracker, cx| StaticSource::new(PathBuf::from("/tasks.json"), tracker, cx),
+                cx,
+            );
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        let task_ids = |cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let mut task_ids = inventory
+                    .list_tasks(None, None, false, false, cx)
+                    .into_iter()
+                    .map(|(_, task)| (task.name().to_string(), task.id().0.clone()))
+                    .collect::<Vec<_>>();
+                task_ids.sort();
+                task_ids
+            })
+        };
+        let initial_ids = task_ids(cx);
         assert_eq!(
-            list_task_names(&inventory, None, false, cx),
-            &expected_initial_state,
-            "Task list without lru sorting, should be sorted alphanumerically"
+            initial_ids,
+            vec![
+                ("build".to_string(), "static_/tasks.json_build".to_string()),
+                ("test".to_string(), "static_/tasks.json_test".to_string()),
+                ("test".to_string(), "static_/tasks.json_test#1".to_string()),
+            ]
         );
+
+        fs.insert_file(
+            "/tasks.json",
+            r#"[
+                {"label": "lint", "command": "cargo clippy"},
+                {"label": "test", "command": "cargo test"},
+                {"label": "test", "command": "cargo test --release"},
+                {"label": "build", "command": "cargo build --locked"}
+            ]"#
+            .to_string(),
+        )
+        .await;
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        let reloaded_ids = task_ids(cx);
         assert_eq!(
-            list_task_names(&inventory, None, true, cx),
-            vec![
-                "3_task".to_string(),
-                "1_task".to_string(),
-                "2_task".to_string(),
-                "1_a_task".to_string(),
-            ],
+            reloaded_ids
+                .iter()
+                .filter(|(name, _)| name != "lint")
+                .cloned()
+                .collect::<Vec<_>>(),
+            initial_ids,
+            "Reloaded tasks should keep their ids, regardless of their positions and changes in the file"
         );
+    }
 
+    #[gpui::test]
+    async fn test_static_source_errors(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "build", "command": "cargo build"}]"#.to_string(),
+        )
+        .await;
+        let inventory = cx.update(Inventory::new);
+        let source_kind = TaskSourceKind::AbsPath(PathBuf::from("/tasks.json"));
         inventory.update(cx, |inventory, cx| {
-            inventory.add_source(
-                TestSource::new(vec!["10_hello".to_string(), "11_hello".to_string()], cx),
+            inventory.add_static_source(
+                source_kind.clone(),
+                fs.clone(),
+                |tracker, cx| StaticSource::new(PathBuf::from("/tasks.json"), tracker, cx),
                 cx,
             );
         });
-        let expected_updated_state = [
-            "1_a_task".to_string(),
-            "1_task".to_string(),
-            "2_task".to_string(),
-            "3_task".to_string(),
-            "10_hello".to_string(),
-            "11_hello".to_string(),
-        ];
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert!(inventory
+            .update(cx, |inventory, cx| inventory.source_errors(cx))
+            .is_empty());
+
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "test", "commnd": "cargo test"}]"#.to_string(),
+        )
+        .await;
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        let errors = inventory.update(cx, |inventory, cx| inventory.source_errors(cx));
+        assert_eq!(errors.len(), 1, "Malformed file should be reported");
+        assert_eq!(errors[0].kind, source_kind);
+        assert!(
+            errors[0].message.contains("command"),
+            "Unexpected error message: {}",
+            errors[0].message
+        );
         assert_eq!(
             list_task_names(&inventory, None, false, cx),
-            &expected_updated_state,
-            "Task list without lru sorting, should be sorted alphanumerically"
+            vec!["build".to_string()],
+            "Tasks from the last successfully parsed file should be kept"
+        );
+
+        fs.insert_file(
+            "/tasks.json",
+            r#"[{"label": "test", "command": "cargo test"}]"#.to_string(),
+        )
+        .await;
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert!(
+            inventory
+                .update(cx, |inventory, cx| inventory.source_errors(cx))
+                .is_empty(),
+            "Error should be cleared after the file is fixed"
+        );
+        assert_eq!(
+            list_task_names(&inventory, None, false, cx),
+            vec!["test".to_string()]
         );
+    }
+
+    #[gpui::test]
+    async fn test_refreshing_static_sources(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(
+            "/first.json",
+            r#"[{"label": "build", "command": "cargo build"}]"#.to_string(),
+        )
+        .await;
+        fs.insert_file(
+            "/second.json",
+            r#"[{"label": "test", "command": "cargo test"}]"#.to_string(),
+        )
+        .await;
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            for path in ["/first.json", "/second.json"] {
+                inventory.add_static_source(
+                    TaskSourceKind::AbsPath(PathBuf::from(path)),
+                    fs.clone(),
+                    |tracker, cx| StaticSource::new(PathBuf::from(path), tracker, cx),
+                    cx,
+                );
+            }
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        register_task_used(&inventory, "test", cx);
         assert_eq!(
             list_task_names(&inventory, None, true, cx),
-            vec![
-                "3_task".to_string(),
-                "1_task".to_string(),
-                "2_task".to_string(),
-                "1_a_task".to_string(),
-                "10_hello".to_string(),
-                "11_hello".to_string(),
-            ],
+            vec!["test".to_string(), "build".to_string()]
         );
 
-        register_task_used(&inventory, "11_hello", cx);
+        fs.pause_events();
+        fs.insert_file(
+            "/first.json",
+            r#"[{"label": "build", "command": "cargo build"}, {"label": "check", "command": "cargo check"}]"#.to_string(),
+        )
+        .await;
+        fs.insert_file(
+            "/second.json",
+            r#"[{"label": "test", "command": "cargo test"}, {"label": "lint", "command": "cargo clippy"}]"#.to_string(),
+        )
+        .await;
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
         assert_eq!(
-            list_task_names(&inventory, None, false, cx),
-            &expected_updated_state,
-            "Task list without lru sorting, should be sorted alphanumerically"
+            list_task_names(&inventory, None, true, cx),
+            vec!["test".to_string(), "build".to_string()],
+            "Without file events, sources should not pick up the changes"
+        );
+
+        inventory.update(cx, |inventory, cx| {
+            inventory.refresh_source_for_path(Path::new("/first.json"), cx)
+        });
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        assert_eq!(
+            list_task_names(&inventory, None, true, cx),
+            vec!["test".to_string(), "build".to_string(), "check".to_string()],
+            "Only the requested source should be refreshed"
         );
+
+        inventory.update(cx, |inventory, cx| inventory.refresh_sources(cx));
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
         assert_eq!(
             list_task_names(&inventory, None, true, cx),
             vec![
-                "11_hello".to_string(),
-                "3_task".to_string(),
-                "1_task".to_string(),
-                "2_task".to_string(),
-                "1_a_task".to_string(),
-                "10_hello".to_string(),
+                "test".to_string(),
+                "build".to_string(),
+                "check".to_string(),
+                "lint".to_string(),
             ],
+            "All sources should be refreshed, keeping the task history"
         );
     }
 
@@ -274,6 +4476,9 @@ mod tests {
     struct TestTask {
         id: TaskId,
         name: String,
+        dependencies: Vec<String>,
+        source_order: Option<usize>,
+        hidden: bool,
     }
 
     impl Task for TestTask {
@@ -289,6 +4494,18 @@ mod tests {
             None
         }
 
+        fn dependencies(&self) -> Vec<String> {
+            self.dependencies.clone()
+        }
+
+        fn source_order(&self) -> Option<usize> {
+            self.source_order
+        }
+
+        fn hidden(&self) -> bool {
+            self.hidden
+        }
+
         fn exec(&self, _cwd: Option<PathBuf>) -> Option<task::SpawnInTerminal> {
             None
         }
@@ -296,12 +4513,34 @@ mod tests {
 
     struct TestSource {
         tasks: Vec<TestTask>,
+        /// If set, the tasks are only returned for this path.
+        path_scope: Option<PathBuf>,
     }
 
     impl TestSource {
         fn new(
             task_names: impl IntoIterator<Item = String>,
             cx: &mut AppContext,
+        ) -> Model<Box<dyn TaskSource>> {
+            Self::new_scoped(task_names, None, cx)
+        }
+
+        fn new_scoped(
+            task_names: impl IntoIterator<Item = String>,
+            path_scope: Option<PathBuf>,
+            cx: &mut AppContext,
+        ) -> Model<Box<dyn TaskSource>> {
+            Self::new_with_dependencies(
+                task_names.into_iter().map(|name| (name, Vec::new())),
+                path_scope,
+                cx,
+            )
+        }
+
+        /// A source with the natural order of its tasks, the order of the names given.
+        fn new_ordered(
+            task_names: impl IntoIterator<Item = String>,
+            cx: &mut AppContext,
         ) -> Model<Box<dyn TaskSource>> {
             cx.new_model(|_| {
                 Box::new(Self {
@@ -311,8 +4550,35 @@ mod tests {
                         .map(|(i, name)| TestTask {
                             id: TaskId(format!("task_{i}_{name}")),
                             name,
+                            dependencies: Vec::new(),
+                            source_order: Some(i),
+                            hidden: false,
+                        })
+                        .collect(),
+                    path_scope: None,
+                }) as Box<dyn TaskSource>
+            })
+        }
+
+        fn new_with_dependencies(
+            tasks: impl IntoIterator<Item = (String, Vec<String>)>,
+            path_scope: Option<PathBuf>,
+            cx: &mut AppContext,
+        ) -> Model<Box<dyn TaskSource>> {
+            cx.new_model(|_| {
+                Box::new(Self {
+                    tasks: tasks
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, (name, dependencies))| TestTask {
+                            id: TaskId(format!("task_{i}_{name}")),
+                            name,
+                            dependencies,
+                            source_order: None,
+                            hidden: false,
                         })
                         .collect(),
+                    path_scope,
                 }) as Box<dyn TaskSource>
             })
         }
@@ -321,9 +4587,12 @@ mod tests {
     impl TaskSource for TestSource {
         fn tasks_for_path(
             &mut self,
-            _path: Option<&Path>,
+            path: Option<&Path>,
             _cx: &mut ModelContext<Box<dyn TaskSource>>,
         ) -> Vec<Arc<dyn Task>> {
+            if self.path_scope.is_some() && self.path_scope.as_deref() != path {
+                return Vec::new();
+            }
             self.tasks
                 .clone()
                 .into_iter()
@@ -336,6 +4605,94 @@ mod tests {
         }
     }
 
+    /// A source, attributing its tasks to the files they are discovered in.
+    struct DiscoveringTestSource {
+        tasks: Vec<(Option<PathBuf>, TestTask)>,
+    }
+
+    impl DiscoveringTestSource {
+        fn new(
+            tasks: impl IntoIterator<Item = (&'static str, Option<&'static str>)>,
+            cx: &mut AppContext,
+        ) -> Model<Box<dyn TaskSource>> {
+            cx.new_model(|_| {
+                Box::new(Self {
+                    tasks: tasks
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, (name, origin))| {
+                            let task = TestTask {
+                                id: TaskId(format!("task_{i}_{name}")),
+                                name: name.to_string(),
+                                dependencies: Vec::new(),
+                                source_order: None,
+                                hidden: false,
+                            };
+                            (origin.map(PathBuf::from), task)
+                        })
+                        .collect(),
+                }) as Box<dyn TaskSource>
+            })
+        }
+    }
+
+    impl TaskSource for DiscoveringTestSource {
+        fn tasks_for_path(
+            &mut self,
+            path: Option<&Path>,
+            cx: &mut ModelContext<Box<dyn TaskSource>>,
+        ) -> Vec<Arc<dyn Task>> {
+            self.tasks_for_path_with_origins(path, cx)
+                .map(|tasks| tasks.into_iter().map(|(_, task)| task).collect())
+                .unwrap_or_default()
+        }
+
+        fn tasks_for_path_with_origins(
+            &mut self,
+            _path: Option<&Path>,
+            _cx: &mut ModelContext<Box<dyn TaskSource>>,
+        ) -> anyhow::Result<Vec<(Option<PathBuf>, Arc<dyn Task>)>> {
+            Ok(self
+                .tasks
+                .iter()
+                .map(|(origin, task)| (origin.clone(), Arc::new(task.clone()) as Arc<dyn Task>))
+                .collect())
+        }
+
+        fn as_any(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    /// A source, failing to list its tasks.
+    struct FailingTestSource;
+
+    impl TaskSource for FailingTestSource {
+        fn tasks_for_path(
+            &mut self,
+            _: Option<&Path>,
+            _: &mut ModelContext<Box<dyn TaskSource>>,
+        ) -> Vec<Arc<dyn Task>> {
+            Vec::new()
+        }
+
+        fn tasks_for_path_with_origins(
+            &mut self,
+            _: Option<&Path>,
+            _: &mut ModelContext<Box<dyn TaskSource>>,
+        ) -> anyhow::Result<Vec<(Option<PathBuf>, Arc<dyn Task>)>> {
+            anyhow::bail!("Failed to list tasks")
+        }
+
+        fn error(&self, _: &AppContext) -> Option<String> {
+            Some("Failed to list tasks".to_string())
+        }
+
+        fn as_any(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
     fn list_task_names(
         inventory: &Model<Inventory>,
         path: Option<&Path>,
@@ -344,21 +4701,31 @@ mod tests {
     ) -> Vec<String> {
         inventory.update(cx, |inventory, cx| {
             inventory
-                .list_tasks(path, lru, cx)
+                .list_tasks(path, None, lru, false, cx)
                 .into_iter()
-                .map(|task| task.name().to_string())
+                .map(|(_, task)| task.name().to_string())
+                .collect()
+        })
+    }
+
+    fn history_ids(inventory: &Model<Inventory>, cx: &mut TestAppContext) -> Vec<String> {
+        inventory.read_with(cx, |inventory, _| {
+            inventory
+                .last_scheduled_tasks
+                .iter()
+                .map(|scheduled_task| scheduled_task.id.0.clone())
                 .collect()
         })
     }
 
     fn register_task_used(inventory: &Model<Inventory>, task_name: &str, cx: &mut TestAppContext) {
         inventory.update(cx, |inventory, cx| {
-            let task = inventory
-                .list_tasks(None, false, cx)
+            let (source_kind, task) = inventory
+                .list_tasks(None, None, false, false, cx)
                 .into_iter()
-                .find(|task| task.name() == task_name)
+                .find(|(_, task)| task.name() == task_name)
                 .unwrap_or_else(|| panic!("Failed to find task with name {task_name}"));
-            inventory.task_scheduled(task.id().clone());
+            inventory.task_scheduled(source_kind, task.id().clone(), None, cx);
         });
     }
 }
@@ -1,8 +1,16 @@
 use crate::Project;
-use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, WeakModel};
+use anyhow::Context as _;
+use collections::HashMap;
+use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, WeakModel};
 use settings::Settings;
 use smol::channel::bounded;
-use std::path::{Path, PathBuf};
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::SystemTime,
+};
+use task::{SpawnInTerminal, TaskExitStatus};
 use terminal::{
     terminal_settings::{self, Shell, TerminalSettings, VenvSettingsContent},
     SpawnTask, TaskState, Terminal, TerminalBuilder,
@@ -15,6 +23,14 @@ pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
 }
 
+/// What a task, run without a terminal, has printed, see [`Project::run_capturing_output`].
+#[derive(Debug, Clone)]
+pub struct CapturedOutput {
+    pub exit_status: TaskExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
 impl Project {
     pub fn create_terminal(
         &mut self,
@@ -32,13 +48,20 @@ impl Project {
         let python_settings = settings.detect_venv.clone();
         let (completion_tx, completion_rx) = bounded(1);
         let mut env = settings.env.clone();
+        let task_id = spawn_task.as_ref().map(|spawn_task| spawn_task.id.clone());
         let (spawn_task, shell) = if let Some(spawn_task) = spawn_task {
             env.extend(spawn_task.env);
             (
                 Some(TaskState {
                     id: spawn_task.id,
                     label: spawn_task.label,
+                    reuse_key: spawn_task.reuse_key,
+                    timeout: spawn_task.timeout,
+                    reveal: spawn_task.reveal,
+                    success_message: spawn_task.success_message,
+                    failure_message: spawn_task.failure_message,
                     completed: false,
+                    exit_status: None,
                     completion_rx,
                 }),
                 Shell::WithArguments {
@@ -68,8 +91,46 @@ impl Project {
                 .local_handles
                 .push(terminal_handle.downgrade());
 
+            if let Some(task_id) = &task_id {
+                let task_id = task_id.clone();
+                let started_at = SystemTime::now();
+                cx.subscribe(&terminal_handle, move |project, _, event, cx| {
+                    if let terminal::Event::TaskExited(exit_status) = event {
+                        project.tasks.update(cx, |inventory, cx| {
+                            inventory.task_exited(task_id.clone(), *exit_status, started_at, cx)
+                        });
+                    }
+                })
+                .detach();
+            }
+
+            // The task is deregistered once, either when its process exits or when its terminal is closed.
+            let task_finished = task_id.map(|task_id| {
+                self.tasks.update(cx, |inventory, cx| {
+                    inventory.task_started(task_id.clone(), cx)
+                });
+                let tasks = self.tasks.clone();
+                let finished = Rc::new(Cell::new(false));
+                move |cx: &mut AppContext| {
+                    if !finished.replace(true) {
+                        tasks.update(cx, |inventory, cx| inventory.task_finished(&task_id, cx));
+                    }
+                }
+            });
+            if let Some(task_finished) = task_finished.clone() {
+                cx.observe(&terminal_handle, move |_, terminal, cx| {
+                    if terminal.read(cx).task().map_or(true, |task| task.completed) {
+                        task_finished(cx);
+                    }
+                })
+                .detach();
+            }
+
             let id = terminal_handle.entity_id();
             cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
+                if let Some(task_finished) = &task_finished {
+                    task_finished(cx);
+                }
                 let handles = &mut project.terminals.local_handles;
 
                 if let Some(index) = handles
@@ -99,6 +160,98 @@ impl Project {
         terminal
     }
 
+    /// The environment, the processes in the terminals get: the one of Zed, with the overrides from the terminal settings.
+    pub fn terminal_env(&self, cx: &AppContext) -> HashMap<String, String> {
+        let mut env = std::env::vars().collect::<HashMap<_, _>>();
+        env.extend(TerminalSettings::get_global(cx).env.clone());
+        env
+    }
+
+    /// Runs the task given to its end without a terminal, collecting everything it prints.
+    /// The task is registered as running meanwhile, and its exit is recorded the same way as of the tasks in the terminals;
+    /// tasks that outlive their timeout are killed and fail without an exit code.
+    pub fn run_capturing_output(
+        &mut self,
+        spawn_in_terminal: SpawnInTerminal,
+        cx: &mut ModelContext<Self>,
+    ) -> gpui::Task<anyhow::Result<CapturedOutput>> {
+        if self.is_remote() {
+            return gpui::Task::ready(Err(anyhow::anyhow!(
+                "running tasks as a guest is not supported yet"
+            )));
+        }
+        let Some((program, args)) = spawn_in_terminal.non_interactive_program_and_args(|| {
+            match TerminalSettings::get_global(cx).shell.clone() {
+                Shell::System => std::env::var("SHELL").ok().map(|shell| (shell, vec![])),
+                Shell::Program(shell) => Some((shell, vec![])),
+                Shell::WithArguments { program, args } => Some((program, args)),
+            }
+        }) else {
+            return gpui::Task::ready(Err(anyhow::anyhow!(
+                "no shell to run task {:?} in",
+                spawn_in_terminal.label
+            )));
+        };
+        let mut env = self.terminal_env(cx);
+        env.extend(spawn_in_terminal.env.clone());
+        let mut command = smol::process::Command::new(&program);
+        command
+            .args(&args)
+            .envs(env)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true);
+        if let Some(cwd) = &spawn_in_terminal.cwd {
+            command.current_dir(cwd);
+        }
+
+        let task_id = spawn_in_terminal.id.clone();
+        let started_at = SystemTime::now();
+        self.tasks.update(cx, |inventory, cx| {
+            inventory.task_started(task_id.clone(), cx)
+        });
+        let timeout = spawn_in_terminal.timeout;
+        let tasks = self.tasks.clone();
+        cx.spawn(|_, mut cx| async move {
+            let run = async { Some(command.output().await) };
+            // Dropping the unfinished run kills the process.
+            let output = match timeout {
+                Some(timeout) => {
+                    let timer = cx.background_executor().timer(timeout);
+                    smol::future::or(run, async {
+                        timer.await;
+                        None
+                    })
+                    .await
+                }
+                None => run.await,
+            };
+            let output = output
+                .transpose()
+                .with_context(|| format!("running program {program:?}"));
+            let exit_status = match &output {
+                Ok(Some(output)) if output.status.success() => TaskExitStatus::Success,
+                Ok(Some(output)) => TaskExitStatus::Failure(output.status.code()),
+                Ok(None) | Err(_) => TaskExitStatus::Failure(None),
+            };
+            tasks.update(&mut cx, |inventory, cx| {
+                inventory.task_exited(task_id.clone(), exit_status, started_at, cx);
+                inventory.task_finished(&task_id, cx);
+            })?;
+            Ok(match output? {
+                Some(output) => CapturedOutput {
+                    exit_status,
+                    stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                    stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                },
+                None => CapturedOutput {
+                    exit_status,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                },
+            })
+        })
+    }
+
     pub fn find_activate_script_path(
         &mut self,
         settings: &VenvSettingsContent,
@@ -0,0 +1,284 @@
+//! A source of tasks, based on the shell aliases, declared in a user-specified file.
+//! Both the shell syntax (`alias name='command'`) and a simpler `name = command` one are understood,
+//! lines that are neither are skipped, so a whole shell dotfile can be used as an alias file.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::channel::mpsc::UnboundedReceiver;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription};
+
+use crate::{static_source::TrackedFile, SpawnInTerminal, Task, TaskId, TaskSource};
+
+/// A command, run under the name of its alias.
+#[derive(Clone, Debug, PartialEq)]
+struct AliasTask {
+    id: TaskId,
+    name: String,
+    command: String,
+    source_order: usize,
+}
+
+impl Task for AliasTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        None
+    }
+
+    fn source_order(&self) -> Option<usize> {
+        Some(self.source_order)
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
+        Some(SpawnInTerminal {
+            cwd,
+            env: self.env(),
+            // Aliases are shell command lines, with pipes, chains and the like, so the shell has to run them.
+            separate_shell: true,
+            ..SpawnInTerminal::new(self.id.clone(), self.name.clone(), self.command.clone())
+        })
+    }
+}
+
+/// Aliases, declared in the file, as `(name, command)` pairs in the order of their first declaration.
+/// Redeclared aliases keep their position, but get the command of the last declaration, same as in the shell.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Aliases(Vec<(String, String)>);
+
+impl Aliases {
+    fn parse(contents: &str) -> anyhow::Result<Self> {
+        let mut aliases = Vec::<(String, String)>::new();
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let alias = match line.strip_prefix("alias ") {
+                Some(declaration) => shell_alias(declaration),
+                None => simple_alias(line),
+            };
+            let Some((name, command)) = alias else {
+                log::debug!(
+                    "Skipping line {} of the alias file: {line:?}",
+                    line_number + 1
+                );
+                continue;
+            };
+            match aliases
+                .iter_mut()
+                .find(|(known_name, _)| known_name == &name)
+            {
+                Some((_, known_command)) => *known_command = command,
+                None => aliases.push((name, command)),
+            }
+        }
+        Ok(Self(aliases))
+    }
+
+    fn tasks(&self, alias_file: &Path) -> Vec<AliasTask> {
+        let ids = TaskId::for_tasks_file(
+            "alias",
+            alias_file,
+            self.0.iter().map(|(name, _)| name.as_str()),
+        );
+        ids.into_iter()
+            .zip(&self.0)
+            .enumerate()
+            .map(|(source_order, (id, (name, command)))| AliasTask {
+                id,
+                name: name.clone(),
+                command: command.clone(),
+                source_order,
+            })
+            .collect()
+    }
+}
+
+/// Parses the `name='command'` part of the `alias name='command'` line, unquoting the command as the shell does.
+fn shell_alias(declaration: &str) -> Option<(String, String)> {
+    let (name, value) = declaration.trim_start().split_once('=')?;
+    let command = unquote(value)?;
+    alias_with_name(name, command)
+}
+
+/// Parses the `name = command` line, the command is either quoted or taken as is, up to the end of the line.
+/// The spaces around `=` are required, to tell the aliases from the shell variable assignments, e.g. `EDITOR=vim`.
+fn simple_alias(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.split_once('=')?;
+    if !name.ends_with(char::is_whitespace) || !value.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let value = value.trim();
+    let command = if value.starts_with(['\'', '"']) {
+        unquote(value)?
+    } else {
+        value.to_string()
+    };
+    alias_with_name(name.trim_end(), command)
+}
+
+fn alias_with_name(name: &str, command: String) -> Option<(String, String)> {
+    let is_alias_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+    (is_alias_name && !command.trim().is_empty()).then(|| (name.to_string(), command))
+}
+
+/// Unquotes a single shell word, e.g. `'git status'` or `"echo \"hi\""`, possibly made of several quoted parts,
+/// as in `'it'\''s'`. The word may be followed by a comment, anything else after it makes the word malformed.
+fn unquote(word: &str) -> Option<String> {
+    let mut unquoted = String::with_capacity(word.len());
+    let mut chars = word.chars();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open_quote), c) if c == open_quote => quote = None,
+            (Some('"'), '\\') => match chars.next()? {
+                escaped @ ('"' | '\\' | '$' | '`') => unquoted.push(escaped),
+                other => {
+                    unquoted.push('\\');
+                    unquoted.push(other);
+                }
+            },
+            (Some(_), c) => unquoted.push(c),
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '\\') => unquoted.push(chars.next()?),
+            (None, c) if c.is_whitespace() => {
+                let rest = chars.as_str().trim_start();
+                return (rest.is_empty() || rest.starts_with('#')).then_some(unquoted);
+            }
+            (None, c) => unquoted.push(c),
+        }
+    }
+    quote.is_none().then_some(unquoted)
+}
+
+/// The source of tasks, running the shell aliases, declared in a file.
+pub struct AliasTaskSource {
+    tasks: Vec<AliasTask>,
+    aliases: Model<TrackedFile<Aliases>>,
+    _subscription: Subscription,
+}
+
+impl AliasTaskSource {
+    /// Initializes the alias source, reacting on the alias file changes.
+    /// The path of the alias file makes the ids of its tasks, see [`TaskId::for_tasks_file`].
+    pub fn new(
+        alias_file: PathBuf,
+        alias_file_tracker: UnboundedReceiver<String>,
+        cx: &mut AppContext,
+    ) -> Model<Box<dyn TaskSource>> {
+        let aliases = TrackedFile::new_with_parser(
+            Aliases::default(),
+            alias_file_tracker,
+            Aliases::parse,
+            cx,
+        );
+        cx.new_model(|cx| {
+            let _subscription = cx.observe(
+                &aliases,
+                move |source: &mut Box<(dyn TaskSource + 'static)>, new_aliases, cx| {
+                    if let Some(alias_source) = source.as_any().downcast_mut::<Self>() {
+                        alias_source.tasks = new_aliases.read(cx).get().tasks(&alias_file);
+                        cx.notify();
+                    }
+                },
+            );
+            Box::new(Self {
+                tasks: Vec::new(),
+                aliases,
+                _subscription,
+            })
+        })
+    }
+}
+
+impl TaskSource for AliasTaskSource {
+    fn tasks_for_path(
+        &mut self,
+        _: Option<&Path>,
+        _: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Vec<Arc<dyn Task>> {
+        self.tasks
+            .clone()
+            .into_iter()
+            .map(|task| Arc::new(task) as Arc<dyn Task>)
+            .collect()
+    }
+
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn error(&self, cx: &AppContext) -> Option<String> {
+        self.aliases.read(cx).error().map(ToOwned::to_owned)
+    }
+
+    fn is_loading(&self, cx: &AppContext) -> bool {
+        self.aliases.read(cx).is_loading()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_aliases() {
+        let alias_file = "# Git shortcuts\n\
+            alias gs='git status'\n\
+            alias gl=\"git log --oneline | head -n \\\"20\\\"\" # recent commits\n\
+            \x20\x20alias ll=ls\\ -la\n\
+            alias say='echo it'\\''s done'\n\
+            export PATH=\"$HOME/bin:$PATH\"\n\
+            EDITOR=vim\n\
+            alias broken='unterminated\n\
+            alias trailing='ls' -la\n\
+            alias =nameless\n\
+            serve = python3 -m http.server 8000\n\
+            check = \"cargo check --all\"\n\
+            gs = git status --short\n\
+            not an alias line\n";
+
+        let aliases = Aliases::parse(alias_file).unwrap();
+        assert_eq!(
+            aliases,
+            Aliases(vec![
+                ("gs".to_string(), "git status --short".to_string()),
+                (
+                    "gl".to_string(),
+                    "git log --oneline | head -n \"20\"".to_string()
+                ),
+                ("ll".to_string(), "ls -la".to_string()),
+                ("say".to_string(), "echo it's done".to_string()),
+                (
+                    "serve".to_string(),
+                    "python3 -m http.server 8000".to_string()
+                ),
+                ("check".to_string(), "cargo check --all".to_string()),
+            ]),
+            "Malformed lines should be skipped, redeclared aliases should get their last command"
+        );
+
+        let tasks = aliases.tasks(Path::new("/home/user/.aliases"));
+        let serve_task = tasks.iter().find(|task| task.name() == "serve").unwrap();
+        assert_eq!(
+            serve_task.id().0,
+            "alias_/home/user/.aliases_serve".to_string()
+        );
+        let spawn = serve_task.exec(None).unwrap();
+        assert_eq!(spawn.command, "python3 -m http.server 8000");
+        assert!(spawn.args.is_empty());
+        assert!(spawn.separate_shell);
+    }
+}
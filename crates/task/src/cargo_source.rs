@@ -0,0 +1,297 @@
+//! A source of tasks, based on the targets of a `Cargo.toml` manifest.
+//! Only the targets declared in the manifest are turned into tasks, the ones that Cargo discovers automatically are not.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::channel::mpsc::UnboundedReceiver;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription};
+use serde::Deserialize;
+
+use crate::{static_source::TrackedFile, SpawnInTerminal, Task, TaskId, TaskSource};
+
+/// A single `cargo` invocation.
+#[derive(Clone, Debug, PartialEq)]
+struct CargoTask {
+    id: TaskId,
+    name: String,
+    args: Vec<String>,
+    manifest_dir: PathBuf,
+}
+
+impl Task for CargoTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        Some(&self.manifest_dir)
+    }
+
+    fn icon(&self) -> Option<String> {
+        Some("rust".to_owned())
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
+        Some(SpawnInTerminal {
+            args: self.args.clone(),
+            cwd,
+            env: self.env(),
+            ..SpawnInTerminal::new(self.id.clone(), self.name.clone(), "cargo".to_owned())
+        })
+    }
+}
+
+/// Contents of the `Cargo.toml` manifest, relevant to the tasks.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+struct CargoManifest {
+    #[serde(default)]
+    package: Option<CargoPackage>,
+    #[serde(default)]
+    workspace: Option<CargoWorkspace>,
+    #[serde(default)]
+    bin: Vec<CargoTarget>,
+    #[serde(default)]
+    example: Vec<CargoTarget>,
+    #[serde(default)]
+    test: Vec<CargoTarget>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct CargoPackage {
+    name: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+struct CargoWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct CargoTarget {
+    #[serde(default)]
+    name: Option<String>,
+}
+
+impl CargoManifest {
+    fn parse(contents: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    fn tasks(&self, manifest_dir: &Path, workspace_root: bool) -> Vec<CargoTask> {
+        let mut args = Vec::<Vec<&str>>::new();
+        if workspace_root {
+            args.push(vec!["build"]);
+            args.push(vec!["test"]);
+            if self.package.is_some() {
+                args.push(vec!["run"]);
+            }
+        } else if let Some(package) = &self.package {
+            args.push(vec!["test", "-p", package.name.as_str()]);
+        }
+        let targets = [
+            (&self.bin, ["run", "--bin"]),
+            (&self.example, ["run", "--example"]),
+            (&self.test, ["test", "--test"]),
+        ];
+        for (declared_targets, target_args) in targets {
+            for name in declared_targets
+                .iter()
+                .filter_map(|target| target.name.as_deref())
+            {
+                args.push(target_args.iter().copied().chain([name]).collect());
+            }
+        }
+
+        args.into_iter()
+            .map(|args| {
+                let name = format!("cargo {}", args.join(" "));
+                CargoTask {
+                    id: TaskId(format!("cargo_{}_{name}", manifest_dir.to_string_lossy())),
+                    name,
+                    args: args.into_iter().map(ToOwned::to_owned).collect(),
+                    manifest_dir: manifest_dir.to_path_buf(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The source of tasks, building, testing and running the targets of a Cargo package or workspace.
+pub struct CargoTaskSource {
+    tasks: Vec<CargoTask>,
+    manifest: Model<TrackedFile<CargoManifest>>,
+    _subscription: Subscription,
+}
+
+impl CargoTaskSource {
+    /// Initializes the Cargo source, reacting on manifest changes.
+    /// The tasks are run in the directory given, which is expected to contain the `Cargo.toml`.
+    /// Workspace-wide tasks (`cargo build`, `cargo test`) are only produced for the workspace root manifest,
+    /// other workspace members get tasks for their own package and targets only.
+    pub fn new(
+        manifest_dir: PathBuf,
+        workspace_root: bool,
+        manifest_tracker: UnboundedReceiver<String>,
+        cx: &mut AppContext,
+    ) -> Model<Box<dyn TaskSource>> {
+        let manifest = TrackedFile::new_with_parser(
+            CargoManifest::default(),
+            manifest_tracker,
+            CargoManifest::parse,
+            cx,
+        );
+        cx.new_model(|cx| {
+            let _subscription = cx.observe(
+                &manifest,
+                move |source: &mut Box<(dyn TaskSource + 'static)>, new_manifest, cx| {
+                    if let Some(cargo_source) = source.as_any().downcast_mut::<Self>() {
+                        cargo_source.tasks = new_manifest
+                            .read(cx)
+                            .get()
+                            .tasks(&manifest_dir, workspace_root);
+                        cx.notify();
+                    }
+                },
+            );
+            Box::new(Self {
+                tasks: Vec::new(),
+                manifest,
+                _subscription,
+            })
+        })
+    }
+
+    /// Member paths (possibly, globs), declared in the `[workspace]` section of the manifest given.
+    /// Returns no members for manifests without the section, or ones that fail to parse.
+    pub fn workspace_members(manifest_contents: &str) -> Vec<String> {
+        CargoManifest::parse(manifest_contents)
+            .ok()
+            .and_then(|manifest| manifest.workspace)
+            .map(|workspace| workspace.members)
+            .unwrap_or_default()
+    }
+}
+
+impl TaskSource for CargoTaskSource {
+    fn tasks_for_path(
+        &mut self,
+        _: Option<&Path>,
+        _: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Vec<Arc<dyn Task>> {
+        self.tasks
+            .clone()
+            .into_iter()
+            .map(|task| Arc::new(task) as Arc<dyn Task>)
+            .collect()
+    }
+
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn error(&self, cx: &AppContext) -> Option<String> {
+        self.manifest.read(cx).error().map(ToOwned::to_owned)
+    }
+
+    fn is_loading(&self, cx: &AppContext) -> bool {
+        self.manifest.read(cx).is_loading()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task_names(manifest: &str, workspace_root: bool) -> Vec<String> {
+        CargoManifest::parse(manifest)
+            .unwrap()
+            .tasks(Path::new("/project"), workspace_root)
+            .into_iter()
+            .map(|task| task.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_parsing_cargo_manifests() {
+        let package_manifest = r#"
+            [package]
+            name = "server"
+            version = "0.1.0"
+
+            [dependencies]
+            anyhow = "1.0"
+
+            [[bin]]
+            name = "server"
+            path = "src/main.rs"
+
+            [[bin]]
+            name = "migrate"
+
+            [[example]]
+            name = "echo"
+
+            [[test]]
+            name = "integration"
+        "#;
+        assert_eq!(
+            task_names(package_manifest, true),
+            vec![
+                "cargo build",
+                "cargo test",
+                "cargo run",
+                "cargo run --bin server",
+                "cargo run --bin migrate",
+                "cargo run --example echo",
+                "cargo test --test integration",
+            ]
+        );
+        assert_eq!(
+            task_names(package_manifest, false),
+            vec![
+                "cargo test -p server",
+                "cargo run --bin server",
+                "cargo run --bin migrate",
+                "cargo run --example echo",
+                "cargo test --test integration",
+            ],
+            "Workspace members should not get workspace-wide tasks"
+        );
+
+        let virtual_manifest = r#"
+            [workspace]
+            members = ["crates/*", "tools/xtask"]
+        "#;
+        assert_eq!(
+            task_names(virtual_manifest, true),
+            vec!["cargo build", "cargo test"],
+            "Virtual workspaces have no root package to run"
+        );
+        assert_eq!(
+            CargoTaskSource::workspace_members(virtual_manifest),
+            vec!["crates/*".to_string(), "tools/xtask".to_string()]
+        );
+        assert!(CargoTaskSource::workspace_members("[package").is_empty());
+
+        let run_task = CargoManifest::parse(package_manifest)
+            .unwrap()
+            .tasks(Path::new("/project"), true)
+            .into_iter()
+            .find(|task| task.name == "cargo run --bin migrate")
+            .unwrap();
+        let spawn = run_task
+            .exec(run_task.cwd().map(Path::to_path_buf))
+            .unwrap();
+        assert_eq!(spawn.command, "cargo");
+        assert_eq!(spawn.args, vec!["run", "--bin", "migrate"]);
+        assert_eq!(spawn.cwd, Some(PathBuf::from("/project")));
+    }
+}
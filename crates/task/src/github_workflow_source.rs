@@ -0,0 +1,520 @@
+//! A source of tasks, based on the `run:` steps of a GitHub Actions workflow in `.github/workflows`.
+//! Only the subset of YAML, used by the workflow files, is parsed: jobs and their steps, with plain, quoted and block scalars.
+//! Steps that use actions (`uses:`) are skipped, as they cannot be run locally.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context as _};
+use futures::channel::mpsc::UnboundedReceiver;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription};
+
+use crate::{static_source::TrackedFile, SpawnInTerminal, Task, TaskId, TaskShell, TaskSource};
+
+/// A single workflow step, run the way GitHub Actions runs it by default: with `bash -e`.
+#[derive(Clone, Debug, PartialEq)]
+struct WorkflowStepTask {
+    id: TaskId,
+    name: String,
+    script: String,
+    cwd: PathBuf,
+}
+
+impl Task for WorkflowStepTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        Some(&self.cwd)
+    }
+
+    fn icon(&self) -> Option<String> {
+        Some("github".to_owned())
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
+        Some(SpawnInTerminal {
+            cwd,
+            env: self.env(),
+            shell: TaskShell::WithArguments {
+                program: "bash".to_owned(),
+                args: vec!["-e".to_owned()],
+            },
+            ..SpawnInTerminal::new(self.id.clone(), self.name.clone(), self.script.clone())
+        })
+    }
+}
+
+/// A `run:` step of a workflow job.
+#[derive(Clone, Debug, PartialEq)]
+struct WorkflowStep {
+    job: String,
+    /// Position of the step in its job, starting from 1, to name the steps without names.
+    index: usize,
+    name: Option<String>,
+    run: String,
+    working_directory: Option<String>,
+}
+
+/// Keys of a step, being parsed.
+#[derive(Default)]
+struct StepKeys {
+    name: Option<String>,
+    run: Option<String>,
+    uses: Option<String>,
+    working_directory: Option<String>,
+}
+
+/// The `run:` steps of all jobs, declared in the workflow file, in the order of their appearance.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct WorkflowSteps(Vec<WorkflowStep>);
+
+impl WorkflowSteps {
+    fn parse(contents: &str) -> anyhow::Result<Self> {
+        let lines = contents.lines().collect::<Vec<_>>();
+        let mut parser = WorkflowParser::default();
+        let mut row = 0;
+        let mut found_jobs = false;
+        while row < lines.len() {
+            let line = lines[row];
+            row += 1;
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            if line[indent..].starts_with('\t') {
+                bail!("line {row}: tabs are not allowed for indentation");
+            }
+
+            if indent == 0 {
+                parser.finish_job();
+                parser.in_jobs = text == "jobs:";
+                found_jobs |= parser.in_jobs;
+                continue;
+            }
+            if !parser.in_jobs {
+                continue;
+            }
+            let job_indent = *parser.job_indent.get_or_insert(indent);
+            if indent < job_indent {
+                bail!("line {row}: unexpected indentation");
+            }
+            if indent == job_indent {
+                parser.finish_job();
+                let (job, _) =
+                    split_key(text).with_context(|| format!("line {row}: expected a job name"))?;
+                parser.job = Some(job.to_owned());
+                continue;
+            }
+            if parser.job.is_none() {
+                continue;
+            }
+
+            if let Some(steps_indent) = parser.steps_indent {
+                let is_item = text == "-" || text.starts_with("- ");
+                if is_item
+                    && indent >= steps_indent
+                    && parser
+                        .item_indent
+                        .map_or(true, |item_indent| item_indent == indent)
+                {
+                    parser.finish_step();
+                    parser.item_indent = Some(indent);
+                    parser.step = Some(StepKeys::default());
+                    parser.step_index += 1;
+                    let rest = text[1..].trim_start();
+                    parser.key_indent = None;
+                    if !rest.is_empty() {
+                        let key_indent = line.len() - rest.len();
+                        parser.key_indent = Some(key_indent);
+                        row = parser.parse_step_key(rest, key_indent, &lines, row)?;
+                    }
+                    continue;
+                }
+                let item_indent = parser.item_indent.unwrap_or(steps_indent);
+                if indent > item_indent && parser.step.is_some() {
+                    let key_indent = *parser.key_indent.get_or_insert(indent);
+                    // Deeper lines are the values of the nested keys, e.g. `with:` or `env:`.
+                    if indent == key_indent {
+                        row = parser.parse_step_key(text, key_indent, &lines, row)?;
+                    }
+                    continue;
+                }
+                parser.finish_steps();
+            }
+            if text == "steps:" {
+                parser.steps_indent = Some(indent);
+            }
+        }
+        parser.finish_job();
+        if !found_jobs {
+            bail!("no jobs are declared in the workflow");
+        }
+        Ok(Self(parser.steps))
+    }
+
+    fn tasks(&self, workflow_name: &str, repository_root: &Path) -> Vec<WorkflowStepTask> {
+        self.0
+            .iter()
+            .map(|step| {
+                let step_name = match &step.name {
+                    Some(name) => name.clone(),
+                    None => format!("step {}", step.index),
+                };
+                WorkflowStepTask {
+                    id: TaskId(format!(
+                        "github_workflow_{}_{workflow_name}_{}_{}",
+                        repository_root.to_string_lossy(),
+                        step.job,
+                        step.index
+                    )),
+                    name: format!("{workflow_name}:{}:{step_name}", step.job),
+                    script: step.run.clone(),
+                    cwd: match &step.working_directory {
+                        Some(working_directory) => repository_root.join(working_directory),
+                        None => repository_root.to_path_buf(),
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+/// State of the workflow parsing: the job and the step the lines currently belong to.
+#[derive(Default)]
+struct WorkflowParser {
+    steps: Vec<WorkflowStep>,
+    in_jobs: bool,
+    job_indent: Option<usize>,
+    job: Option<String>,
+    steps_indent: Option<usize>,
+    item_indent: Option<usize>,
+    key_indent: Option<usize>,
+    step: Option<StepKeys>,
+    step_index: usize,
+}
+
+impl WorkflowParser {
+    /// Parses a `key: value` line of the current step, returning the row to continue parsing from:
+    /// block scalar values span the following lines.
+    fn parse_step_key(
+        &mut self,
+        text: &str,
+        key_indent: usize,
+        lines: &[&str],
+        mut row: usize,
+    ) -> anyhow::Result<usize> {
+        let Some((key, value)) = split_key(text) else {
+            return Ok(row);
+        };
+        let value = if value.starts_with(['|', '>']) {
+            let folded = value.starts_with('>');
+            let mut block_lines = Vec::new();
+            while let Some(line) = lines.get(row) {
+                let indent = line.len() - line.trim_start_matches(' ').len();
+                if !line.trim().is_empty() && indent <= key_indent {
+                    break;
+                }
+                block_lines.push(*line);
+                row += 1;
+            }
+            block_scalar(&block_lines, folded)
+        } else {
+            flow_scalar(value).with_context(|| format!("line {row}: malformed value"))?
+        };
+        if let Some(step) = &mut self.step {
+            match key {
+                "name" => step.name = Some(value),
+                "run" => step.run = Some(value),
+                "uses" => step.uses = Some(value),
+                "working-directory" => step.working_directory = Some(value),
+                _ => {}
+            }
+        }
+        Ok(row)
+    }
+
+    fn finish_step(&mut self) {
+        let Some(step) = self.step.take() else {
+            return;
+        };
+        let Some(job) = &self.job else {
+            return;
+        };
+        if step.uses.is_some() {
+            return;
+        }
+        if let Some(run) = step.run.filter(|run| !run.trim().is_empty()) {
+            self.steps.push(WorkflowStep {
+                job: job.clone(),
+                index: self.step_index,
+                name: step.name,
+                run,
+                working_directory: step.working_directory,
+            });
+        }
+    }
+
+    fn finish_steps(&mut self) {
+        self.finish_step();
+        self.steps_indent = None;
+        self.item_indent = None;
+        self.key_indent = None;
+    }
+
+    fn finish_job(&mut self) {
+        self.finish_steps();
+        self.job = None;
+        self.step_index = 0;
+    }
+}
+
+/// Splits the `key: value` mapping entry, the value may be empty.
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    let (key, value) = match text.split_once(": ") {
+        Some((key, value)) => (key, value.trim()),
+        None => (text.strip_suffix(':')?, ""),
+    };
+    let key = key.trim().trim_matches(['"', '\'']);
+    (!key.is_empty()).then_some((key, value))
+}
+
+/// Parses a single line scalar: a quoted or a plain one, with an optional trailing comment.
+fn flow_scalar(value: &str) -> anyhow::Result<String> {
+    if let Some(quoted) = value.strip_prefix('\'') {
+        let mut unquoted = String::new();
+        let mut chars = quoted.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    unquoted.push('\'');
+                }
+                '\'' => return Ok(unquoted),
+                c => unquoted.push(c),
+            }
+        }
+        bail!("unterminated single-quoted string");
+    }
+    if let Some(quoted) = value.strip_prefix('"') {
+        let mut unquoted = String::new();
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => unquoted.push('\n'),
+                    Some('t') => unquoted.push('\t'),
+                    Some(c) => unquoted.push(c),
+                    None => break,
+                },
+                '"' => return Ok(unquoted),
+                c => unquoted.push(c),
+            }
+        }
+        bail!("unterminated double-quoted string");
+    }
+    let value = match value.find(" #") {
+        Some(comment_start) => &value[..comment_start],
+        None => value,
+    };
+    Ok(value.trim().to_owned())
+}
+
+/// Joins the lines of a `|` (literal) or `>` (folded) block scalar, removing their common indentation.
+fn block_scalar(lines: &[&str], folded: bool) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .min()
+        .unwrap_or_default();
+    let mut lines = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default().trim_end())
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let mut scalar = String::new();
+    for line in lines {
+        if !scalar.is_empty() {
+            let separator = if folded && !line.is_empty() && !scalar.ends_with('\n') {
+                ' '
+            } else {
+                '\n'
+            };
+            scalar.push(separator);
+        }
+        scalar.push_str(line);
+    }
+    scalar
+}
+
+/// The source of tasks, running the steps of a GitHub Actions workflow.
+pub struct GithubWorkflowTaskSource {
+    tasks: Vec<WorkflowStepTask>,
+    steps: Model<TrackedFile<WorkflowSteps>>,
+    _subscription: Subscription,
+}
+
+impl GithubWorkflowTaskSource {
+    /// Initializes the workflow source, reacting on file changes.
+    /// The steps are named after the workflow given, and are run in the repository root given.
+    pub fn new(
+        workflow_name: String,
+        repository_root: PathBuf,
+        workflow_tracker: UnboundedReceiver<String>,
+        cx: &mut AppContext,
+    ) -> Model<Box<dyn TaskSource>> {
+        let steps = TrackedFile::new_with_parser(
+            WorkflowSteps::default(),
+            workflow_tracker,
+            WorkflowSteps::parse,
+            cx,
+        );
+        cx.new_model(|cx| {
+            let _subscription = cx.observe(
+                &steps,
+                move |source: &mut Box<(dyn TaskSource + 'static)>, new_steps, cx| {
+                    if let Some(workflow_source) = source.as_any().downcast_mut::<Self>() {
+                        workflow_source.tasks = new_steps
+                            .read(cx)
+                            .get()
+                            .tasks(&workflow_name, &repository_root);
+                        cx.notify();
+                    }
+                },
+            );
+            Box::new(Self {
+                tasks: Vec::new(),
+                steps,
+                _subscription,
+            })
+        })
+    }
+}
+
+impl TaskSource for GithubWorkflowTaskSource {
+    fn tasks_for_path(
+        &mut self,
+        _: Option<&Path>,
+        _: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Vec<Arc<dyn Task>> {
+        self.tasks
+            .clone()
+            .into_iter()
+            .map(|task| Arc::new(task) as Arc<dyn Task>)
+            .collect()
+    }
+
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn error(&self, cx: &AppContext) -> Option<String> {
+        self.steps.read(cx).error().map(ToOwned::to_owned)
+    }
+
+    fn is_loading(&self, cx: &AppContext) -> bool {
+        self.steps.read(cx).is_loading()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_workflow_steps() {
+        let workflow = "name: CI\n\
+            on:\n\
+            \x20 push:\n\
+            \x20   branches: [main]\n\
+            \n\
+            jobs:\n\
+            \x20 # Lints and tests\n\
+            \x20 check:\n\
+            \x20   runs-on: ubuntu-latest\n\
+            \x20   steps:\n\
+            \x20     - uses: actions/checkout@v4\n\
+            \x20     - name: Lint\n\
+            \x20       run: cargo clippy -- -D warnings # no warnings\n\
+            \x20     - name: 'Run tests'\n\
+            \x20       working-directory: crates/app\n\
+            \x20       run: |\n\
+            \x20         cargo test\n\
+            \x20         echo \"done: tests\"\n\
+            \n\
+            \x20       env:\n\
+            \x20         RUST_LOG: info\n\
+            \x20     - run: >-\n\
+            \x20         ./scripts/report.sh\n\
+            \x20         --verbose\n\
+            \x20 deploy:\n\
+            \x20   needs: check\n\
+            \x20   steps:\n\
+            \x20   - uses: actions/setup-node@v4\n\
+            \x20     with:\n\
+            \x20       run: not a step\n\
+            \x20   - name: Publish\n\
+            \x20     run: \"npm publish --tag \\\"next\\\"\"\n";
+
+        let steps = WorkflowSteps::parse(workflow).unwrap();
+        let tasks = steps.tasks("ci", Path::new("/project"));
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| (task.name().to_string(), task.script.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "ci:check:Lint".to_string(),
+                    "cargo clippy -- -D warnings".to_string()
+                ),
+                (
+                    "ci:check:Run tests".to_string(),
+                    "cargo test\necho \"done: tests\"".to_string()
+                ),
+                (
+                    "ci:check:step 4".to_string(),
+                    "./scripts/report.sh --verbose".to_string()
+                ),
+                (
+                    "ci:deploy:Publish".to_string(),
+                    "npm publish --tag \"next\"".to_string()
+                ),
+            ],
+            "Only the `run:` steps should be listed, with the steps without names named by their positions"
+        );
+
+        let tests_task = &tasks[1];
+        let spawn = tests_task
+            .exec(tests_task.cwd().map(Path::to_path_buf))
+            .unwrap();
+        assert_eq!(spawn.cwd, Some(PathBuf::from("/project/crates/app")));
+        assert_eq!(
+            spawn.program_and_args(|| None),
+            Some((
+                "bash".to_string(),
+                vec![
+                    "-e".to_string(),
+                    "-i".to_string(),
+                    "-c".to_string(),
+                    "cargo test\necho \"done: tests\"".to_string()
+                ]
+            ))
+        );
+
+        assert!(WorkflowSteps::parse("name: no jobs\non: push\n").is_err());
+        assert!(WorkflowSteps::parse("jobs:\n\tbuild:\n").is_err());
+        assert!(WorkflowSteps::parse("jobs:\n  build:\n    steps:\n      - run: 'echo\n").is_err());
+    }
+}
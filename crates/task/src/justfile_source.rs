@@ -0,0 +1,299 @@
+//! A source of tasks, based on the recipes of a `justfile`.
+//! The file is parsed directly, so the recipes are listed even before `just` is run,
+//! but the tasks are only usable with `just` installed: its absence is reported as the source error.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::channel::mpsc::UnboundedReceiver;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription};
+
+use crate::{static_source::TrackedFile, SpawnInTerminal, Task, TaskId, TaskSource};
+
+/// A single `just <recipe>` invocation.
+#[derive(Clone, Debug, PartialEq)]
+struct JustRecipeTask {
+    id: TaskId,
+    name: String,
+    recipe: String,
+    justfile_dir: PathBuf,
+    source_order: usize,
+}
+
+impl Task for JustRecipeTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        Some(&self.justfile_dir)
+    }
+
+    fn source_order(&self) -> Option<usize> {
+        Some(self.source_order)
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
+        Some(SpawnInTerminal {
+            args: vec![self.recipe.clone()],
+            cwd,
+            env: self.env(),
+            ..SpawnInTerminal::new(self.id.clone(), self.name.clone(), "just".to_owned())
+        })
+    }
+}
+
+/// A public recipe, declared in the `justfile`.
+#[derive(Clone, Debug, PartialEq)]
+struct JustRecipe {
+    name: String,
+    /// Parameters without default values, that have to be passed to the recipe.
+    required_parameters: Vec<String>,
+}
+
+/// Public recipes, declared in the `justfile`, in the order of their appearance.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct JustfileRecipes(Vec<JustRecipe>);
+
+impl JustfileRecipes {
+    fn parse(contents: &str) -> anyhow::Result<Self> {
+        let mut recipes = Vec::new();
+        let mut private_attribute = false;
+        for line in contents.lines() {
+            // Recipe bodies are indented, and neither they nor other indented lines declare recipes.
+            if line.starts_with(|c: char| c.is_whitespace()) || line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some(attributes) = line.strip_prefix('[') {
+                private_attribute |= attributes
+                    .trim_end_matches(']')
+                    .split(',')
+                    .any(|attribute| attribute.trim() == "private");
+                continue;
+            }
+            let is_private = std::mem::take(&mut private_attribute);
+            let Some(header) = recipe_header(line) else {
+                continue;
+            };
+            let mut words = header.trim_start_matches('@').split_whitespace();
+            let Some(name) = words.next() else {
+                continue;
+            };
+            let is_recipe_name = name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !is_recipe_name
+                || matches!(name, "alias" | "export" | "set" | "import" | "mod")
+                || name.starts_with('_')
+                || is_private
+            {
+                continue;
+            }
+            let required_parameters = words
+                .filter(|parameter| !parameter.contains('=') && !parameter.starts_with('*'))
+                .map(|parameter| parameter.trim_start_matches(['+', '$']).to_owned())
+                .collect();
+            recipes.push(JustRecipe {
+                name: name.to_owned(),
+                required_parameters,
+            });
+        }
+        Ok(Self(recipes))
+    }
+
+    fn tasks(&self, justfile_dir: &Path) -> Vec<JustRecipeTask> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(source_order, recipe)| {
+                // Recipes with required parameters fail without them, so the placeholders hint what to pass in a oneshot task.
+                let name = std::iter::once(format!("just {}", recipe.name))
+                    .chain(
+                        recipe
+                            .required_parameters
+                            .iter()
+                            .map(|parameter| format!("<{parameter}>")),
+                    )
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                JustRecipeTask {
+                    id: TaskId(format!(
+                        "just_{}_{}",
+                        justfile_dir.to_string_lossy(),
+                        recipe.name
+                    )),
+                    name,
+                    recipe: recipe.name.clone(),
+                    justfile_dir: justfile_dir.to_path_buf(),
+                    source_order,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns the part of the line before the recipe's `:` separator, if the line is a recipe header.
+/// Assignments (`name := value`) and `:` inside quoted default values are not separators.
+fn recipe_header(line: &str) -> Option<&str> {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some(open_quote), c) if c == open_quote => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ':') if line[i + 1..].starts_with('=') => return None,
+            (None, ':') => return Some(&line[..i]),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+fn is_just_installed() -> bool {
+    let executable_name = format!("just{}", std::env::consts::EXE_SUFFIX);
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| dir.join(&executable_name).is_file())
+    })
+}
+
+/// The source of tasks, running the recipes declared in a `justfile`.
+pub struct JustfileTaskSource {
+    tasks: Vec<JustRecipeTask>,
+    recipes: Model<TrackedFile<JustfileRecipes>>,
+    _subscription: Subscription,
+}
+
+impl JustfileTaskSource {
+    /// Initializes the `justfile` source, reacting on file changes.
+    /// The recipes are run in the directory given, which is expected to contain the `justfile`.
+    pub fn new(
+        justfile_dir: PathBuf,
+        justfile_tracker: UnboundedReceiver<String>,
+        cx: &mut AppContext,
+    ) -> Model<Box<dyn TaskSource>> {
+        let recipes = TrackedFile::new_with_parser(
+            JustfileRecipes::default(),
+            justfile_tracker,
+            |contents| {
+                anyhow::ensure!(
+                    is_just_installed(),
+                    "`just` executable is not found in PATH, cannot run the justfile recipes"
+                );
+                JustfileRecipes::parse(contents)
+            },
+            cx,
+        );
+        cx.new_model(|cx| {
+            let _subscription = cx.observe(
+                &recipes,
+                move |source: &mut Box<(dyn TaskSource + 'static)>, new_recipes, cx| {
+                    if let Some(justfile_source) = source.as_any().downcast_mut::<Self>() {
+                        justfile_source.tasks = new_recipes.read(cx).get().tasks(&justfile_dir);
+                        cx.notify();
+                    }
+                },
+            );
+            Box::new(Self {
+                tasks: Vec::new(),
+                recipes,
+                _subscription,
+            })
+        })
+    }
+}
+
+impl TaskSource for JustfileTaskSource {
+    fn tasks_for_path(
+        &mut self,
+        _: Option<&Path>,
+        _: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Vec<Arc<dyn Task>> {
+        self.tasks
+            .clone()
+            .into_iter()
+            .map(|task| Arc::new(task) as Arc<dyn Task>)
+            .collect()
+    }
+
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn error(&self, cx: &AppContext) -> Option<String> {
+        self.recipes.read(cx).error().map(ToOwned::to_owned)
+    }
+
+    fn is_loading(&self, cx: &AppContext) -> bool {
+        self.recipes.read(cx).is_loading()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_justfile_recipes() {
+        let justfile = "set shell := [\"bash\", \"-c\"]\n\
+            version := \"1.0\"\n\
+            alias b := build\n\
+            export RUST_LOG := \"info\"\n\
+            \n\
+            # Builds everything\n\
+            build:\n\
+            \tcargo build\n\
+            \n\
+            @test filter='' *args: build\n\
+            \tcargo test {{filter}} {{args}}\n\
+            \n\
+            deploy env url='https://example.com': test\n\
+            \t./deploy.sh {{env}} {{url}}\n\
+            \n\
+            [private]\n\
+            helper:\n\
+            \techo hidden\n\
+            \n\
+            _internal:\n\
+            \techo hidden\n\
+            \n\
+            [no-cd]\n\
+            fmt +files:\n\
+            \trustfmt {{files}}\n";
+
+        let recipes = JustfileRecipes::parse(justfile).unwrap();
+        let tasks = recipes.tasks(Path::new("/project"));
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| task.name().to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "just build",
+                "just test",
+                "just deploy <env>",
+                "just fmt <files>",
+            ],
+            "Only public recipes should be listed, with placeholders for their required parameters"
+        );
+
+        let deploy_task = tasks
+            .iter()
+            .find(|task| task.name() == "just deploy <env>")
+            .unwrap();
+        let spawn = deploy_task
+            .exec(deploy_task.cwd().map(Path::to_path_buf))
+            .unwrap();
+        assert_eq!(spawn.command, "just");
+        assert_eq!(spawn.args, vec!["deploy".to_string()]);
+        assert_eq!(spawn.cwd, Some(PathBuf::from("/project")));
+    }
+}
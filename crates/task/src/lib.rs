@@ -1,20 +1,123 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+pub mod alias_source;
+pub mod cargo_source;
+pub mod github_workflow_source;
+pub mod justfile_source;
+pub mod makefile_source;
 pub mod oneshot_source;
+pub mod package_json_source;
 pub mod static_source;
+pub mod vscode_source;
 
 use collections::HashMap;
-use gpui::ModelContext;
+use gpui::{AppContext, ModelContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Task identifier, unique within the application.
 /// Based on it, task reruns and terminal tabs are managed.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TaskId(pub String);
 
+impl TaskId {
+    /// Ids of the tasks with the names given, defined in the tasks file given, in the order of the names.
+    /// An id is `<prefix>_<file path>_<task name>`, with `#<n>` appended for the `n`th repeated name in the file,
+    /// so re-reading the file gives its tasks the same ids, keeping their history and pins,
+    /// even if the tasks are reordered, added or removed.
+    pub fn for_tasks_file<'a>(
+        prefix: &str,
+        tasks_file: &Path,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<Self> {
+        let tasks_file = tasks_file.to_string_lossy();
+        let mut repeats = HashMap::<&str, usize>::default();
+        names
+            .into_iter()
+            .map(|name| {
+                let repeat = repeats.entry(name).or_default();
+                let id = match *repeat {
+                    0 => format!("{prefix}_{tasks_file}_{name}"),
+                    n => format!("{prefix}_{tasks_file}_{name}#{n}"),
+                };
+                *repeat += 1;
+                Self(id)
+            })
+            .collect()
+    }
+}
+
+/// How the process of a finished task has exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskExitStatus {
+    /// The process exited with the zero code.
+    Success,
+    /// The process exited with a non-zero code,
+    /// or was terminated (by a signal or after a timeout) without any, in which case the code is `None`.
+    Failure(Option<i32>),
+}
+
+impl TaskExitStatus {
+    /// Whether the task has succeeded.
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success)
+    }
+}
+
+/// A shell to run the task's command in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskShell {
+    /// Use the shell from the terminal settings.
+    #[default]
+    Inherit,
+    /// Use the program given, e.g. `bash`.
+    Program(String),
+    /// Use the program given with the arguments given, e.g. `bash` with `["--login"]`.
+    WithArguments {
+        /// Shell program to spawn.
+        program: String,
+        /// Arguments to the shell, passed before the task's command.
+        args: Vec<String>,
+    },
+}
+
+/// Whether the terminal of a spawned task is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RevealStrategy {
+    /// Show and focus the task's terminal when the task is spawned.
+    #[default]
+    Always,
+    /// Run the task in a background terminal, shown only if the task fails.
+    OnFailure,
+}
+
+/// A value the user is prompted for when the task is scheduled, substituted in the task as the `$ZED_INPUT_<id>` variable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TaskInput {
+    /// Name of the input, used in the variable it is substituted as.
+    pub id: String,
+    /// Human readable description of the input, shown in the prompt.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Value to prefill the prompt with.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+impl TaskInput {
+    /// Name of the variable, the input value is substituted as, see [`VARIABLE_INPUT_PREFIX`].
+    pub fn variable_name(&self) -> String {
+        format!("{VARIABLE_INPUT_PREFIX}{}", self.id)
+    }
+}
+
 /// Contains all information needed by Zed to spawn a new terminal tab for the given task.
 #[derive(Debug, Clone)]
 pub struct SpawnInTerminal {
@@ -36,6 +139,291 @@ pub struct SpawnInTerminal {
     pub allow_concurrent_runs: bool,
     /// Whether the command should be spawned in a separate shell instance.
     pub separate_shell: bool,
+    /// If set, the terminal of any task spawned with the same key is reused:
+    /// its process is restarted with this command, instead of a new terminal being spawned.
+    pub reuse_key: Option<String>,
+    /// If set, the spawned process is terminated when it runs longer than this.
+    pub timeout: Option<Duration>,
+    /// Shell to run the command in. Tasks with a shell other than [`TaskShell::Inherit`] are always run in a shell,
+    /// as if [`Self::separate_shell`] was set.
+    pub shell: TaskShell,
+    /// Whether to show the task's terminal when the task is spawned, or only if the task fails.
+    pub reveal: RevealStrategy,
+    /// Message to notify about the task success with, instead of the generic one.
+    pub success_message: Option<String>,
+    /// Message to notify about the task failure with, instead of the generic one.
+    pub failure_message: Option<String>,
+    /// How many times to spawn the task again, if it exits with a non-zero code.
+    /// Tasks, terminated without an exit code, e.g. killed or timed out, are not retried.
+    pub retries: u32,
+    /// Time to wait before spawning a failed task again.
+    pub retry_delay: Option<Duration>,
+    /// Whether the task starts an interactive session, e.g. a `python` or `node` REPL, rather than a command to run to its end:
+    /// its terminal is always a new one, focused for input, and is neither restarted by the reruns nor timed out.
+    pub interactive: bool,
+}
+
+impl SpawnInTerminal {
+    /// The task, spawning the command given with no arguments in the project's default directory and environment,
+    /// directly rather than in a shell, in a reused terminal, shown on spawn, with no timeout or retries.
+    /// The other fields are meant to be set with the struct update syntax, e.g. `SpawnInTerminal { args, ..SpawnInTerminal::new(id, label, command) }`.
+    pub fn new(id: TaskId, label: String, command: String) -> Self {
+        Self {
+            id,
+            label,
+            command,
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::default(),
+            use_new_terminal: false,
+            allow_concurrent_runs: false,
+            separate_shell: false,
+            reuse_key: None,
+            timeout: None,
+            shell: TaskShell::Inherit,
+            reveal: RevealStrategy::Always,
+            success_message: None,
+            failure_message: None,
+            retries: 0,
+            retry_delay: None,
+            interactive: false,
+        }
+    }
+
+    /// Whether there is anything to spawn: tasks with empty or whitespace-only commands should not be run.
+    pub fn has_command(&self) -> bool {
+        !self.command.trim().is_empty()
+    }
+
+    /// Program and its arguments to spawn for the task.
+    /// Tasks that run in a shell are spawned in the interactive shell of their [`Self::shell`],
+    /// [`TaskShell::Inherit`] resolves to the `inherited_shell` given, and nothing can be spawned without it.
+    pub fn program_and_args(
+        &self,
+        inherited_shell: impl FnOnce() -> Option<(String, Vec<String>)>,
+    ) -> Option<(String, Vec<String>)> {
+        self.program_and_args_in_shell(&["-i", "-c"], inherited_shell)
+    }
+
+    /// Same as [`Self::program_and_args`], but the shell is not interactive, for the tasks run without a terminal:
+    /// with no tty, interactive shells warn about the job control, and source the user's rc files.
+    pub fn non_interactive_program_and_args(
+        &self,
+        inherited_shell: impl FnOnce() -> Option<(String, Vec<String>)>,
+    ) -> Option<(String, Vec<String>)> {
+        self.program_and_args_in_shell(&["-c"], inherited_shell)
+    }
+
+    fn program_and_args_in_shell(
+        &self,
+        shell_flags: &[&str],
+        inherited_shell: impl FnOnce() -> Option<(String, Vec<String>)>,
+    ) -> Option<(String, Vec<String>)> {
+        let (program, mut args) = match &self.shell {
+            TaskShell::Inherit if !self.separate_shell => {
+                return Some((self.command.clone(), self.args.clone()))
+            }
+            TaskShell::Inherit => inherited_shell()?,
+            TaskShell::Program(program) => (program.clone(), Vec::new()),
+            TaskShell::WithArguments { program, args } => (program.clone(), args.clone()),
+        };
+        args.extend(shell_flags.iter().map(|flag| flag.to_string()));
+        args.push(self.command_line());
+        Some((program, args))
+    }
+
+    fn runs_in_shell(&self) -> bool {
+        self.separate_shell || self.shell != TaskShell::Inherit
+    }
+
+    /// The command with its arguments, as a line for a shell to run, e.g. to show or copy it elsewhere.
+    /// Commands of the tasks that run in a shell are kept as is, so they can use the shell syntax.
+    pub fn command_line(&self) -> String {
+        let command = if self.runs_in_shell() {
+            self.command.clone()
+        } else {
+            shell_quote(&self.command)
+        };
+        std::iter::once(command)
+            .chain(self.args.iter().map(|arg| shell_quote(arg)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Replaces the `$VARIABLE` placeholders in the command, its arguments, working directory, env values
+    /// and completion messages with the values given, see [`substitute_variables`].
+    pub fn substitute_variables(&mut self, variables: &HashMap<String, String>) {
+        self.command = substitute_variables(&self.command, variables);
+        for arg in &mut self.args {
+            *arg = substitute_variables(arg, variables);
+        }
+        if let Some(cwd) = &mut self.cwd {
+            *cwd = PathBuf::from(substitute_variables(&cwd.to_string_lossy(), variables));
+        }
+        for value in self.env.values_mut() {
+            *value = substitute_variables(value, variables);
+        }
+        for message in [&mut self.success_message, &mut self.failure_message]
+            .into_iter()
+            .flatten()
+        {
+            *message = substitute_variables(message, variables);
+        }
+    }
+
+    /// Whether the command, its arguments, working directory, env values or completion messages
+    /// reference the `$VARIABLE` given, see [`references_variable`].
+    pub fn references_variable(&self, variable: &str) -> bool {
+        std::iter::once(self.command.clone())
+            .chain(self.args.iter().cloned())
+            .chain(
+                self.cwd
+                    .iter()
+                    .map(|cwd| cwd.to_string_lossy().into_owned()),
+            )
+            .chain(self.env.values().cloned())
+            .chain(self.success_message.iter().cloned())
+            .chain(self.failure_message.iter().cloned())
+            .any(|template| references_variable(&template, variable))
+    }
+
+    /// Whether the task references any of the [`FILE_VARIABLES`], so it cannot be spawned without an active file.
+    pub fn references_file_variables(&self) -> bool {
+        FILE_VARIABLES
+            .iter()
+            .any(|variable| self.references_variable(variable))
+    }
+
+    /// Combines the tasks given into a single shell command, running them one after another,
+    /// each in its own working directory, environment and shell, and stopping at the first failed one.
+    /// The id, label and terminal options of the last task are used for the combined task.
+    pub fn chain(tasks: Vec<SpawnInTerminal>) -> Option<SpawnInTerminal> {
+        let command = tasks
+            .iter()
+            .map(|task| {
+                let mut steps = Vec::new();
+                if let Some(cwd) = &task.cwd {
+                    steps.push(format!("cd {}", shell_quote(&cwd.to_string_lossy())));
+                }
+                for (key, value) in &task.env {
+                    steps.push(format!("export {}", shell_quote(&format!("{key}={value}"))));
+                }
+                steps.push(match &task.shell {
+                    TaskShell::Inherit => task.command_line(),
+                    TaskShell::Program(program) => {
+                        format!(
+                            "{} -c {}",
+                            shell_quote(program),
+                            shell_quote(&task.command_line())
+                        )
+                    }
+                    TaskShell::WithArguments { program, args } => std::iter::once(program)
+                        .chain(args)
+                        .map(|arg| shell_quote(arg))
+                        .chain(["-c".to_owned(), shell_quote(&task.command_line())])
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                });
+                format!("({})", steps.join(" && "))
+            })
+            .collect::<Vec<_>>()
+            .join(" && ");
+        let last_task = tasks.into_iter().last()?;
+        Some(SpawnInTerminal {
+            command,
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::default(),
+            separate_shell: true,
+            shell: TaskShell::Inherit,
+            ..last_task
+        })
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    let is_safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if is_safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Absolute path of the file, currently open in the active editor.
+pub const VARIABLE_FILE: &str = "ZED_FILE";
+/// Absolute path of the directory, containing the file from [`VARIABLE_FILE`].
+pub const VARIABLE_DIR: &str = "ZED_DIR";
+/// 1-based line of the newest cursor in the active editor.
+pub const VARIABLE_LINE: &str = "ZED_LINE";
+/// 1-based column of the newest cursor in the active editor.
+pub const VARIABLE_COLUMN: &str = "ZED_COLUMN";
+/// Absolute path of the worktree root, containing the file from [`VARIABLE_FILE`].
+pub const VARIABLE_WORKTREE_ROOT: &str = "ZED_WORKTREE_ROOT";
+/// File name of the file from [`VARIABLE_FILE`], e.g. `main.rs`.
+pub const VARIABLE_FILENAME: &str = "ZED_FILENAME";
+/// File name of the file from [`VARIABLE_FILE`] without its extension, e.g. `main`.
+pub const VARIABLE_STEM: &str = "ZED_STEM";
+/// Extension of the file from [`VARIABLE_FILE`] without the leading dot, e.g. `rs`, empty for the files without one.
+pub const VARIABLE_EXT: &str = "ZED_EXT";
+/// Variables, substituted from the file open in the active editor only: the tasks referencing them cannot run without one,
+/// see [`Task::requires_file`].
+pub const FILE_VARIABLES: [&str; 5] = [
+    VARIABLE_FILE,
+    VARIABLE_DIR,
+    VARIABLE_FILENAME,
+    VARIABLE_STEM,
+    VARIABLE_EXT,
+];
+/// Name of the task being spawned, e.g. to mention it in the task's completion messages.
+pub const VARIABLE_TASK_NAME: &str = "ZED_TASK_NAME";
+/// Prefix of the variables with the values of [`TaskInput`]s: an input with the `target` id is substituted as `$ZED_INPUT_target`.
+pub const VARIABLE_INPUT_PREFIX: &str = "ZED_INPUT_";
+
+/// Replaces every `$VARIABLE` in the template with the corresponding value.
+/// Variables without a value are left untouched, `$$` is replaced with a single `$`.
+pub fn substitute_variables(template: &str, variables: &HashMap<String, String>) -> String {
+    expand_variables(template, |name| variables.get(name).cloned())
+}
+
+/// Whether the template references the `$VARIABLE` given, the escaped `$$VARIABLE` is not a reference.
+pub fn references_variable(template: &str, variable: &str) -> bool {
+    let mut referenced = false;
+    expand_variables(template, |name| {
+        referenced |= name == variable;
+        None
+    });
+    referenced
+}
+
+fn expand_variables(template: &str, mut value_of: impl FnMut(&str) -> Option<String>) -> String {
+    let mut substituted = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            substituted.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&'$').is_some() {
+            substituted.push('$');
+            continue;
+        }
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+            name.push(c);
+        }
+        match value_of(&name) {
+            Some(value) => substituted.push_str(&value),
+            None => {
+                substituted.push('$');
+                substituted.push_str(&name);
+            }
+        }
+    }
+    substituted
 }
 
 /// Represents a short lived recipe of a task, whose main purpose
@@ -47,6 +435,66 @@ pub trait Task {
     fn name(&self) -> &str;
     /// Task's current working directory. If `None`, current project's root will be used.
     fn cwd(&self) -> Option<&Path>;
+    /// Env overrides for the task, will be appended to the terminal's environment from the settings.
+    fn env(&self) -> HashMap<String, String> {
+        HashMap::default()
+    }
+    /// Names of the tasks to run before this one, in the order given.
+    /// The task is only run if all of its dependencies succeed.
+    fn dependencies(&self) -> Vec<String> {
+        Vec::new()
+    }
+    /// Explicit position of the task in the task lists, lower values go first.
+    /// Tasks without it are listed after the ones that have it.
+    fn order(&self) -> Option<i32> {
+        None
+    }
+    /// Position of the task among the tasks of its source, e.g. the declaration order of a `Makefile` target.
+    /// Tasks with it are listed in this order, after the alphanumerically sorted tasks without it.
+    fn source_order(&self) -> Option<usize> {
+        None
+    }
+    /// Whether the task is declared as the one to run by default, e.g. the build task of a project.
+    fn is_default(&self) -> bool {
+        false
+    }
+    /// Whether the task is left out of the task lists, e.g. a step of the other tasks, declared to be run as their dependency only.
+    /// Hidden tasks are still found by their names and ids, to be run programmatically.
+    fn hidden(&self) -> bool {
+        false
+    }
+    /// Whether the task is run without a terminal, to show what it prints in a notification instead, e.g. for quick status checks.
+    fn capture_output(&self) -> bool {
+        false
+    }
+    /// Whether the task is rerun on every save of the project files, once scheduled, e.g. to rerun the tests while editing.
+    fn watch(&self) -> bool {
+        false
+    }
+    /// Tags of the task, to filter the task lists by.
+    fn tags(&self) -> Vec<String> {
+        Vec::new()
+    }
+    /// Name of the icon to show next to the task in the UI, e.g. `build` or `test`.
+    /// Tasks without it, or with an unknown one, show the default icon of their source.
+    fn icon(&self) -> Option<String> {
+        None
+    }
+    /// Values to prompt the user for before the task is spawned.
+    fn inputs(&self) -> Vec<TaskInput> {
+        Vec::new()
+    }
+    /// Whether the task can only be spawned with a file open in the active editor.
+    /// By default, the tasks referencing any of the [`FILE_VARIABLES`] require one.
+    fn requires_file(&self) -> bool {
+        self.exec(None)
+            .is_some_and(|spawn_in_terminal| spawn_in_terminal.references_file_variables())
+    }
+    /// The command line the task runs, with its variables not substituted yet, to find the task by in the UI.
+    fn command(&self) -> Option<String> {
+        self.exec(None)
+            .map(|spawn_in_terminal| spawn_in_terminal.command_line())
+    }
     /// Sets up everything needed to spawn the task in the given directory (`cwd`).
     /// If a task is intended to be spawned in the terminal, it should return the corresponding struct filled with the data necessary.
     fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal>;
@@ -65,4 +513,272 @@ pub trait TaskSource: Any {
         path: Option<&Path>,
         cx: &mut ModelContext<Box<dyn TaskSource>>,
     ) -> Vec<Arc<dyn Task>>;
+    /// Same as [`Self::tasks_for_path`], but every task may come with the path of the file it is defined in,
+    /// for the sources that discover their tasks across many files, e.g. in the subdirectories of a project.
+    /// Tasks without such a path are attributed to the source itself.
+    /// Sources that fail to produce any tasks return an error here, to be skipped when listing the tasks,
+    /// and are expected to report the failure with [`Self::error`] too.
+    fn tasks_for_path_with_origins(
+        &mut self,
+        path: Option<&Path>,
+        cx: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> anyhow::Result<Vec<(Option<PathBuf>, Arc<dyn Task>)>> {
+        Ok(self
+            .tasks_for_path(path, cx)
+            .into_iter()
+            .map(|task| (None, task))
+            .collect())
+    }
+    /// A problem that prevents the source from producing its tasks, e.g. a malformed tasks file.
+    /// The source may still produce the tasks from before the problem occurred.
+    fn error(&self, _cx: &AppContext) -> Option<String> {
+        None
+    }
+    /// Whether the source is still producing its tasks in the background, e.g. reading and parsing its file.
+    /// Sources are expected to notify their observers when done.
+    fn is_loading(&self, _cx: &AppContext) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chaining_tasks() {
+        let spawn = |command: &str, args: &[&str], cwd: Option<&str>, separate_shell: bool| {
+            SpawnInTerminal {
+                cwd: cwd.map(PathBuf::from),
+                separate_shell,
+                ..spawn_in_terminal(command, args)
+            }
+        };
+        let chained = SpawnInTerminal::chain(vec![
+            spawn(
+                "codegen",
+                &["--out", "it's here"],
+                Some("/project/gen"),
+                false,
+            ),
+            spawn("echo $HOME | wc", &[], None, true),
+            spawn("cargo", &["build"], Some("/project"), false),
+        ])
+        .unwrap();
+        assert_eq!(
+            chained.command,
+            "(cd /project/gen && codegen --out 'it'\\''s here') && (echo $HOME | wc) && (cd /project && cargo build)"
+        );
+        assert!(chained.args.is_empty());
+        assert!(chained.separate_shell);
+        assert_eq!(chained.id, TaskId("cargo".to_string()));
+        assert!(SpawnInTerminal::chain(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_shell_selection() {
+        let spawn = |command: &str, args: &[&str], separate_shell: bool, shell: TaskShell| {
+            SpawnInTerminal {
+                separate_shell,
+                shell,
+                ..spawn_in_terminal(command, args)
+            }
+        };
+        let terminal_shell = || Some(("zsh".to_string(), vec!["--no-rcs".to_string()]));
+        let to_strings = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            spawn("cargo", &["build"], false, TaskShell::Inherit).program_and_args(terminal_shell),
+            Some(("cargo".to_string(), to_strings(&["build"]))),
+            "Tasks not run in a shell should spawn their command directly"
+        );
+        assert_eq!(
+            spawn("echo $HOME", &[], true, TaskShell::Inherit).program_and_args(terminal_shell),
+            Some((
+                "zsh".to_string(),
+                to_strings(&["--no-rcs", "-i", "-c", "echo $HOME"])
+            )),
+        );
+        assert_eq!(
+            spawn("echo $HOME", &[], true, TaskShell::Inherit).program_and_args(|| None),
+            None,
+            "Nothing should be spawned without a shell to inherit"
+        );
+        assert_eq!(
+            spawn(
+                "for i in {1..3}; do echo $i; done",
+                &[],
+                false,
+                TaskShell::Program("bash".to_string())
+            )
+            .program_and_args(terminal_shell),
+            Some((
+                "bash".to_string(),
+                to_strings(&["-i", "-c", "for i in {1..3}; do echo $i; done"])
+            )),
+            "Tasks with a specific shell should run in it, even if not marked to run in a separate shell"
+        );
+        assert_eq!(
+            spawn("echo $HOME", &[], true, TaskShell::Inherit)
+                .non_interactive_program_and_args(terminal_shell),
+            Some((
+                "zsh".to_string(),
+                to_strings(&["--no-rcs", "-c", "echo $HOME"])
+            )),
+            "Tasks run without a terminal should not spawn an interactive shell"
+        );
+        assert_eq!(
+            spawn("cargo", &["build"], false, TaskShell::Inherit)
+                .non_interactive_program_and_args(terminal_shell),
+            Some(("cargo".to_string(), to_strings(&["build"]))),
+        );
+        assert_eq!(
+            spawn(
+                "ls",
+                &["my dir"],
+                false,
+                TaskShell::WithArguments {
+                    program: "fish".to_string(),
+                    args: to_strings(&["--login"]),
+                }
+            )
+            .program_and_args(terminal_shell),
+            Some((
+                "fish".to_string(),
+                to_strings(&["--login", "-i", "-c", "ls 'my dir'"])
+            )),
+        );
+
+        assert_eq!(
+            spawn("ls", &["my dir"], false, TaskShell::Inherit).command_line(),
+            "ls 'my dir'"
+        );
+
+        let chained = SpawnInTerminal::chain(vec![
+            spawn(
+                "echo {a,b}",
+                &[],
+                false,
+                TaskShell::Program("bash".to_string()),
+            ),
+            spawn("cargo", &["test"], false, TaskShell::Inherit),
+        ])
+        .unwrap();
+        assert_eq!(
+            chained.command, "(bash -c 'echo {a,b}') && (cargo test)",
+            "Chained tasks should run in their own shells"
+        );
+        assert_eq!(chained.shell, TaskShell::Inherit);
+    }
+
+    #[test]
+    fn test_empty_commands() {
+        let spawn = |command: &str| spawn_in_terminal(command, &["--verbose"]);
+        assert!(spawn("cargo").has_command());
+        assert!(!spawn("").has_command());
+        assert!(
+            !spawn(" \t\n").has_command(),
+            "Whitespace-only commands should not be spawned, even with arguments"
+        );
+
+        let mut unresolved = spawn("$ZED_FILE");
+        unresolved.substitute_variables(&HashMap::from_iter([(
+            VARIABLE_FILE.to_string(),
+            String::new(),
+        )]));
+        assert!(
+            !unresolved.has_command(),
+            "Commands should be checked after the variables are substituted"
+        );
+    }
+
+    #[test]
+    fn test_substituting_completion_messages() {
+        let mut spawn = SpawnInTerminal {
+            success_message: Some("$ZED_TASK_NAME is live".to_string()),
+            ..spawn_in_terminal("./deploy.sh", &[])
+        };
+        spawn.substitute_variables(&HashMap::from_iter([(
+            VARIABLE_TASK_NAME.to_string(),
+            "deploy".to_string(),
+        )]));
+        assert_eq!(spawn.success_message.as_deref(), Some("deploy is live"));
+        assert_eq!(spawn.failure_message, None);
+    }
+
+    #[test]
+    fn test_referencing_file_variables() {
+        assert!(spawn_in_terminal("cargo", &["test", "--test", "$ZED_STEM"])
+            .references_file_variables());
+        assert!(spawn_in_terminal("rustc $ZED_FILENAME", &[]).references_file_variables());
+        assert!(!spawn_in_terminal("cargo", &["test", "$$ZED_STEM"]).references_file_variables());
+        assert!(!spawn_in_terminal("cargo", &["test", "$ZED_STEMS"]).references_file_variables());
+        assert!(
+            spawn_in_terminal("cargo", &["test", "$ZED_STEMS", "$ZED_FILE"])
+                .references_file_variables()
+        );
+        assert!(spawn_in_terminal("ls", &["$ZED_DIR"]).references_file_variables());
+        assert!(
+            !spawn_in_terminal("ls", &["$ZED_WORKTREE_ROOT", "$$ZED_FILE"])
+                .references_file_variables()
+        );
+
+        let mut unresolved = spawn_in_terminal("test-$ZED_EXT", &["$ZED_STEM.$ZED_EXT"]);
+        unresolved.substitute_variables(&HashMap::from_iter([
+            (VARIABLE_FILENAME.to_string(), "lib.rs".to_string()),
+            (VARIABLE_STEM.to_string(), "lib".to_string()),
+            (VARIABLE_EXT.to_string(), "rs".to_string()),
+        ]));
+        assert_eq!(unresolved.command_line(), "test-rs lib.rs");
+    }
+
+    #[test]
+    fn test_substituting_inputs() {
+        let input = TaskInput {
+            id: "target".to_string(),
+            description: None,
+            default: None,
+        };
+        assert_eq!(input.variable_name(), "ZED_INPUT_target");
+        let variables = HashMap::from_iter([(input.variable_name(), "x86_64".to_string())]);
+        assert_eq!(
+            substitute_variables("cargo build --target $ZED_INPUT_target", &variables),
+            "cargo build --target x86_64"
+        );
+    }
+
+    #[test]
+    fn test_substituting_variables() {
+        let variables = HashMap::from_iter([
+            (
+                VARIABLE_FILE.to_string(),
+                "/project/src/main.rs".to_string(),
+            ),
+            (VARIABLE_LINE.to_string(), "42".to_string()),
+            (VARIABLE_COLUMN.to_string(), "7".to_string()),
+        ]);
+        assert_eq!(
+            substitute_variables(
+                "run $ZED_FILE:$ZED_LINE:$ZED_COLUMN --root $ZED_WORKTREE_ROOT --price $$5 $",
+                &variables
+            ),
+            "run /project/src/main.rs:42:7 --root $ZED_WORKTREE_ROOT --price $5 $",
+            "Known variables should be substituted, unknown ones left untouched and `$$` escaped"
+        );
+        assert_eq!(
+            substitute_variables("$ZED_LINE_SUFFIX $ZED_LINE$ZED_COLUMN", &variables),
+            "$ZED_LINE_SUFFIX 427",
+        );
+    }
+
+    fn spawn_in_terminal(command: &str, args: &[&str]) -> SpawnInTerminal {
+        SpawnInTerminal {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            ..SpawnInTerminal::new(
+                TaskId(command.to_string()),
+                command.to_string(),
+                command.to_string(),
+            )
+        }
+    }
 }
@@ -0,0 +1,233 @@
+//! A source of tasks, based on the targets of a `Makefile`.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::channel::mpsc::UnboundedReceiver;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription};
+
+use crate::{static_source::TrackedFile, SpawnInTerminal, Task, TaskId, TaskSource};
+
+/// A single `make <target>` invocation.
+#[derive(Clone, Debug, PartialEq)]
+struct MakeTargetTask {
+    id: TaskId,
+    name: String,
+    target: String,
+    makefile_dir: PathBuf,
+    source_order: usize,
+}
+
+impl Task for MakeTargetTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        Some(&self.makefile_dir)
+    }
+
+    fn source_order(&self) -> Option<usize> {
+        Some(self.source_order)
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
+        Some(SpawnInTerminal {
+            args: vec![self.target.clone()],
+            cwd,
+            env: self.env(),
+            ..SpawnInTerminal::new(self.id.clone(), self.name.clone(), "make".to_owned())
+        })
+    }
+}
+
+/// Whether the name can be run as a target, matching `[a-zA-Z0-9_-]+`.
+fn is_target_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Target names, declared in the `Makefile`, in the order of their first appearance.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct MakefileTargets(Vec<String>);
+
+impl MakefileTargets {
+    fn parse(contents: &str) -> anyhow::Result<Self> {
+        let mut targets = Vec::new();
+        for line in contents.lines() {
+            // Recipe lines start with a tab, and neither they nor other indented lines declare targets.
+            if line.starts_with(|c: char| c.is_whitespace()) {
+                continue;
+            }
+            let Some((declared, rest)) = line.split_once(':') else {
+                continue;
+            };
+            // `VARIABLE := value`, `VARIABLE ::= value`, and the assignments with colons in their values,
+            // e.g. `URL = http://host` or `CFLAGS += -I a:b`, are not rules.
+            if declared.contains('=') || rest.starts_with('=') || rest.starts_with(":=") {
+                continue;
+            }
+            let names = if declared.trim_end() == ".PHONY" {
+                // Phony targets are declared as the prerequisites of `.PHONY`, among the variables, skipped here.
+                rest.split('#')
+                    .next()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .filter(|name| is_target_name(name))
+                    .collect::<Vec<_>>()
+            } else if declared.split_whitespace().all(is_target_name) {
+                declared.split_whitespace().collect()
+            } else {
+                // Rules with patterns or variables in their targets, e.g. `%.o: %.c`, declare no targets to run by name.
+                continue;
+            };
+            for name in names {
+                if !targets.iter().any(|target| target == name) {
+                    targets.push(name.to_owned());
+                }
+            }
+        }
+        Ok(Self(targets))
+    }
+
+    fn tasks(&self, makefile_dir: &Path) -> Vec<MakeTargetTask> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(source_order, target)| MakeTargetTask {
+                id: TaskId(format!("make_{}_{target}", makefile_dir.to_string_lossy())),
+                name: format!("make {target}"),
+                target: target.clone(),
+                makefile_dir: makefile_dir.to_path_buf(),
+                source_order,
+            })
+            .collect()
+    }
+}
+
+/// The source of tasks, running the targets declared in a `Makefile`.
+pub struct MakefileTaskSource {
+    tasks: Vec<MakeTargetTask>,
+    targets: Model<TrackedFile<MakefileTargets>>,
+    _subscription: Subscription,
+}
+
+impl MakefileTaskSource {
+    /// Initializes the `Makefile` source, reacting on file changes.
+    /// The targets are run in the directory given, which is expected to contain the `Makefile`.
+    pub fn new(
+        makefile_dir: PathBuf,
+        makefile_tracker: UnboundedReceiver<String>,
+        cx: &mut AppContext,
+    ) -> Model<Box<dyn TaskSource>> {
+        let targets = TrackedFile::new_with_parser(
+            MakefileTargets::default(),
+            makefile_tracker,
+            MakefileTargets::parse,
+            cx,
+        );
+        cx.new_model(|cx| {
+            let _subscription = cx.observe(
+                &targets,
+                move |source: &mut Box<(dyn TaskSource + 'static)>, new_targets, cx| {
+                    if let Some(makefile_source) = source.as_any().downcast_mut::<Self>() {
+                        makefile_source.tasks = new_targets.read(cx).get().tasks(&makefile_dir);
+                        cx.notify();
+                    }
+                },
+            );
+            Box::new(Self {
+                tasks: Vec::new(),
+                targets,
+                _subscription,
+            })
+        })
+    }
+}
+
+impl TaskSource for MakefileTaskSource {
+    fn tasks_for_path(
+        &mut self,
+        _: Option<&Path>,
+        _: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Vec<Arc<dyn Task>> {
+        self.tasks
+            .clone()
+            .into_iter()
+            .map(|task| Arc::new(task) as Arc<dyn Task>)
+            .collect()
+    }
+
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn error(&self, cx: &AppContext) -> Option<String> {
+        self.targets.read(cx).error().map(ToOwned::to_owned)
+    }
+
+    fn is_loading(&self, cx: &AppContext) -> bool {
+        self.targets.read(cx).is_loading()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_makefile_targets() {
+        let makefile = "CC := gcc\n\
+            PREFIX ::= /usr/local\n\
+            URL = http://host\n\
+            CFLAGS += -I a:b\n\
+            .PHONY: all clean install # not files\n\
+            \n\
+            all: build test\n\
+            build: main.o\n\
+            \t$(CC) -o app main.o\n\
+            \techo done: built\n\
+            %.o: %.c\n\
+            \t$(CC) -c $<\n\
+            test : build\n\
+            \t./app --test\n\
+            \x20\x20indented: not-a-target\n\
+            lint-all docs_html: ; @true\n\
+            clean:\n\
+            \trm -f app *.o\n";
+
+        let targets = MakefileTargets::parse(makefile).unwrap();
+        assert_eq!(
+            targets,
+            MakefileTargets(vec![
+                "all".to_string(),
+                "clean".to_string(),
+                "install".to_string(),
+                "build".to_string(),
+                "test".to_string(),
+                "lint-all".to_string(),
+                "docs_html".to_string(),
+            ])
+        );
+
+        let tasks = targets.tasks(Path::new("/project"));
+        let build_task = tasks
+            .iter()
+            .find(|task| task.name() == "make build")
+            .unwrap();
+        let spawn = build_task
+            .exec(build_task.cwd().map(Path::to_path_buf))
+            .unwrap();
+        assert_eq!(spawn.command, "make");
+        assert_eq!(spawn.args, vec!["build".to_string()]);
+        assert_eq!(spawn.cwd, Some(PathBuf::from("/project")));
+    }
+}
@@ -3,21 +3,35 @@
 use std::sync::Arc;
 
 use crate::{SpawnInTerminal, Task, TaskId, TaskSource};
-use gpui::{AppContext, Context, Model};
+use collections::{HashMap, HashSet};
+use gpui::{AppContext, Context, Model, ModelContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_MAX_TASKS: usize = 50;
 
 /// A storage and source of tasks generated out of user command prompt inputs.
+/// Every distinct prompt is stored once, the oldest ones are evicted when over the limit.
+/// Prompts are compared and stored normalized: trimmed, with the whitespace between the words collapsed.
+/// Multi-line prompts are scripts: their lines are run by the shell one after another.
 pub struct OneshotSource {
-    tasks: Vec<Arc<dyn Task>>,
+    tasks: Vec<Arc<OneshotTask>>,
+    max_tasks: usize,
 }
 
 #[derive(Clone)]
 struct OneshotTask {
     id: TaskId,
+    /// The prompt on a single line, to display in the task lists.
+    name: String,
 }
 
 impl OneshotTask {
     fn new(prompt: String) -> Self {
-        Self { id: TaskId(prompt) }
+        Self {
+            name: prompt.lines().collect::<Vec<_>>().join(" ↵ "),
+            id: TaskId(prompt),
+        }
     }
 }
 
@@ -27,7 +41,7 @@ impl Task for OneshotTask {
     }
 
     fn name(&self) -> &str {
-        &self.id.0
+        &self.name
     }
 
     fn cwd(&self) -> Option<&std::path::Path> {
@@ -39,33 +53,258 @@ impl Task for OneshotTask {
             return None;
         }
         Some(SpawnInTerminal {
-            id: self.id().clone(),
-            label: self.name().to_owned(),
-            command: self.id().0.clone(),
-            args: vec![],
             cwd,
-            env: Default::default(),
-            use_new_terminal: Default::default(),
-            allow_concurrent_runs: Default::default(),
+            env: self.env(),
+            // The shell runs the script's lines one after another, same as if they were typed in it.
             separate_shell: true,
+            ..SpawnInTerminal::new(
+                self.id().clone(),
+                self.name().to_owned(),
+                self.id().0.clone(),
+            )
         })
     }
 }
 
+/// A command line, split into the program and its arguments, see [`OneshotSource::parse_command`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParsedCommand {
+    /// The program to run, empty if the command line has no words.
+    pub program: String,
+    /// Arguments of the program, unquoted.
+    pub args: Vec<String>,
+}
+
+impl ParsedCommand {
+    /// Whether there is no program to run, e.g. for the blank command lines.
+    pub fn is_empty(&self) -> bool {
+        self.program.is_empty()
+    }
+}
+
 impl OneshotSource {
+    /// Splits the command line into the program and its arguments, the way the shell splits the prompts of the oneshot tasks:
+    /// words are separated by whitespace, unless it is quoted or escaped with `\`; single quotes keep everything literally,
+    /// double quotes allow escaping `"`, `\`, `$` and `` ` `` only. Quotes left open extend to the end of the command line.
+    /// Nothing is expanded: the `$VAR` references and the shell operators, e.g. `|` or `&&`, are kept as the words.
+    pub fn parse_command(command: &str) -> ParsedCommand {
+        let mut words = Vec::new();
+        let mut word: Option<String> = None;
+        let mut chars = command.chars();
+        let mut quote = None;
+        while let Some(c) = chars.next() {
+            let unquoted = match (quote, c) {
+                (Some(open_quote), c) if c == open_quote => {
+                    quote = None;
+                    continue;
+                }
+                (Some('"'), '\\') => match chars.next() {
+                    Some(escaped @ ('"' | '\\' | '$' | '`')) => escaped.to_string(),
+                    Some(other) => format!("\\{other}"),
+                    None => '\\'.to_string(),
+                },
+                (Some(_), c) => c.to_string(),
+                (None, '\'' | '"') => {
+                    quote = Some(c);
+                    // Quotes make a word, even an empty one, e.g. `''`.
+                    word.get_or_insert_with(String::new);
+                    continue;
+                }
+                (None, '\\') => match chars.next() {
+                    // Escaped line breaks continue the command on the next line.
+                    Some('\n') => continue,
+                    Some(escaped) => escaped.to_string(),
+                    None => '\\'.to_string(),
+                },
+                (None, c) if c.is_whitespace() => {
+                    words.extend(word.take());
+                    continue;
+                }
+                (None, c) => c.to_string(),
+            };
+            word.get_or_insert_with(String::new).push_str(&unquoted);
+        }
+        words.extend(word);
+
+        let mut words = words.into_iter();
+        ParsedCommand {
+            program: words.next().unwrap_or_default(),
+            args: words.collect(),
+        }
+    }
+
     /// Initializes the oneshot source, preparing to store user prompts.
     pub fn new(cx: &mut AppContext) -> Model<Box<dyn TaskSource>> {
-        cx.new_model(|_| Box::new(Self { tasks: Vec::new() }) as Box<dyn TaskSource>)
+        cx.new_model(|_| {
+            Box::new(Self {
+                tasks: Vec::new(),
+                max_tasks: DEFAULT_MAX_TASKS,
+            }) as Box<dyn TaskSource>
+        })
     }
 
     /// Spawns a certain task based on the user prompt.
-    pub fn spawn(&mut self, prompt: String) -> Arc<dyn Task> {
-        let ret = Arc::new(OneshotTask::new(prompt));
-        self.tasks.push(ret.clone());
-        ret
+    /// A task, spawned from the same normalized prompt before, is reused and becomes the most recent one.
+    pub fn spawn(
+        &mut self,
+        prompt: String,
+        cx: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Arc<dyn Task> {
+        let prompt = normalize_prompt(&prompt);
+        let task = match self.tasks.iter().position(|task| task.id.0 == prompt) {
+            Some(existing_ix) => self.tasks.remove(existing_ix),
+            None => Arc::new(OneshotTask::new(prompt)),
+        };
+        self.tasks.push(task.clone());
+        self.truncate();
+        cx.notify();
+        task
+    }
+
+    /// Prompts of all stored tasks, from the oldest to the most recent one.
+    pub fn prompts(&self) -> impl Iterator<Item = &str> {
+        self.tasks.iter().map(|task| task.id.0.as_str())
+    }
+
+    /// Stores the prompts given (e.g. from a previous session) as older than the current ones.
+    pub fn restore(&mut self, prompts: Vec<String>, cx: &mut ModelContext<Box<dyn TaskSource>>) {
+        let current_tasks = std::mem::take(&mut self.tasks);
+        let mut known_prompts = current_tasks
+            .iter()
+            .map(|task| task.id.0.clone())
+            .collect::<HashSet<_>>();
+        self.tasks = prompts
+            .into_iter()
+            .map(|prompt| normalize_prompt(&prompt))
+            .filter(|prompt| !prompt.is_empty() && known_prompts.insert(prompt.clone()))
+            .map(|prompt| Arc::new(OneshotTask::new(prompt)))
+            .chain(current_tasks)
+            .collect();
+        self.truncate();
+        cx.notify();
+    }
+
+    /// Removes all stored tasks.
+    pub fn clear(&mut self, cx: &mut ModelContext<Box<dyn TaskSource>>) {
+        self.tasks.clear();
+        cx.notify();
+    }
+
+    /// Sets the maximum amount of tasks stored, evicting the oldest ones if needed.
+    pub fn set_max_tasks(&mut self, max_tasks: usize, cx: &mut ModelContext<Box<dyn TaskSource>>) {
+        self.max_tasks = max_tasks;
+        self.truncate();
+        cx.notify();
+    }
+
+    fn truncate(&mut self) {
+        let excess = self.tasks.len().saturating_sub(self.max_tasks);
+        self.tasks.drain(..excess);
+    }
+}
+
+/// How the `$VAR` and `${VAR}` references of the oneshot commands are expanded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvExpansion {
+    /// Leave the references for the shell to expand, same as for the commands typed in the terminal.
+    #[default]
+    Shell,
+    /// Expand the references against the environment given before spawning the command.
+    /// Same as the shell, references inside of single quotes or escaped with `\` are kept,
+    /// and so are the references to the variables missing from the environment.
+    Environment,
+}
+
+impl EnvExpansion {
+    /// Expands the environment variable references of the command, according to the expansion mode.
+    pub fn expand(&self, command: &str, env: &HashMap<String, String>) -> String {
+        match self {
+            Self::Shell => command.to_string(),
+            Self::Environment => expand_env_references(command, env),
+        }
     }
 }
 
+fn expand_env_references(command: &str, env: &HashMap<String, String>) -> String {
+    let mut expanded = String::with_capacity(command.len());
+    let mut chars = command.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\'', None) | ('"', None) => quote = Some(c),
+            ('\'', Some('\'')) | ('"', Some('"')) => quote = None,
+            ('\\', None) | ('\\', Some('"')) => {
+                expanded.push(c);
+                if let Some(escaped) = chars.next() {
+                    expanded.push(escaped);
+                }
+                continue;
+            }
+            ('$', None) | ('$', Some('"')) => {
+                let braced = chars.next_if_eq(&'{').is_some();
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                let closed = !braced || chars.next_if_eq(&'}').is_some();
+                match env.get(&name) {
+                    Some(value) if closed => expanded.push_str(value),
+                    _ => {
+                        expanded.push('$');
+                        if braced {
+                            expanded.push('{');
+                        }
+                        expanded.push_str(&name);
+                        if braced && closed {
+                            expanded.push('}');
+                        }
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+        expanded.push(c);
+    }
+    expanded
+}
+
+/// Trims the prompt and collapses the whitespace between its words into single spaces,
+/// so the same commands, typed slightly differently, end up as the same task.
+/// Line breaks separate the commands of multi-line prompts, so they are kept, dropping the blank lines.
+/// Whitespace inside of quotes is kept intact, as it is a part of the command's arguments.
+fn normalize_prompt(prompt: &str) -> String {
+    let mut normalized = String::with_capacity(prompt.len());
+    let mut quote = None;
+    let mut pending_space = false;
+    let mut pending_line_break = false;
+    for c in prompt.trim().chars() {
+        match quote {
+            Some(open_quote) if c == open_quote => quote = None,
+            Some(_) => {}
+            None if c == '\n' => {
+                pending_line_break = true;
+                continue;
+            }
+            None if c.is_whitespace() => {
+                pending_space = true;
+                continue;
+            }
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None => {}
+        }
+        let pending_space = std::mem::take(&mut pending_space);
+        if std::mem::take(&mut pending_line_break) {
+            normalized.push('\n');
+        } else if pending_space {
+            normalized.push(' ');
+        }
+        normalized.push(c);
+    }
+    normalized
+}
+
 impl TaskSource for OneshotSource {
     fn as_any(&mut self) -> &mut dyn std::any::Any {
         self
@@ -76,6 +315,146 @@ impl TaskSource for OneshotSource {
         _path: Option<&std::path::Path>,
         _cx: &mut gpui::ModelContext<Box<dyn TaskSource>>,
     ) -> Vec<Arc<dyn Task>> {
-        self.tasks.clone()
+        self.tasks
+            .iter()
+            .map(|task| task.clone() as Arc<dyn Task>)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+
+    use super::*;
+
+    fn update_oneshot_source<R>(
+        source: &Model<Box<dyn TaskSource>>,
+        cx: &mut TestAppContext,
+        f: impl FnOnce(&mut OneshotSource, &mut ModelContext<Box<dyn TaskSource>>) -> R,
+    ) -> R {
+        source.update(cx, |source, cx| {
+            f(source.as_any().downcast_mut::<OneshotSource>().unwrap(), cx)
+        })
+    }
+
+    fn prompts(source: &Model<Box<dyn TaskSource>>, cx: &mut TestAppContext) -> Vec<String> {
+        update_oneshot_source(source, cx, |source, _| {
+            source.prompts().map(ToOwned::to_owned).collect()
+        })
+    }
+
+    #[gpui::test]
+    fn test_oneshot_tasks_history(cx: &mut TestAppContext) {
+        let source = cx.update(OneshotSource::new);
+        update_oneshot_source(&source, cx, |source, cx| {
+            source.set_max_tasks(3, cx);
+            source.spawn("cargo test".to_string(), cx);
+            source.spawn("cargo check".to_string(), cx);
+            source.spawn("  cargo   test ".to_string(), cx);
+        });
+        assert_eq!(
+            prompts(&source, cx),
+            vec!["cargo check".to_string(), "cargo test".to_string()],
+            "Same normalized prompts should reuse the existing task, making it the most recent one"
+        );
+        assert_eq!(
+            normalize_prompt("\techo  'a  b'   \"c\td\" e "),
+            "echo 'a  b' \"c\td\" e",
+            "Whitespace inside of quotes should be kept"
+        );
+        assert_eq!(
+            normalize_prompt("cd  src \r\n\n  \tls -la\necho 'a\n  b'\n"),
+            "cd src\nls -la\necho 'a\n  b'",
+            "Lines of multi-line prompts should be normalized separately, dropping the blank ones"
+        );
+        let script = OneshotTask::new(normalize_prompt("cd src\n  ls"));
+        assert_eq!(script.name(), "cd src ↵ ls");
+        assert_eq!(script.exec(None).unwrap().command, "cd src\nls");
+
+        update_oneshot_source(&source, cx, |source, cx| {
+            source.restore(
+                vec![
+                    "ls".to_string(),
+                    "cargo  test".to_string(),
+                    "ls".to_string(),
+                    "pwd".to_string(),
+                ],
+                cx,
+            )
+        });
+        assert_eq!(
+            prompts(&source, cx),
+            vec![
+                "pwd".to_string(),
+                "cargo check".to_string(),
+                "cargo test".to_string()
+            ],
+            "Restored prompts should be deduplicated, older than the current ones and evicted first"
+        );
+
+        update_oneshot_source(&source, cx, |source, cx| source.clear(cx));
+        assert!(prompts(&source, cx).is_empty());
+    }
+
+    #[test]
+    fn test_parsing_commands() {
+        let parsed = |program: &str, args: &[&str]| ParsedCommand {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        };
+
+        assert_eq!(
+            OneshotSource::parse_command("  cargo   test\t-p  zed "),
+            parsed("cargo", &["test", "-p", "zed"])
+        );
+        assert_eq!(
+            OneshotSource::parse_command(
+                "echo 'a  b' \"c \\\"d\\\" \\n $HOME\" x'y'\"z\" '' \"it's\""
+            ),
+            parsed("echo", &["a  b", "c \"d\" \\n $HOME", "xyz", "", "it's"]),
+            "Quoted whitespace should be kept, quoted parts should make single words"
+        );
+        assert_eq!(
+            OneshotSource::parse_command("ls my\\ dir \\'quoted\\' 'single \\\" kept' \\\n-la"),
+            parsed("ls", &["my dir", "'quoted'", "single \\\" kept", "-la"]),
+            "Escapes should work outside of the quotes, but not inside the single ones"
+        );
+        assert_eq!(
+            OneshotSource::parse_command("cargo test | head && echo \"unterminated  quote"),
+            parsed(
+                "cargo",
+                &["test", "|", "head", "&&", "echo", "unterminated  quote"]
+            ),
+            "Shell operators should be kept as words, open quotes should extend to the end"
+        );
+
+        for blank in ["", "   ", "\n\t"] {
+            let parsed_blank = OneshotSource::parse_command(blank);
+            assert!(parsed_blank.is_empty(), "{blank:?} should have no program");
+            assert!(parsed_blank.args.is_empty());
+        }
+        assert!(OneshotSource::parse_command("''").is_empty());
+    }
+
+    #[test]
+    fn test_expanding_env_variables() {
+        std::env::set_var("ZED_ONESHOT_TEST_TARGET", "release");
+        let mut env = std::env::vars().collect::<HashMap<_, _>>();
+        env.insert("PROJECT_NAME".to_string(), "zed".to_string());
+        let command = "cargo build --$ZED_ONESHOT_TEST_TARGET -p ${PROJECT_NAME} && echo \
+            \"${PROJECT_NAME}_$ZED_ONESHOT_TEST_TARGET\" '$PROJECT_NAME' \\$PROJECT_NAME $MISSING ${MISSING} $";
+
+        assert_eq!(
+            EnvExpansion::Shell.expand(command, &env),
+            command,
+            "References should be left for the shell by default"
+        );
+        assert_eq!(
+            EnvExpansion::Environment.expand(command, &env),
+            "cargo build --release -p zed && echo \
+            \"zed_release\" '$PROJECT_NAME' \\$PROJECT_NAME $MISSING ${MISSING} $",
+            "References should be expanded as the shell does, keeping the quoted, escaped and unknown ones"
+        );
     }
 }
@@ -0,0 +1,273 @@
+//! A source of tasks, based on the `scripts` section of a `package.json` file.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::channel::mpsc::UnboundedReceiver;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription};
+use serde::{de, Deserialize, Deserializer};
+
+use crate::{static_source::TrackedFile, SpawnInTerminal, Task, TaskId, TaskSource};
+
+/// A Node.js package manager to run the package scripts with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PackageManager {
+    /// `npm`, used when no other package manager is detected.
+    #[default]
+    Npm,
+    /// `yarn`
+    Yarn,
+    /// `pnpm`
+    Pnpm,
+}
+
+impl PackageManager {
+    /// All package managers, in the order their lockfiles are checked during detection.
+    pub const ALL: [Self; 3] = [Self::Yarn, Self::Pnpm, Self::Npm];
+
+    /// Name of the executable to run the scripts with.
+    pub fn command(&self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Yarn => "yarn",
+            Self::Pnpm => "pnpm",
+        }
+    }
+
+    /// Name of the lockfile the package manager creates next to the `package.json`.
+    pub fn lockfile_name(&self) -> &'static str {
+        match self {
+            Self::Npm => "package-lock.json",
+            Self::Yarn => "yarn.lock",
+            Self::Pnpm => "pnpm-lock.yaml",
+        }
+    }
+}
+
+/// A single script from the `package.json` file.
+#[derive(Clone, Debug, PartialEq)]
+struct PackageScriptTask {
+    id: TaskId,
+    name: String,
+    script: String,
+    package_manager: PackageManager,
+    package_dir: PathBuf,
+    source_order: usize,
+}
+
+impl Task for PackageScriptTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        Some(&self.package_dir)
+    }
+
+    fn source_order(&self) -> Option<usize> {
+        Some(self.source_order)
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
+        Some(SpawnInTerminal {
+            args: vec!["run".to_owned(), self.script.clone()],
+            cwd,
+            env: self.env(),
+            ..SpawnInTerminal::new(
+                self.id.clone(),
+                self.name.clone(),
+                self.package_manager.command().to_owned(),
+            )
+        })
+    }
+}
+
+/// Contents of the `package.json` file, relevant to the tasks.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+struct PackageJson {
+    #[serde(default)]
+    scripts: PackageScripts,
+}
+
+/// Names of the scripts, in the order they are declared in the `package.json`.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct PackageScripts(Vec<String>);
+
+impl<'de> Deserialize<'de> for PackageScripts {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ScriptsVisitor;
+
+        impl<'de> de::Visitor<'de> for ScriptsVisitor {
+            type Value = PackageScripts;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of script names to commands")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut scripts = Vec::new();
+                while let Some((script, _command)) = map.next_entry::<String, String>()? {
+                    if !scripts.contains(&script) {
+                        scripts.push(script);
+                    }
+                }
+                Ok(PackageScripts(scripts))
+            }
+        }
+
+        deserializer.deserialize_map(ScriptsVisitor)
+    }
+}
+
+impl PackageJson {
+    fn tasks(&self, package_manager: PackageManager, package_dir: &Path) -> Vec<PackageScriptTask> {
+        self.scripts
+            .0
+            .iter()
+            .enumerate()
+            .map(|(source_order, script)| PackageScriptTask {
+                id: TaskId(format!(
+                    "package_json_{}_{script}",
+                    package_dir.to_string_lossy()
+                )),
+                name: format!("{}: {script}", package_manager.command()),
+                script: script.clone(),
+                package_manager,
+                package_dir: package_dir.to_path_buf(),
+                source_order,
+            })
+            .collect()
+    }
+}
+
+/// The source of tasks defined as scripts in a `package.json` file.
+pub struct PackageJsonTaskSource {
+    tasks: Vec<PackageScriptTask>,
+    package_json: Model<TrackedFile<PackageJson>>,
+    _subscription: Subscription,
+}
+
+impl PackageJsonTaskSource {
+    /// Initializes the `package.json` source, reacting on file changes.
+    /// The scripts are run with the package manager given, in the directory given.
+    pub fn new(
+        package_manager: PackageManager,
+        package_dir: PathBuf,
+        package_json_tracker: UnboundedReceiver<String>,
+        cx: &mut AppContext,
+    ) -> Model<Box<dyn TaskSource>> {
+        let package_json = TrackedFile::new(PackageJson::default(), package_json_tracker, cx);
+        cx.new_model(|cx| {
+            let _subscription = cx.observe(
+                &package_json,
+                move |source: &mut Box<(dyn TaskSource + 'static)>, new_package_json, cx| {
+                    if let Some(package_json_source) = source.as_any().downcast_mut::<Self>() {
+                        package_json_source.tasks = new_package_json
+                            .read(cx)
+                            .get()
+                            .tasks(package_manager, &package_dir);
+                        cx.notify();
+                    }
+                },
+            );
+            Box::new(Self {
+                tasks: Vec::new(),
+                package_json,
+                _subscription,
+            })
+        })
+    }
+}
+
+impl TaskSource for PackageJsonTaskSource {
+    fn tasks_for_path(
+        &mut self,
+        _: Option<&Path>,
+        _: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Vec<Arc<dyn Task>> {
+        self.tasks
+            .clone()
+            .into_iter()
+            .map(|task| Arc::new(task) as Arc<dyn Task>)
+            .collect()
+    }
+
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn error(&self, cx: &AppContext) -> Option<String> {
+        self.package_json.read(cx).error().map(ToOwned::to_owned)
+    }
+
+    fn is_loading(&self, cx: &AppContext) -> bool {
+        self.package_json.read(cx).is_loading()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_package_scripts() {
+        let package_json = serde_json_lenient::from_str::<PackageJson>(
+            r#"{
+                "name": "web",
+                "version": "1.0.0",
+                "scripts": {
+                    "test": "jest",
+                    "build": "tsc -p ."
+                },
+                "devDependencies": { "jest": "^29.0.0" }
+            }"#,
+        )
+        .unwrap();
+        let package_dir = Path::new("/project/web");
+        let spawned = package_json
+            .tasks(PackageManager::Yarn, package_dir)
+            .into_iter()
+            .map(|task| {
+                let spawn = task.exec(task.cwd().map(Path::to_path_buf)).unwrap();
+                (
+                    task.name().to_string(),
+                    spawn.command,
+                    spawn.args,
+                    spawn.cwd,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spawned,
+            vec![
+                (
+                    "yarn: test".to_string(),
+                    "yarn".to_string(),
+                    vec!["run".to_string(), "test".to_string()],
+                    Some(package_dir.to_path_buf()),
+                ),
+                (
+                    "yarn: build".to_string(),
+                    "yarn".to_string(),
+                    vec!["run".to_string(), "build".to_string()],
+                    Some(package_dir.to_path_buf()),
+                ),
+            ]
+        );
+
+        let package_json =
+            serde_json_lenient::from_str::<PackageJson>(r#"{ "name": "no-scripts" }"#).unwrap();
+        assert!(
+            package_json
+                .tasks(PackageManager::Npm, package_dir)
+                .is_empty(),
+            "package.json without scripts should produce no tasks"
+        );
+    }
+}
@@ -3,6 +3,7 @@
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use collections::HashMap;
@@ -10,9 +11,8 @@ use futures::StreamExt;
 use gpui::{AppContext, Context, Model, ModelContext, Subscription};
 use schemars::{gen::SchemaSettings, JsonSchema};
 use serde::{Deserialize, Serialize};
-use util::ResultExt;
 
-use crate::{SpawnInTerminal, Task, TaskId, TaskSource};
+use crate::{RevealStrategy, SpawnInTerminal, Task, TaskId, TaskInput, TaskShell, TaskSource};
 use futures::channel::mpsc::UnboundedReceiver;
 
 /// A single config file entry with the deserialized task definition.
@@ -23,26 +23,43 @@ struct StaticTask {
 }
 
 impl StaticTask {
-    pub(super) fn new(id: usize, task_definition: Definition) -> Self {
-        Self {
-            id: TaskId(format!("static_{}_{}", task_definition.label, id)),
-            definition: task_definition,
-        }
+    fn for_definitions(tasks_file: &Path, definitions: Vec<Definition>) -> Vec<Self> {
+        let ids = TaskId::for_tasks_file(
+            "static",
+            tasks_file,
+            definitions
+                .iter()
+                .map(|definition| definition.label.as_str()),
+        );
+        ids.into_iter()
+            .zip(definitions)
+            .map(|(id, definition)| Self { id, definition })
+            .collect()
     }
 }
 
 impl Task for StaticTask {
     fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
         Some(SpawnInTerminal {
-            id: self.id.clone(),
             cwd,
             use_new_terminal: self.definition.use_new_terminal,
             allow_concurrent_runs: self.definition.allow_concurrent_runs,
-            label: self.definition.label.clone(),
-            command: self.definition.command.clone(),
             args: self.definition.args.clone(),
-            env: self.definition.env.clone(),
-            separate_shell: false,
+            env: self.env(),
+            reuse_key: self.definition.reuse_key.clone(),
+            timeout: self.definition.timeout.map(Duration::from_secs),
+            shell: self.definition.shell.clone(),
+            reveal: self.definition.reveal,
+            success_message: self.definition.success_message.clone(),
+            failure_message: self.definition.failure_message.clone(),
+            retries: self.definition.retries,
+            retry_delay: self.definition.retry_delay.map(Duration::from_secs),
+            interactive: self.definition.interactive,
+            ..SpawnInTerminal::new(
+                self.id.clone(),
+                self.definition.label.clone(),
+                self.definition.command.clone(),
+            )
         })
     }
 
@@ -57,12 +74,59 @@ impl Task for StaticTask {
     fn cwd(&self) -> Option<&Path> {
         self.definition.cwd.as_deref()
     }
+
+    fn env(&self) -> HashMap<String, String> {
+        self.definition.env.clone()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        self.definition.depends_on.clone()
+    }
+
+    fn order(&self) -> Option<i32> {
+        self.definition.order
+    }
+
+    fn is_default(&self) -> bool {
+        self.definition.default
+    }
+
+    fn hidden(&self) -> bool {
+        self.definition.hidden
+    }
+
+    fn capture_output(&self) -> bool {
+        self.definition.capture_output
+    }
+
+    fn watch(&self) -> bool {
+        self.definition.watch
+    }
+
+    fn tags(&self) -> Vec<String> {
+        self.definition.tags.clone()
+    }
+
+    fn inputs(&self) -> Vec<TaskInput> {
+        self.definition.inputs.clone()
+    }
+
+    fn icon(&self) -> Option<String> {
+        self.definition.icon.clone()
+    }
+
+    fn requires_file(&self) -> bool {
+        self.definition.requires_file
+            || self
+                .exec(None)
+                .is_some_and(|spawn_in_terminal| spawn_in_terminal.references_file_variables())
+    }
 }
 
 /// The source of tasks defined in a tasks config file.
 pub struct StaticSource {
     tasks: Vec<StaticTask>,
-    _definitions: Model<TrackedFile<DefinitionProvider>>,
+    definitions: Model<TrackedFile<DefinitionProvider>>,
     _subscription: Subscription,
 }
 
@@ -88,6 +152,75 @@ pub(crate) struct Definition {
     /// Whether to allow multiple instances of the same task to be run, or rather wait for the existing ones to finish.
     #[serde(default)]
     pub allow_concurrent_runs: bool,
+    /// Labels of the tasks to run before this one, in the order given.
+    /// The task is only run if all of them succeed.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Explicit position of the task in the task lists, lower values go first.
+    /// Tasks without it are listed after the ones that have it.
+    #[serde(default)]
+    pub order: Option<i32>,
+    /// Key to reuse the terminal by: tasks with the same key restart their process in the terminal, spawned for any of them.
+    /// Useful for long-running processes, e.g. development servers.
+    #[serde(default)]
+    pub reuse_key: Option<String>,
+    /// Whether to run the task by default with `task::RunDefaultTask`, without picking it in the tasks modal.
+    #[serde(default)]
+    pub default: bool,
+    /// Tags to filter the tasks by, e.g. with `#test` in the task modal query.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Time in seconds, after which the spawned process is terminated, if it still runs.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Shell to run the command in, e.g. `{"program": "bash"}` for the commands using bash syntax.
+    /// Defaults to the terminal's shell, which is only used if the task is run in a shell.
+    #[serde(default)]
+    pub shell: TaskShell,
+    /// Whether to show the task's terminal when the task is spawned, or to run the task silently and show it only if the task fails.
+    #[serde(default)]
+    pub reveal: RevealStrategy,
+    /// Message to notify about the task success with, e.g. `Deployed $ZED_TASK_NAME`.
+    /// Without it, a generic message is shown.
+    #[serde(default)]
+    pub success_message: Option<String>,
+    /// Message to notify about the task failure with, instead of the generic one.
+    #[serde(default)]
+    pub failure_message: Option<String>,
+    /// Name of the icon to show next to the task, e.g. `build`, `test`, `run` or `terminal`.
+    /// Unknown names fall back to the default icon of the tasks file.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Values to prompt for when the task is scheduled, referenced in the task as `$ZED_INPUT_<id>`.
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+    /// Whether the task can only run with a file open in the active editor, e.g. for the scripts finding the file on their own.
+    /// The tasks referencing `$ZED_FILE` and the other file variables require it regardless.
+    #[serde(default)]
+    pub requires_file: bool,
+    /// Whether to leave the task out of the tasks modal, e.g. for the steps, only run as the `depends_on` of the other tasks.
+    /// Hidden tasks can still be run by their names.
+    #[serde(default)]
+    pub hidden: bool,
+    /// How many times to run the task again, if it exits with a non-zero code, e.g. for the tests, failing on network hiccups.
+    /// Every attempt is notified about, the first successful one stops the retries.
+    #[serde(default)]
+    pub retries: u32,
+    /// Time in seconds to wait before each retry of the failed task.
+    #[serde(default)]
+    pub retry_delay: Option<u64>,
+    /// Whether to run the task without a terminal, showing its output in a notification, with the full output one click away.
+    /// Such tasks run to their end with no input, and are not retried.
+    #[serde(default)]
+    pub capture_output: bool,
+    /// Whether to rerun the task in its terminal on every save of the project files, once the task is scheduled,
+    /// until the watching is stopped with the `task::StopWatching` action.
+    #[serde(default)]
+    pub watch: bool,
+    /// Whether the task launches an interactive session, e.g. `python` or `node`, keeping its terminal attached for input.
+    /// Such sessions are never replaced by the reruns of the task, nor their output captured.
+    #[serde(default)]
+    pub interactive: bool,
 }
 
 /// A group of Tasks defined in a JSON file.
@@ -108,48 +241,116 @@ impl DefinitionProvider {
 /// A Wrapper around deserializable T that keeps track of it's contents
 /// via a provided channel. Once T value changes, the observers of [`TrackedFile`] are
 /// notified.
-struct TrackedFile<T> {
+/// The contents are parsed on a background thread, until the first contents arrive, the file is considered loading.
+/// Dropping the [`TrackedFile`] stops the tracking.
+pub(crate) struct TrackedFile<T> {
     parsed_contents: T,
+    error: Option<String>,
+    loading: bool,
+    _tracker: gpui::Task<anyhow::Result<()>>,
 }
 
-impl<T: for<'a> Deserialize<'a> + PartialEq + 'static> TrackedFile<T> {
-    fn new(
+/// Rapid successive file updates within this interval are coalesced into a single reparse.
+const TRACKED_FILE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+impl<T: for<'a> Deserialize<'a> + PartialEq + Send + 'static> TrackedFile<T> {
+    pub(crate) fn new(
+        parsed_contents: T,
+        tracker: UnboundedReceiver<String>,
+        cx: &mut AppContext,
+    ) -> Model<Self> {
+        Self::new_with_parser(
+            parsed_contents,
+            tracker,
+            |contents| serde_json_lenient::from_str(contents),
+            cx,
+        )
+    }
+}
+
+impl<T: PartialEq + Send + 'static> TrackedFile<T> {
+    /// Same as [`TrackedFile::new`], but for files in formats other than JSON.
+    pub(crate) fn new_with_parser<E: Into<anyhow::Error>>(
         parsed_contents: T,
         mut tracker: UnboundedReceiver<String>,
+        parse: impl Fn(&str) -> Result<T, E> + Send + Sync + 'static,
         cx: &mut AppContext,
     ) -> Model<Self> {
+        let parse = Arc::new(parse);
         cx.new_model(move |cx| {
-            cx.spawn(|tracked_file, mut cx| async move {
-                while let Some(new_contents) = tracker.next().await {
-                    if !new_contents.trim().is_empty() {
-                        let Some(new_contents) =
-                            serde_json_lenient::from_str(&new_contents).log_err()
-                        else {
-                            continue;
-                        };
-                        tracked_file.update(&mut cx, |tracked_file: &mut TrackedFile<T>, cx| {
-                            if tracked_file.parsed_contents != new_contents {
-                                tracked_file.parsed_contents = new_contents;
-                                cx.notify();
-                            };
-                        })?;
+            let _tracker = cx.spawn(|tracked_file, mut cx| async move {
+                while let Some(mut new_contents) = tracker.next().await {
+                    cx.background_executor().timer(TRACKED_FILE_DEBOUNCE).await;
+                    while let Ok(Some(newer_contents)) = tracker.try_next() {
+                        new_contents = newer_contents;
                     }
+                    let parsed_contents = if new_contents.trim().is_empty() {
+                        None
+                    } else {
+                        let parse = parse.clone();
+                        Some(
+                            cx.background_executor()
+                                .spawn(async move { parse(&new_contents).map_err(Into::into) })
+                                .await,
+                        )
+                    };
+                    tracked_file.update(&mut cx, |tracked_file: &mut TrackedFile<T>, cx| {
+                        let mut changed = std::mem::take(&mut tracked_file.loading);
+                        match parsed_contents {
+                            Some(Ok(new_contents)) => {
+                                changed |= tracked_file.error.take().is_some();
+                                if tracked_file.parsed_contents != new_contents {
+                                    tracked_file.parsed_contents = new_contents;
+                                    changed = true;
+                                }
+                            }
+                            // Keep the last successfully parsed contents, so a typo does not wipe out all tasks.
+                            Some(Err(e)) => {
+                                log::error!("{e:#}");
+                                let error = Some(format!("{e:#}"));
+                                if tracked_file.error != error {
+                                    tracked_file.error = error;
+                                    changed = true;
+                                }
+                            }
+                            None => {}
+                        }
+                        if changed {
+                            cx.notify();
+                        }
+                    })?;
                 }
                 anyhow::Ok(())
-            })
-            .detach_and_log_err(cx);
-            Self { parsed_contents }
+            });
+            Self {
+                parsed_contents,
+                error: None,
+                loading: true,
+                _tracker,
+            }
         })
     }
 
-    fn get(&self) -> &T {
+    pub(crate) fn get(&self) -> &T {
         &self.parsed_contents
     }
+
+    /// The error of the last file parsing attempt, if it failed.
+    pub(crate) fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Whether the file contents were not received and parsed yet.
+    pub(crate) fn is_loading(&self) -> bool {
+        self.loading
+    }
 }
 
 impl StaticSource {
     /// Initializes the static source, reacting on tasks config changes.
+    /// The path of the tasks config file makes the ids of its tasks, see [`TaskId::for_tasks_file`].
     pub fn new(
+        tasks_file: PathBuf,
         tasks_file_tracker: UnboundedReceiver<String>,
         cx: &mut AppContext,
     ) -> Model<Box<dyn TaskSource>> {
@@ -157,24 +358,19 @@ impl StaticSource {
         cx.new_model(|cx| {
             let _subscription = cx.observe(
                 &definitions,
-                |source: &mut Box<(dyn TaskSource + 'static)>, new_definitions, cx| {
+                move |source: &mut Box<(dyn TaskSource + 'static)>, new_definitions, cx| {
                     if let Some(static_source) = source.as_any().downcast_mut::<Self>() {
-                        static_source.tasks = new_definitions
-                            .read(cx)
-                            .get()
-                            .0
-                            .clone()
-                            .into_iter()
-                            .enumerate()
-                            .map(|(id, definition)| StaticTask::new(id, definition))
-                            .collect();
+                        static_source.tasks = StaticTask::for_definitions(
+                            &tasks_file,
+                            new_definitions.read(cx).get().0.clone(),
+                        );
                         cx.notify();
                     }
                 },
             );
             Box::new(Self {
                 tasks: Vec::new(),
-                _definitions: definitions,
+                definitions,
                 _subscription,
             })
         })
@@ -197,4 +393,42 @@ impl TaskSource for StaticSource {
     fn as_any(&mut self) -> &mut dyn std::any::Any {
         self
     }
+
+    fn error(&self, cx: &AppContext) -> Option<String> {
+        self.definitions.read(cx).error().map(ToOwned::to_owned)
+    }
+
+    fn is_loading(&self, cx: &AppContext) -> bool {
+        self.definitions.read(cx).is_loading()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interactive_tasks() {
+        let definitions: DefinitionProvider = serde_json_lenient::from_str(
+            r#"[
+                { "label": "repl", "command": "python", "interactive": true },
+                { "label": "script", "command": "python", "args": ["main.py"] }
+            ]"#,
+        )
+        .unwrap();
+        let tasks =
+            StaticTask::for_definitions(Path::new("/project/.zed/tasks.json"), definitions.0);
+        let spawns = tasks
+            .iter()
+            .map(|task| task.exec(None).unwrap())
+            .collect::<Vec<_>>();
+        assert!(
+            spawns[0].interactive,
+            "Interactive task should spawn a session, kept attached for input"
+        );
+        assert!(
+            !spawns[1].interactive,
+            "Tasks should not be interactive by default"
+        );
+    }
 }
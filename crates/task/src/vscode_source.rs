@@ -0,0 +1,288 @@
+//! A source of tasks, based on VS Code's `.vscode/tasks.json` file format.
+//! Only the fields needed to spawn the tasks are used, the rest (problem matchers, presentation options, etc.) is ignored.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collections::HashMap;
+use futures::channel::mpsc::UnboundedReceiver;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription};
+use serde::Deserialize;
+
+use crate::{static_source::TrackedFile, SpawnInTerminal, Task, TaskId, TaskSource};
+
+/// A task, defined in the VS Code tasks file.
+#[derive(Clone, Debug, PartialEq)]
+struct VsCodeTask {
+    id: TaskId,
+    definition: VsCodeTaskDefinition,
+}
+
+impl Task for VsCodeTask {
+    fn id(&self) -> &TaskId {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.definition.label
+    }
+
+    fn cwd(&self) -> Option<&Path> {
+        self.definition.options.cwd.as_deref()
+    }
+
+    fn env(&self) -> HashMap<String, String> {
+        self.definition.options.env.clone()
+    }
+
+    fn dependencies(&self) -> Vec<String> {
+        match &self.definition.depends_on {
+            Some(VsCodeDependsOn::Single(label)) => vec![label.clone()],
+            Some(VsCodeDependsOn::Multiple(labels)) => labels.clone(),
+            None => Vec::new(),
+        }
+    }
+
+    fn exec(&self, cwd: Option<PathBuf>) -> Option<SpawnInTerminal> {
+        Some(SpawnInTerminal {
+            args: self
+                .definition
+                .args
+                .iter()
+                .map(|arg| arg.value().to_owned())
+                .collect(),
+            cwd,
+            env: self.env(),
+            separate_shell: self.definition.task_type == VsCodeTaskType::Shell,
+            ..SpawnInTerminal::new(
+                self.id.clone(),
+                self.definition.label.clone(),
+                self.definition.command.clone(),
+            )
+        })
+    }
+}
+
+/// Contents of the VS Code tasks file.
+/// Tasks are kept unparsed, so that a single unsupported task does not fail the whole file.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+struct VsCodeTaskFile {
+    #[serde(default)]
+    tasks: Vec<serde_json_lenient::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct VsCodeTaskDefinition {
+    label: String,
+    command: String,
+    #[serde(default)]
+    args: Vec<VsCodeTaskArg>,
+    #[serde(default, rename = "type")]
+    task_type: VsCodeTaskType,
+    #[serde(default)]
+    options: VsCodeTaskOptions,
+    #[serde(default, rename = "dependsOn")]
+    depends_on: Option<VsCodeDependsOn>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum VsCodeDependsOn {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum VsCodeTaskType {
+    #[default]
+    Shell,
+    Process,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum VsCodeTaskArg {
+    Plain(String),
+    Quoted { value: String },
+}
+
+impl VsCodeTaskArg {
+    fn value(&self) -> &str {
+        match self {
+            Self::Plain(value) | Self::Quoted { value } => value,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+struct VsCodeTaskOptions {
+    #[serde(default)]
+    cwd: Option<PathBuf>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+}
+
+impl VsCodeTaskFile {
+    fn tasks(&self, tasks_file: &Path) -> Vec<VsCodeTask> {
+        let definitions = self
+            .tasks
+            .iter()
+            .filter_map(|task| {
+                serde_json_lenient::from_value::<VsCodeTaskDefinition>(task.clone()).ok()
+            })
+            .collect::<Vec<_>>();
+        let ids = TaskId::for_tasks_file(
+            "vscode",
+            tasks_file,
+            definitions
+                .iter()
+                .map(|definition| definition.label.as_str()),
+        );
+        ids.into_iter()
+            .zip(definitions)
+            .map(|(id, definition)| VsCodeTask { id, definition })
+            .collect()
+    }
+}
+
+/// The source of tasks defined in a VS Code tasks file.
+pub struct VsCodeTaskSource {
+    tasks: Vec<VsCodeTask>,
+    tasks_file: Model<TrackedFile<VsCodeTaskFile>>,
+    _subscription: Subscription,
+}
+
+impl VsCodeTaskSource {
+    /// Initializes the VS Code source, reacting on tasks file changes.
+    /// The path of the tasks file makes the ids of its tasks, see [`TaskId::for_tasks_file`].
+    pub fn new(
+        tasks_file_path: PathBuf,
+        tasks_file_tracker: UnboundedReceiver<String>,
+        cx: &mut AppContext,
+    ) -> Model<Box<dyn TaskSource>> {
+        let tasks_file = TrackedFile::new(VsCodeTaskFile::default(), tasks_file_tracker, cx);
+        cx.new_model(|cx| {
+            let _subscription = cx.observe(
+                &tasks_file,
+                move |source: &mut Box<(dyn TaskSource + 'static)>, new_tasks_file, cx| {
+                    if let Some(vscode_source) = source.as_any().downcast_mut::<Self>() {
+                        vscode_source.tasks = new_tasks_file.read(cx).get().tasks(&tasks_file_path);
+                        cx.notify();
+                    }
+                },
+            );
+            Box::new(Self {
+                tasks: Vec::new(),
+                tasks_file,
+                _subscription,
+            })
+        })
+    }
+}
+
+impl TaskSource for VsCodeTaskSource {
+    fn tasks_for_path(
+        &mut self,
+        _: Option<&Path>,
+        _: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Vec<Arc<dyn Task>> {
+        self.tasks
+            .clone()
+            .into_iter()
+            .map(|task| Arc::new(task) as Arc<dyn Task>)
+            .collect()
+    }
+
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn error(&self, cx: &AppContext) -> Option<String> {
+        self.tasks_file.read(cx).error().map(ToOwned::to_owned)
+    }
+
+    fn is_loading(&self, cx: &AppContext) -> bool {
+        self.tasks_file.read(cx).is_loading()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_vscode_tasks() {
+        let tasks_file = serde_json_lenient::from_str::<VsCodeTaskFile>(
+            r#"{
+                // Comments are allowed in VS Code task files
+                "version": "2.0.0",
+                "tasks": [
+                    {
+                        "label": "build",
+                        "type": "shell",
+                        "command": "cargo",
+                        "args": ["build", { "value": "--release", "quoting": "escape" }],
+                        "group": { "kind": "build", "isDefault": true },
+                        "problemMatcher": ["$rustc"],
+                        "presentation": { "reveal": "always" },
+                    },
+                    {
+                        "label": "npm install",
+                        "type": "npm",
+                        "script": "install",
+                    },
+                    {
+                        "label": "serve",
+                        "type": "process",
+                        "command": "./serve.sh",
+                        "options": { "cwd": "/project/web", "env": { "PORT": "8080" } },
+                        "dependsOn": "build",
+                    },
+                ],
+            }"#,
+        )
+        .unwrap();
+
+        let spawned = tasks_file
+            .tasks(Path::new("/project/.vscode/tasks.json"))
+            .into_iter()
+            .map(|task| {
+                let cwd = task.cwd().map(Path::to_path_buf);
+                let spawn = task.exec(cwd.clone()).unwrap();
+                (task.name().to_string(), spawn.command, spawn.args, cwd)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spawned,
+            vec![
+                (
+                    "build".to_string(),
+                    "cargo".to_string(),
+                    vec!["build".to_string(), "--release".to_string()],
+                    None,
+                ),
+                (
+                    "serve".to_string(),
+                    "./serve.sh".to_string(),
+                    Vec::new(),
+                    Some(PathBuf::from("/project/web")),
+                ),
+            ],
+            "Tasks without a command should be skipped, unsupported fields ignored"
+        );
+
+        let serve_task = tasks_file
+            .tasks(Path::new("/project/.vscode/tasks.json"))
+            .into_iter()
+            .find(|task| task.name() == "serve")
+            .unwrap();
+        assert_eq!(
+            serve_task.exec(None).unwrap().env,
+            HashMap::from_iter([("PORT".to_string(), "8080".to_string())]),
+        );
+        assert_eq!(serve_task.dependencies(), vec!["build".to_string()]);
+    }
+}
@@ -0,0 +1,142 @@
+use collections::HashMap;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Subscription, View,
+    ViewContext, VisualContext, WindowContext,
+};
+use task::TaskInput;
+use ui::prelude::*;
+use workspace::ModalView;
+
+/// Gets the input values, keyed by their variable names.
+type OnConfirm = Box<dyn FnOnce(HashMap<String, String>, &mut WindowContext)>;
+
+/// A modal to prompt for the values of the task inputs, one after another, before the task is spawned.
+/// Dismissing the modal before all values are entered cancels the task.
+pub(crate) struct TaskInputModal {
+    task_name: String,
+    inputs: Vec<TaskInput>,
+    current_input: usize,
+    values: HashMap<String, String>,
+    editor: View<Editor>,
+    on_confirm: Option<OnConfirm>,
+    _subscription: Subscription,
+}
+
+impl TaskInputModal {
+    pub(crate) fn new(
+        task_name: String,
+        inputs: Vec<TaskInput>,
+        on_confirm: impl FnOnce(HashMap<String, String>, &mut WindowContext) + 'static,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let editor = cx.new_view(|cx| Editor::single_line(cx));
+        let _subscription = cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            if let EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+        let mut modal = Self {
+            task_name,
+            inputs,
+            current_input: 0,
+            values: HashMap::default(),
+            editor,
+            on_confirm: Some(Box::new(on_confirm)),
+            _subscription,
+        };
+        modal.show_current_input(cx);
+        modal
+    }
+
+    fn show_current_input(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(input) = self.inputs.get(self.current_input) else {
+            return;
+        };
+        let placeholder = input
+            .description
+            .clone()
+            .unwrap_or_else(|| input.id.clone());
+        let default = input.default.clone().unwrap_or_default();
+        self.editor.update(cx, |editor, cx| {
+            editor.set_placeholder_text(placeholder, cx);
+            editor.set_text(default, cx);
+            editor.select_all(&Default::default(), cx);
+        });
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let Some(input) = self.inputs.get(self.current_input) else {
+            return;
+        };
+        let value = self.editor.read(cx).text(cx);
+        self.values.insert(input.variable_name(), value);
+        self.current_input += 1;
+        if self.current_input < self.inputs.len() {
+            self.show_current_input(cx);
+            return;
+        }
+
+        cx.emit(DismissEvent);
+        if let Some(on_confirm) = self.on_confirm.take() {
+            on_confirm(std::mem::take(&mut self.values), cx);
+        }
+    }
+}
+
+impl Render for TaskInputModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let progress = format!(
+            "Input {} of {} for task {:?}",
+            (self.current_input + 1).min(self.inputs.len()),
+            self.inputs.len(),
+            self.task_name,
+        );
+        div()
+            .elevation_2(cx)
+            .key_context("TaskInputModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .child(
+                v_flex()
+                    .px_1()
+                    .pt_0p5()
+                    .gap_px()
+                    .child(
+                        v_flex()
+                            .py_0p5()
+                            .px_1()
+                            .child(div().px_1().py_0p5().child(self.editor.clone())),
+                    )
+                    .child(
+                        div()
+                            .h_px()
+                            .w_full()
+                            .bg(cx.theme().colors().element_background),
+                    )
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .px_2()
+                            .py_1()
+                            .child(Label::new(progress).color(Color::Muted)),
+                    ),
+            )
+    }
+}
+
+impl EventEmitter<DismissEvent> for TaskInputModal {}
+
+impl FocusableView for TaskInputModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl ModalView for TaskInputModal {}
@@ -1,53 +1,1117 @@
-use std::path::PathBuf;
+use std::{fmt::Write as _, path::PathBuf, sync::Arc, time::Duration};
 
-use gpui::{AppContext, ViewContext, WindowContext};
+use anyhow::Context;
+use collections::{HashMap, HashSet};
+use command_palette_hooks::{CommandPaletteCommands, PaletteCommand};
+use editor::{Editor, MultiBuffer};
+use gpui::{
+    AppContext, ClipboardItem, Context as _, Model, ViewContext, VisualContext, WindowContext,
+};
+use input_modal::TaskInputModal;
+use language::Point;
 use modal::TasksModal;
-use task::Task;
-use util::ResultExt;
-use workspace::Workspace;
+use project::{
+    terminals::CapturedOutput, Inventory, InventoryEvent, SourceDescription, TaskSourceKind,
+    WorktreeId,
+};
+use settings::{Settings, SettingsStore};
+use task::{
+    alias_source::AliasTaskSource,
+    oneshot_source::{EnvExpansion, OneshotSource},
+    SpawnInTerminal, Task, TaskExitStatus, TaskId, TaskInput,
+};
+use task_settings::TaskSettings;
+use util::{paths, ResultExt};
+use workspace::{item::Item, Toast, Workspace};
 
+mod input_modal;
 mod modal;
+mod task_settings;
+mod worktree_modal;
+
+const NOTHING_TO_RERUN_TOAST_ID: usize = 0x7a5c_4e00;
+const TASK_DEPENDENCIES_TOAST_ID: usize = 0x7a5c_4e01;
+const EMPTY_TASK_COMMAND_TOAST_ID: usize = 0x7a5c_4e03;
+const REPEATED_LAUNCH_TOAST_ID: usize = 0x7a5c_4e05;
+const NO_ACTIVE_FILE_TOAST_ID: usize = 0x7a5c_4e06;
+const TASK_RETRY_TOAST_ID: usize = 0x7a5c_4e07;
+const EXPORTED_TASKS_TOAST_ID: usize = 0x7a5c_4e08;
+const CAPTURED_OUTPUT_TOAST_ID: usize = 0x7a5c_4e09;
+const TASK_NOT_FOUND_TOAST_ID: usize = 0x7a5c_4e0a;
+const TASK_WATCH_TOAST_ID: usize = 0x7a5c_4e0b;
+
+/// Saves, made within this time from each other, e.g. with "save all", rerun the watched tasks once.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Captured outputs, longer than this, are shown in the notification by their last lines only.
+const MAX_NOTIFIED_OUTPUT_LINES: usize = 10;
+const MAX_NOTIFIED_OUTPUT_CHARS: usize = 1000;
+
+/// Where a task is scheduled from, logged along with the task to tell why it ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScheduleOrigin {
+    /// Picked in one of the tasks modals.
+    Modal,
+    /// Rerun as the last scheduled task.
+    Rerun,
+    /// Scheduled programmatically, e.g. by its name.
+    Api,
+    /// Run as the default task of the active worktree.
+    Default,
+}
 
 pub fn init(cx: &mut AppContext) {
+    TaskSettings::register(cx);
     cx.observe_new_views(
-        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            let mut alias_files = Vec::new();
+            sync_alias_sources(workspace, &mut alias_files, cx);
+            sync_task_preference(workspace, cx);
+            cx.observe_global::<SettingsStore>(move |workspace, cx| {
+                sync_alias_sources(workspace, &mut alias_files, cx);
+                sync_task_preference(workspace, cx);
+            })
+            .detach();
+            register_palette_tasks(workspace, cx);
+            load_persisted_tasks_state(workspace, cx);
+            let inventory = workspace.project().read(cx).task_inventory().clone();
+            cx.subscribe(&inventory, |workspace, inventory, event, cx| {
+                if let InventoryEvent::RetryTask { id, attempt } = event {
+                    retry_task(workspace, &inventory, id, *attempt, cx);
+                }
+            })
+            .detach();
+            let project = workspace.project().clone();
+            let mut _pending_rerun = None;
+            cx.subscribe(&project, move |_, _, event, cx| {
+                if !matches!(event, project::Event::BufferSaved(_))
+                    || !inventory.read(cx).has_watched_tasks()
+                {
+                    return;
+                }
+                // Replacing the pending rerun cancels it, so the tasks rerun once the saves stop.
+                _pending_rerun = Some(cx.spawn(|workspace, mut cx| async move {
+                    cx.background_executor().timer(WATCH_DEBOUNCE).await;
+                    workspace
+                        .update(&mut cx, |workspace, cx| rerun_watched_tasks(workspace, cx))
+                        .ok();
+                }));
+            })
+            .detach();
+
             workspace
-                .register_action(|workspace, _: &modal::Spawn, cx| {
+                .register_action(|workspace, action: &modal::Spawn, cx| {
+                    toggle_tasks_modal(workspace, action.query.clone(), cx);
+                })
+                .register_action(|workspace, action: &modal::SpawnByName, cx| {
+                    let active_worktree = active_worktree(workspace, cx);
+                    if let Err(e) =
+                        schedule_task_by_name(workspace, &action.task_name, active_worktree, cx)
+                    {
+                        workspace
+                            .show_toast(Toast::new(TASK_NOT_FOUND_TOAST_ID, format!("{e:#}")), cx);
+                    }
+                })
+                .register_action(|workspace, _: &modal::RunDefaultTask, cx| {
                     let inventory = workspace.project().read(cx).task_inventory().clone();
-                    let workspace_handle = workspace.weak_handle();
-                    workspace
-                        .toggle_modal(cx, |cx| TasksModal::new(inventory, workspace_handle, cx))
+                    let active_worktree = active_worktree(workspace, cx);
+                    let default_task = inventory.update(cx, |inventory, cx| {
+                        inventory.default_task(active_worktree, cx)
+                    });
+                    match default_task {
+                        Some((source_kind, task)) => schedule_task(
+                            workspace,
+                            source_kind,
+                            &task,
+                            ScheduleOrigin::Default,
+                            cx,
+                        ),
+                        // Without a default task, let the user pick among the likely candidates.
+                        None => toggle_tasks_modal(workspace, Some("build".to_string()), cx),
+                    }
                 })
                 .register_action(move |workspace, _: &modal::Rerun, cx| {
-                    if let Some(task) = workspace.project().update(cx, |project, cx| {
-                        project
-                            .task_inventory()
-                            .update(cx, |inventory, cx| inventory.last_scheduled_task(cx))
-                    }) {
-                        schedule_task(workspace, task.as_ref(), cx)
+                    let inventory = workspace.project().read(cx).task_inventory().clone();
+                    let last_task = inventory.update(cx, |inventory, cx| {
+                        let (source_kind, task) = inventory.last_scheduled_task(cx)?;
+                        let cwd = inventory
+                            .last_scheduled_cwd(task.id())
+                            .map(|cwd| cwd.to_path_buf());
+                        Some((source_kind, task, cwd))
+                    });
+                    match last_task {
+                        Some((source_kind, task, Some(cwd))) => schedule_task_in(
+                            workspace,
+                            source_kind,
+                            &task,
+                            Some(cwd),
+                            ScheduleOrigin::Rerun,
+                            cx,
+                        ),
+                        Some((source_kind, task, None)) => {
+                            schedule_task(workspace, source_kind, &task, ScheduleOrigin::Rerun, cx)
+                        }
+                        None => workspace.show_toast(
+                            Toast::new(
+                                NOTHING_TO_RERUN_TOAST_ID,
+                                "No tasks were run yet, nothing to rerun",
+                            ),
+                            cx,
+                        ),
+                    }
+                })
+                .register_action(|workspace, _: &modal::ShowSources, cx| {
+                    show_sources(workspace, cx);
+                })
+                .register_action(|workspace, _: &modal::ExportTasks, cx| {
+                    export_tasks(workspace, cx);
+                })
+                .register_action(|workspace, _: &modal::StopWatching, cx| {
+                    let inventory = workspace.project().read(cx).task_inventory().clone();
+                    let unwatched = inventory.update(cx, |inventory, cx| inventory.unwatch_all(cx));
+                    let message = match unwatched {
+                        0 => "No tasks are watched".to_string(),
+                        1 => "Stopped watching 1 task".to_string(),
+                        _ => format!("Stopped watching {unwatched} tasks"),
+                    };
+                    workspace.show_toast(Toast::new(TASK_WATCH_TOAST_ID, message), cx);
+                })
+                .register_action(|workspace, _: &modal::EnableTaskSources, cx| {
+                    let inventory = workspace.project().read(cx).task_inventory().clone();
+                    inventory.update(cx, |inventory, cx| {
+                        for description in inventory.describe_sources(cx) {
+                            if !description.enabled {
+                                inventory.set_source_enabled(&description.kind, true, cx);
+                            }
+                        }
+                    });
+                })
+                .register_action(|workspace, _: &modal::RescanTasks, cx| {
+                    // Re-reads the tasks files from disk, e.g. after they were changed outside of Zed unnoticed.
+                    let inventory = workspace.project().read(cx).task_inventory().clone();
+                    inventory.update(cx, |inventory, cx| inventory.refresh_sources(cx));
+                })
+                .register_action(|workspace, _: &modal::ClearHistory, cx| {
+                    let inventory = workspace.project().read(cx).task_inventory().clone();
+                    inventory.update(cx, |inventory, cx| inventory.clear_history(cx));
+                })
+                .register_action(|workspace, _: &modal::ClearOneshotTasks, cx| {
+                    let inventory = workspace.project().read(cx).task_inventory().clone();
+                    let Some(oneshot_source) =
+                        inventory.update(cx, |inventory, _| inventory.source::<OneshotSource>())
+                    else {
+                        return;
                     };
+                    oneshot_source.update(cx, |oneshot_source, cx| {
+                        if let Some(oneshot_source) =
+                            oneshot_source.as_any().downcast_mut::<OneshotSource>()
+                        {
+                            oneshot_source.clear(cx);
+                        }
+                    });
                 });
         },
     )
     .detach();
 }
 
-fn schedule_task(workspace: &Workspace, task: &dyn Task, cx: &mut ViewContext<'_, Workspace>) {
-    let cwd = match task.cwd() {
-        Some(cwd) => Some(cwd.to_path_buf()),
-        None => task_cwd(workspace, cx).log_err().flatten(),
+/// Restores the tasks state, persisted for the workspace, e.g. the task history, once its worktrees are scanned,
+/// so the task sources of the worktrees are registered by then.
+fn load_persisted_tasks_state(workspace: &Workspace, cx: &mut ViewContext<'_, Workspace>) {
+    let scope = workspace.database_id().to_string();
+    let project = workspace.project().clone();
+    let scans = project
+        .read(cx)
+        .worktrees()
+        .filter_map(|worktree| Some(worktree.read(cx).as_local()?.scan_complete()))
+        .collect::<Vec<_>>();
+    cx.spawn(|_, mut cx| async move {
+        for scan in scans {
+            scan.await;
+        }
+        let inventory = project.update(&mut cx, |project, _| project.task_inventory().clone())?;
+        inventory.update(&mut cx, |inventory, cx| {
+            inventory.load_persisted_state(scope, cx)
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Lists the tasks of the workspace's project in the command palette of its window, up to the configured number,
+/// as the commands, scheduling the tasks by their names.
+/// The tasks stop being listed once the workspace is released.
+fn register_palette_tasks(workspace: &Workspace, cx: &mut ViewContext<'_, Workspace>) {
+    let inventory = workspace.project().read(cx).task_inventory().downgrade();
+    let window = cx.window_handle();
+    let owner = cx.view().entity_id();
+    cx.on_release(move |_, window, cx| {
+        cx.default_global::<CommandPaletteCommands>()
+            .remove_provider(window, owner);
+    })
+    .detach();
+    cx.default_global::<CommandPaletteCommands>()
+        .set_provider(window, owner, move |cx| {
+            let settings = TaskSettings::get_global(cx);
+            let (max_tasks, lru) = (settings.command_palette_tasks, settings.reorder_by_usage);
+            let Some(inventory) = inventory.upgrade().filter(|_| max_tasks > 0) else {
+                return Vec::new();
+            };
+            // The workspace is busy opening the palette, so the tasks of all worktrees are listed.
+            let listed = inventory.update(cx, |inventory, cx| {
+                inventory.list_tasks_limited(None, None, lru, max_tasks, cx)
+            });
+            // Same named tasks are scheduled by their source precedence anyway, so they are listed once.
+            let mut task_names = HashSet::default();
+            listed
+                .tasks
+                .into_iter()
+                .filter(|(_, task)| task_names.insert(task.name().to_string()))
+                .map(|(_, task)| PaletteCommand {
+                    name: format!("run task: {}", task.name()),
+                    action: Box::new(modal::SpawnByName {
+                        task_name: task.name().to_string(),
+                    }),
+                })
+                .collect()
+        });
+}
+
+/// Copies the tasks of the project, as JSON, to the clipboard, for the external tools to use.
+fn export_tasks(workspace: &mut Workspace, cx: &mut ViewContext<'_, Workspace>) {
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    let exported_tasks = inventory.update(cx, |inventory, cx| inventory.export_tasks(cx));
+    let task_count = exported_tasks.as_array().map_or(0, Vec::len);
+    let Some(json) = serde_json::to_string_pretty(&exported_tasks).log_err() else {
+        return;
     };
-    let spawn_in_terminal = task.exec(cwd);
-    if let Some(spawn_in_terminal) = spawn_in_terminal {
-        workspace.project().update(cx, |project, cx| {
-            project.task_inventory().update(cx, |inventory, _| {
-                inventory.task_scheduled(task.id().clone());
-            })
+    cx.write_to_clipboard(ClipboardItem::new(json));
+    workspace.show_toast(
+        Toast::new(
+            EXPORTED_TASKS_TOAST_ID,
+            format!("Copied {task_count} tasks to the clipboard as JSON"),
+        ),
+        cx,
+    );
+}
+
+/// Makes the inventory prefer the worktree tasks or the others, as the settings say.
+fn sync_task_preference(workspace: &Workspace, cx: &mut ViewContext<'_, Workspace>) {
+    let prefer_worktree_tasks = TaskSettings::get_global(cx).prefer_worktree_tasks;
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    inventory.update(cx, |inventory, cx| {
+        inventory.set_prefer_worktree_tasks(prefer_worktree_tasks, cx);
+    });
+}
+
+/// Registers the task sources for the alias files from the settings, removing the ones for the files no longer listed.
+/// `registered` are the alias files, registered by the previous call.
+fn sync_alias_sources(
+    workspace: &Workspace,
+    registered: &mut Vec<PathBuf>,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let project = workspace.project().read(cx);
+    if !project.is_local() {
+        return;
+    }
+    let alias_files = TaskSettings::get_global(cx)
+        .alias_files
+        .iter()
+        .map(|alias_file| match alias_file.strip_prefix("~") {
+            Ok(home_relative) => paths::HOME.join(home_relative),
+            Err(_) => alias_file.clone(),
+        })
+        .collect::<Vec<_>>();
+    if &alias_files == registered {
+        return;
+    }
+    let fs = project.fs().clone();
+    let inventory = project.task_inventory().clone();
+    inventory.update(cx, |inventory, cx| {
+        for removed_file in registered
+            .iter()
+            .filter(|alias_file| !alias_files.contains(alias_file))
+        {
+            inventory.remove_local_static_source(removed_file, cx);
+        }
+        for alias_file in alias_files
+            .iter()
+            .filter(|alias_file| !registered.contains(alias_file))
+        {
+            let alias_file = alias_file.clone();
+            inventory.add_static_source(
+                TaskSourceKind::AbsPath(alias_file.clone()),
+                fs.clone(),
+                move |alias_file_tracker, cx| {
+                    AliasTaskSource::new(alias_file, alias_file_tracker, cx)
+                },
+                cx,
+            );
+        }
+    });
+    *registered = alias_files;
+}
+
+/// Opens a buffer, describing every registered task source, to find out why some tasks are listed or not.
+fn show_sources(workspace: &mut Workspace, cx: &mut ViewContext<'_, Workspace>) {
+    let project = workspace.project().clone();
+    let inventory = project.read(cx).task_inventory().clone();
+    let descriptions = inventory.update(cx, |inventory, cx| inventory.describe_sources(cx));
+    let Some(buffer) = project
+        .update(cx, |project, cx| {
+            project.create_buffer(&sources_text(&descriptions), None, cx)
+        })
+        .log_err()
+    else {
+        return;
+    };
+    let buffer = cx
+        .new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title("Task Sources".to_string()));
+    workspace.add_item(
+        Box::new(cx.new_view(|cx| Editor::for_multibuffer(buffer, Some(project), cx))),
+        cx,
+    );
+}
+
+fn sources_text(descriptions: &[SourceDescription]) -> String {
+    let mut text = format!("{} task sources registered\n", descriptions.len());
+    for description in descriptions {
+        let kind = &description.kind;
+        writeln!(text, "\n{}: {} tasks", kind.label(), description.task_count).ok();
+        writeln!(text, "  kind: {kind:?}").ok();
+        if let Some(abs_path) = kind.abs_path() {
+            writeln!(text, "  path: {}", abs_path.display()).ok();
+        }
+        if let Some(worktree) = kind.worktree() {
+            writeln!(text, "  worktree: {worktree:?}").ok();
+        }
+        writeln!(text, "  type: {:?}", description.type_id).ok();
+        if !description.enabled {
+            writeln!(text, "  disabled, the tasks are not listed").ok();
+        }
+        if let Some(tasks_file) = &description.tasks_file {
+            let missing = if description.tasks_file_missing {
+                " (missing on disk, the tasks are not listed)"
+            } else {
+                ""
+            };
+            writeln!(text, "  tasks file: {}{missing}", tasks_file.display()).ok();
+        }
+        if description.loading {
+            writeln!(text, "  loading").ok();
+        }
+        if let Some(error) = &description.error {
+            writeln!(text, "  error: {error}").ok();
+        }
+    }
+    text
+}
+
+fn toggle_tasks_modal(
+    workspace: &mut Workspace,
+    query: Option<String>,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    let workspace_handle = workspace.weak_handle();
+    let active_worktree = active_worktree(workspace, cx);
+    workspace.toggle_modal(cx, |cx| {
+        TasksModal::new(inventory, workspace_handle, active_worktree, query, cx)
+    })
+}
+
+/// Worktree of the active item, to list its tasks first and to look up its default task.
+fn active_worktree(workspace: &Workspace, cx: &AppContext) -> Option<WorktreeId> {
+    workspace
+        .active_item(cx)
+        .and_then(|item| item.project_path(cx))
+        .map(|project_path| project_path.worktree_id)
+}
+
+/// Schedules the task with the exact name given, as if it was picked in the tasks modal.
+/// Same named tasks are resolved by their source precedence, only the tasks for the worktree given are considered, if any.
+/// Returns an error if no such task exists, for the caller to report.
+pub fn schedule_task_by_name(
+    workspace: &mut Workspace,
+    name: &str,
+    worktree: Option<WorktreeId>,
+    cx: &mut ViewContext<'_, Workspace>,
+) -> anyhow::Result<()> {
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    let (source_kind, task) = inventory
+        .update(cx, |inventory, cx| {
+            inventory.task_by_name(name, worktree, cx)
+        })
+        .with_context(|| format!("No task named {name:?} found"))?;
+    schedule_task(workspace, source_kind, &task, ScheduleOrigin::Api, cx);
+    Ok(())
+}
+
+fn schedule_task(
+    workspace: &mut Workspace,
+    source_kind: TaskSourceKind,
+    task: &Arc<dyn Task>,
+    origin: ScheduleOrigin,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let cwd = scheduled_task_cwd(workspace, &source_kind, task.as_ref(), cx);
+    schedule_task_in(workspace, source_kind, task, cwd, origin, cx)
+}
+
+fn scheduled_task_cwd(
+    workspace: &Workspace,
+    source_kind: &TaskSourceKind,
+    task: &dyn Task,
+    cx: &mut WindowContext,
+) -> Option<PathBuf> {
+    match source_kind {
+        TaskSourceKind::UserInput if task.cwd().is_none() => Some(oneshot_task_cwd(workspace, cx)),
+        _ => default_task_cwd(workspace, source_kind, task, cx),
+    }
+}
+
+fn schedule_task_in(
+    workspace: &mut Workspace,
+    source_kind: TaskSourceKind,
+    task: &Arc<dyn Task>,
+    cwd: Option<PathBuf>,
+    origin: ScheduleOrigin,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    if inventory
+        .read(cx)
+        .is_recently_scheduled(task.id(), cwd.as_deref())
+    {
+        workspace.show_toast(
+            Toast::new(
+                REPEATED_LAUNCH_TOAST_ID,
+                format!("Task {:?} has just been scheduled, skipping", task.name()),
+            ),
+            cx,
+        );
+        return;
+    }
+    let dependencies = match inventory.update(cx, |inventory, cx| {
+        inventory.resolve_dependencies(task.as_ref(), cx)
+    }) {
+        Ok(dependencies) => dependencies,
+        Err(e) => {
+            workspace.show_toast(
+                Toast::new(
+                    TASK_DEPENDENCIES_TOAST_ID,
+                    format!("Cannot run task {:?}: {e}", task.name()),
+                ),
+                cx,
+            );
+            return;
+        }
+    };
+
+    let inputs = task_inputs(
+        dependencies
+            .iter()
+            .map(|(_, dependency)| dependency)
+            .chain(Some(task)),
+    );
+    let task = task.clone();
+    with_input_values(
+        workspace,
+        task.name().to_string(),
+        inputs,
+        move |workspace, input_values, cx| {
+            spawn_task(
+                workspace,
+                source_kind,
+                task.as_ref(),
+                dependencies,
+                cwd,
+                input_values,
+                origin,
+                cx,
+            );
+        },
+        cx,
+    );
+}
+
+/// Schedules all tasks given, prompting for the inputs of all of them at once.
+/// Sequentially scheduled tasks run one after another in a single terminal, stopping at the first failed one,
+/// otherwise every task is spawned in its own terminal right away.
+pub(crate) fn schedule_tasks(
+    workspace: &mut Workspace,
+    tasks: Vec<(TaskSourceKind, Arc<dyn Task>)>,
+    sequentially: bool,
+    origin: ScheduleOrigin,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    let mut scheduled = Vec::with_capacity(tasks.len());
+    for (source_kind, task) in tasks {
+        let dependencies = match inventory.update(cx, |inventory, cx| {
+            inventory.resolve_dependencies(task.as_ref(), cx)
+        }) {
+            Ok(dependencies) => dependencies,
+            Err(e) => {
+                workspace.show_toast(
+                    Toast::new(
+                        TASK_DEPENDENCIES_TOAST_ID,
+                        format!("Cannot run task {:?}: {e}", task.name()),
+                    ),
+                    cx,
+                );
+                return;
+            }
+        };
+        let cwd = scheduled_task_cwd(workspace, &source_kind, task.as_ref(), cx);
+        scheduled.push((source_kind, task, cwd, dependencies));
+    }
+
+    let task_names = scheduled
+        .iter()
+        .map(|(_, task, _, _)| task.name())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let inputs = task_inputs(scheduled.iter().flat_map(|(_, task, _, dependencies)| {
+        dependencies
+            .iter()
+            .map(|(_, dependency)| dependency)
+            .chain(Some(task))
+    }));
+    with_input_values(
+        workspace,
+        task_names,
+        inputs,
+        move |workspace, input_values, cx| {
+            if !sequentially {
+                for (source_kind, task, cwd, dependencies) in scheduled {
+                    spawn_task(
+                        workspace,
+                        source_kind,
+                        task.as_ref(),
+                        dependencies,
+                        cwd,
+                        input_values.clone(),
+                        origin,
+                        cx,
+                    );
+                }
+                return;
+            }
+
+            // The tasks before the last one run as its dependencies, after their own ones.
+            let Some((source_kind, task, cwd, dependencies)) = scheduled.pop() else {
+                return;
+            };
+            let mut steps = Vec::new();
+            let mut preceding_tasks = Vec::with_capacity(scheduled.len());
+            for (step_source_kind, step, step_cwd, step_dependencies) in scheduled {
+                steps.extend(step_dependencies);
+                steps.push((step_source_kind.clone(), step.clone()));
+                preceding_tasks.push((step_source_kind, step.id().clone(), step_cwd));
+            }
+            steps.extend(dependencies);
+            let spawned = spawn_task(
+                workspace,
+                source_kind,
+                task.as_ref(),
+                steps,
+                cwd,
+                input_values,
+                origin,
+                cx,
+            );
+            if spawned {
+                let inventory = workspace.project().read(cx).task_inventory().clone();
+                inventory.update(cx, |inventory, cx| {
+                    for (source_kind, task_id, cwd) in preceding_tasks {
+                        inventory.task_scheduled(source_kind, task_id, cwd, cx);
+                    }
+                });
+            }
+        },
+        cx,
+    );
+}
+
+/// Inputs of the tasks given, without the repeated ones.
+fn task_inputs<'a>(tasks: impl IntoIterator<Item = &'a Arc<dyn Task>>) -> Vec<TaskInput> {
+    let mut input_ids = HashSet::default();
+    tasks
+        .into_iter()
+        .flat_map(|task| task.inputs())
+        .filter(|input| input_ids.insert(input.id.clone()))
+        .collect()
+}
+
+/// Prompts for the values of the inputs given, calling back with them, or right away if there are no inputs.
+fn with_input_values(
+    workspace: &mut Workspace,
+    task_name: String,
+    inputs: Vec<TaskInput>,
+    on_values: impl FnOnce(&mut Workspace, HashMap<String, String>, &mut ViewContext<'_, Workspace>)
+        + 'static,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    if inputs.is_empty() {
+        on_values(workspace, HashMap::default(), cx);
+        return;
+    }
+
+    // The modal the task is scheduled from may still be open, and cannot be replaced while it handles its own action.
+    cx.spawn(|workspace, mut cx| async move {
+        workspace.update(&mut cx, |workspace, cx| {
+            let workspace_handle = workspace.weak_handle();
+            workspace.toggle_modal(cx, |cx| {
+                TaskInputModal::new(
+                    task_name,
+                    inputs,
+                    move |input_values, cx| {
+                        workspace_handle
+                            .update(cx, |workspace, cx| on_values(workspace, input_values, cx))
+                            .ok();
+                    },
+                    cx,
+                )
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Spawns the task after its dependencies, with the task variables and input values given substituted.
+/// Returns whether the task was spawned.
+fn spawn_task(
+    workspace: &mut Workspace,
+    source_kind: TaskSourceKind,
+    task: &dyn Task,
+    dependencies: Vec<(TaskSourceKind, Arc<dyn Task>)>,
+    cwd: Option<PathBuf>,
+    input_values: HashMap<String, String>,
+    origin: ScheduleOrigin,
+    cx: &mut ViewContext<'_, Workspace>,
+) -> bool {
+    let mut variables = task_variables(workspace, cx);
+    variables.extend(input_values);
+    let has_file = variables.contains_key(task::VARIABLE_FILE);
+    let mut spawns = Vec::with_capacity(dependencies.len() + 1);
+    for (dependency_source_kind, dependency) in dependencies {
+        let dependency_cwd =
+            default_task_cwd(workspace, &dependency_source_kind, dependency.as_ref(), cx);
+        if !has_file && dependency.requires_file() {
+            show_no_active_file_toast(workspace, task, dependency.name(), cx);
+            return false;
+        }
+        let Some(mut spawn_in_terminal) = dependency.exec(dependency_cwd) else {
+            log::error!(
+                "Task {:?} depends on {:?}, which cannot be spawned",
+                task.name(),
+                dependency.name()
+            );
+            return false;
+        };
+        variables.insert(
+            task::VARIABLE_TASK_NAME.to_string(),
+            dependency.name().to_string(),
+        );
+        spawn_in_terminal.substitute_variables(&variables);
+        spawns.push(spawn_in_terminal);
+    }
+    if !has_file && task.requires_file() {
+        show_no_active_file_toast(workspace, task, task.name(), cx);
+        return false;
+    }
+    let Some(mut spawn_in_terminal) = task.exec(cwd.clone()) else {
+        return false;
+    };
+    variables.insert(
+        task::VARIABLE_TASK_NAME.to_string(),
+        task.name().to_string(),
+    );
+    spawn_in_terminal.substitute_variables(&variables);
+    let env_expansion = TaskSettings::get_global(cx).oneshot_env_expansion;
+    if matches!(source_kind, TaskSourceKind::UserInput)
+        && env_expansion == EnvExpansion::Environment
+    {
+        let mut env = workspace.project().read(cx).terminal_env(cx);
+        env.extend(spawn_in_terminal.env.clone());
+        spawn_in_terminal.command = env_expansion.expand(&spawn_in_terminal.command, &env);
+    }
+    spawns.push(spawn_in_terminal);
+    if let Some(empty_spawn) = spawns.iter().find(|spawn| !spawn.has_command()) {
+        workspace.show_toast(
+            Toast::new(
+                EMPTY_TASK_COMMAND_TOAST_ID,
+                format!(
+                    "Cannot run task {:?}: task {:?} has an empty command",
+                    task.name(),
+                    empty_spawn.label
+                ),
+            ),
+            cx,
+        );
+        return false;
+    }
+    let spawn_in_terminal = if spawns.len() == 1 {
+        spawns.pop()
+    } else {
+        SpawnInTerminal::chain(spawns)
+    };
+
+    if let Some(mut spawn_in_terminal) = spawn_in_terminal {
+        log::debug!(
+            "Scheduling task {:?} (id: {:?}, source: {source_kind:?}, origin: {origin:?}), command: {:?}, cwd: {:?}",
+            task.name(),
+            task.id().0,
+            spawn_in_terminal.command_line(),
+            spawn_in_terminal.cwd,
+        );
+        let inventory = workspace.project().read(cx).task_inventory().clone();
+        // Interactive sessions need a terminal to take the input, and are ended by the user, so they are never captured, watched or retried.
+        let interactive = spawn_in_terminal.interactive;
+        let capture_output = !interactive && task.capture_output();
+        let watch = !interactive && !capture_output && task.watch();
+        if watch {
+            // Reruns restart the task in its terminal, instead of piling up the new ones.
+            spawn_in_terminal
+                .reuse_key
+                .get_or_insert_with(|| task.id().0.clone());
+        }
+        let newly_watched = inventory.update(cx, |inventory, cx| {
+            inventory.task_scheduled(source_kind, task.id().clone(), cwd, cx);
+            if !capture_output && !interactive {
+                inventory.retry_on_failure(spawn_in_terminal.clone());
+            }
+            watch && inventory.watch_task(spawn_in_terminal.clone(), cx)
         });
+        if newly_watched {
+            workspace.show_toast(
+                Toast::new(
+                    TASK_WATCH_TOAST_ID,
+                    format!(
+                        "Task {:?} reruns on every save, until stopped with `task: stop watching`",
+                        task.name()
+                    ),
+                ),
+                cx,
+            );
+        }
+        if capture_output {
+            run_capturing_output(workspace, spawn_in_terminal, cx);
+        } else {
+            cx.emit(workspace::Event::SpawnTask(spawn_in_terminal));
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// Runs the task without a terminal, notifying about its output once it finishes.
+/// Long outputs are cut in the notification, which offers to open the whole output in a buffer.
+fn run_capturing_output(
+    workspace: &mut Workspace,
+    spawn_in_terminal: SpawnInTerminal,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let label = spawn_in_terminal.label.clone();
+    let run = workspace.project().update(cx, |project, cx| {
+        project.run_capturing_output(spawn_in_terminal, cx)
+    });
+    cx.spawn(|workspace, mut cx| async move {
+        let captured = run.await;
+        workspace.update(&mut cx, |workspace, cx| {
+            let captured = match captured {
+                Ok(captured) => captured,
+                Err(e) => {
+                    workspace.show_toast(
+                        Toast::new(
+                            CAPTURED_OUTPUT_TOAST_ID,
+                            format!("Failed to run task {label:?}: {e:#}"),
+                        ),
+                        cx,
+                    );
+                    return;
+                }
+            };
+            let output = captured_output_text(&captured);
+            let status = match captured.exit_status {
+                TaskExitStatus::Success => format!("Task {label:?} succeeded"),
+                TaskExitStatus::Failure(Some(code)) => {
+                    format!("Task {label:?} failed with exit code {code}")
+                }
+                TaskExitStatus::Failure(None) => format!("Task {label:?} was terminated"),
+            };
+            let Some(tail) = output_tail(&output) else {
+                workspace.show_toast(
+                    Toast::new(CAPTURED_OUTPUT_TOAST_ID, format!("{status}, no output")),
+                    cx,
+                );
+                return;
+            };
+            let message = if tail.len() < output.len() {
+                format!("{status}, last lines of the output:\n…\n{tail}")
+            } else {
+                format!("{status}:\n{tail}")
+            };
+            let weak_workspace = cx.view().downgrade();
+            workspace.show_toast(
+                Toast::new(CAPTURED_OUTPUT_TOAST_ID, message).on_click(
+                    "View Full Output",
+                    move |cx| {
+                        weak_workspace
+                            .update(cx, |workspace, cx| {
+                                show_captured_output(workspace, &label, &output, cx)
+                            })
+                            .ok();
+                    },
+                ),
+                cx,
+            );
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// The whole output of the task, with its stderr after the stdout.
+fn captured_output_text(captured: &CapturedOutput) -> String {
+    let stdout = captured.stdout.trim_end();
+    let stderr = captured.stderr.trim_end();
+    match (stdout.is_empty(), stderr.is_empty()) {
+        (_, true) => stdout.to_string(),
+        (true, false) => stderr.to_string(),
+        (false, false) => format!("{stdout}\n{stderr}"),
+    }
+}
+
+/// The end of the output to notify about, at most [`MAX_NOTIFIED_OUTPUT_LINES`] lines of [`MAX_NOTIFIED_OUTPUT_CHARS`] total,
+/// `None` if there is no output at all.
+fn output_tail(output: &str) -> Option<&str> {
+    if output.trim().is_empty() {
+        return None;
+    }
+    let mut tail_start = output
+        .match_indices('\n')
+        .rev()
+        .nth(MAX_NOTIFIED_OUTPUT_LINES - 1)
+        .map_or(0, |(newline, _)| newline + 1);
+    if output.len() - tail_start > MAX_NOTIFIED_OUTPUT_CHARS {
+        tail_start = output.len() - MAX_NOTIFIED_OUTPUT_CHARS;
+        while !output.is_char_boundary(tail_start) {
+            tail_start += 1;
+        }
+    }
+    Some(&output[tail_start..])
+}
+
+/// Opens a buffer with the whole output of the task, run without a terminal.
+fn show_captured_output(
+    workspace: &mut Workspace,
+    label: &str,
+    output: &str,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let project = workspace.project().clone();
+    let Some(buffer) = project
+        .update(cx, |project, cx| project.create_buffer(output, None, cx))
+        .log_err()
+    else {
+        return;
+    };
+    let buffer = cx.new_model(|cx| {
+        MultiBuffer::singleton(buffer, cx).with_title(format!("Output of {label:?}"))
+    });
+    workspace.add_item(
+        Box::new(cx.new_view(|cx| Editor::for_multibuffer(buffer, Some(project), cx))),
+        cx,
+    );
+}
+
+/// Spawns the watched tasks again, after the project files were saved.
+fn rerun_watched_tasks(workspace: &mut Workspace, cx: &mut ViewContext<'_, Workspace>) {
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    for spawn_in_terminal in inventory.read(cx).watched_tasks() {
+        log::debug!(
+            "Rerunning watched task {:?} (id: {:?}) after a save",
+            spawn_in_terminal.label,
+            spawn_in_terminal.id.0,
+        );
         cx.emit(workspace::Event::SpawnTask(spawn_in_terminal));
     }
 }
 
+/// Spawns the failed task again, after its retry delay, notifying about the attempt.
+fn retry_task(
+    workspace: &mut Workspace,
+    inventory: &Model<Inventory>,
+    id: &TaskId,
+    attempt: u32,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let Some(spawn_in_terminal) = inventory.read(cx).task_to_retry(id) else {
+        return;
+    };
+    workspace.show_toast(
+        Toast::new(
+            TASK_RETRY_TOAST_ID,
+            format!(
+                "Task {:?} failed, retrying ({attempt} of {})",
+                spawn_in_terminal.label, spawn_in_terminal.retries
+            ),
+        ),
+        cx,
+    );
+    let retry_delay = spawn_in_terminal.retry_delay;
+    cx.spawn(|workspace, mut cx| async move {
+        if let Some(retry_delay) = retry_delay {
+            cx.background_executor().timer(retry_delay).await;
+        }
+        workspace.update(&mut cx, |_, cx| {
+            cx.emit(workspace::Event::SpawnTask(spawn_in_terminal));
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Tasks requiring a file are not spawned with the file variables left unsubstituted.
+pub(crate) fn show_no_active_file_toast(
+    workspace: &mut Workspace,
+    task: &dyn Task,
+    file_task_name: &str,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    workspace.show_toast(
+        Toast::new(
+            NO_ACTIVE_FILE_TOAST_ID,
+            format!(
+                "Cannot run task {:?}: task {file_task_name:?} needs a file open in the active editor",
+                task.name()
+            ),
+        ),
+        cx,
+    );
+}
+
+/// The command line of the task given, with the variables substituted as if the task was scheduled now.
+fn resolved_command(
+    workspace: &Workspace,
+    source_kind: &TaskSourceKind,
+    task: &dyn Task,
+    cx: &mut WindowContext,
+) -> Option<String> {
+    let cwd = default_task_cwd(workspace, source_kind, task, cx);
+    let mut spawn_in_terminal = task.exec(cwd)?;
+    spawn_in_terminal.substitute_variables(&task_variables(workspace, cx));
+    Some(spawn_in_terminal.command_line())
+}
+
+fn default_task_cwd(
+    workspace: &Workspace,
+    source_kind: &TaskSourceKind,
+    task: &dyn Task,
+    cx: &mut WindowContext,
+) -> Option<PathBuf> {
+    match task.cwd() {
+        Some(cwd) => {
+            let worktree_root = source_worktree_root(workspace, source_kind, cx);
+            Some(source_kind.resolve_cwd(cwd, worktree_root.as_deref()))
+        }
+        None => task_cwd(workspace, cx).log_err().flatten(),
+    }
+}
+
+/// Root of the worktree the source given belongs to, to resolve the relative working directories of its tasks against.
+fn source_worktree_root(
+    workspace: &Workspace,
+    source_kind: &TaskSourceKind,
+    cx: &AppContext,
+) -> Option<PathBuf> {
+    let project = workspace.project().read(cx);
+    let worktree = match source_kind.worktree() {
+        Some(worktree_id) => project.worktree_for_id(worktree_id, cx)?,
+        None => project.find_local_worktree(source_kind.abs_path()?, cx)?.0,
+    };
+    let worktree_root = worktree.read(cx).abs_path().to_path_buf();
+    Some(worktree_root)
+}
+
+/// Oneshot tasks run in the root of the active item's worktree, so the commands typed behave as if run in the project.
+/// Without an active item, the first worktree is used, and the home directory if there are no worktrees.
+fn oneshot_task_cwd(workspace: &Workspace, cx: &mut WindowContext) -> PathBuf {
+    let project = workspace.project().read(cx);
+    let active_worktree = workspace
+        .active_item(cx)
+        .and_then(|item| item.project_path(cx))
+        .and_then(|project_path| project.worktree_for_id(project_path.worktree_id, cx));
+    active_worktree
+        .into_iter()
+        .chain(project.visible_worktrees(cx))
+        .map(|worktree| worktree.read(cx))
+        .find(|worktree| worktree.is_local() && worktree.root_entry().map_or(false, |e| e.is_dir()))
+        .map(|worktree| worktree.abs_path().to_path_buf())
+        .unwrap_or_else(|| paths::HOME.clone())
+}
+
+fn task_variables(workspace: &Workspace, cx: &mut WindowContext) -> HashMap<String, String> {
+    let mut variables = HashMap::default();
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return variables;
+    };
+    let editor = editor.read(cx);
+    let cursor = editor.selections.newest::<Point>(cx).head();
+    variables.insert(
+        task::VARIABLE_LINE.to_string(),
+        (cursor.row + 1).to_string(),
+    );
+    variables.insert(
+        task::VARIABLE_COLUMN.to_string(),
+        (cursor.column + 1).to_string(),
+    );
+
+    let project = workspace.project().read(cx);
+    let Some(project_path) = editor.project_path(cx) else {
+        return variables;
+    };
+    if let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) {
+        variables.insert(
+            task::VARIABLE_WORKTREE_ROOT.to_string(),
+            worktree.read(cx).abs_path().to_string_lossy().into_owned(),
+        );
+    }
+    if let Some(abs_path) = project.absolute_path(&project_path, cx) {
+        if let Some(dir) = abs_path.parent() {
+            variables.insert(
+                task::VARIABLE_DIR.to_string(),
+                dir.to_string_lossy().into_owned(),
+            );
+        }
+        let file_name_parts = [
+            (task::VARIABLE_FILENAME, abs_path.file_name()),
+            (task::VARIABLE_STEM, abs_path.file_stem()),
+            (task::VARIABLE_EXT, abs_path.extension()),
+        ];
+        for (variable, part) in file_name_parts {
+            variables.insert(
+                variable.to_string(),
+                part.map(|part| part.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            );
+        }
+        variables.insert(
+            task::VARIABLE_FILE.to_string(),
+            abs_path.to_string_lossy().into_owned(),
+        );
+    }
+    variables
+}
+
+/// Whether the active item is an editor with a project file, the file variables are substituted from.
+pub(crate) fn has_active_file(workspace: &Workspace, cx: &AppContext) -> bool {
+    workspace
+        .active_item_as::<Editor>(cx)
+        .map_or(false, |editor| editor.read(cx).project_path(cx).is_some())
+}
+
 fn task_cwd(workspace: &Workspace, cx: &mut WindowContext) -> anyhow::Result<Option<PathBuf>> {
     let project = workspace.project().read(cx);
     let available_worktrees = project
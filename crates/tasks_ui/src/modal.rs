@@ -1,53 +1,275 @@
-use std::sync::Arc;
+use std::{
+    path::Path,
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc,
+    },
+    time::Duration,
+};
 
+use collections::{HashMap, HashSet};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    actions, rems, AppContext, DismissEvent, EventEmitter, FocusableView, InteractiveElement,
-    Model, ParentElement, Render, SharedString, Styled, Subscription, View, ViewContext,
-    VisualContext, WeakView,
+    actions, impl_actions, rems, AnyElement, AppContext, ClipboardItem, DismissEvent, Div,
+    EventEmitter, FocusableView, InteractiveElement, IntoElement, Model, ParentElement,
+    PromptLevel, Render, SharedString, Styled, Subscription, View, ViewContext, VisualContext,
+    WeakView,
 };
 use picker::{Picker, PickerDelegate};
-use project::Inventory;
-use task::{oneshot_source::OneshotSource, Task};
-use ui::{v_flex, HighlightedLabel, ListItem, ListItemSpacing, Selectable, WindowContext};
-use util::ResultExt;
-use workspace::{ModalView, Workspace};
+use project::{
+    group_by_source_kind, usage_blended_score, Inventory, TaskRun, TaskSourceError, TaskSourceKind,
+    WorktreeId,
+};
+use serde::Deserialize;
+use settings::Settings;
+use task::{oneshot_source::OneshotSource, Task, TaskId};
+use ui::{
+    h_flex, v_flex, Color, FluentBuilder, HighlightedLabel, Icon, IconName, IconSize, Label,
+    LabelCommon, LabelSize, ListItem, ListItemSpacing, Selectable, WindowContext,
+};
+use util::{paths::PathExt, NumericPrefixWithSuffix, ResultExt};
+use workspace::{ModalView, Toast, Workspace};
+
+use crate::{
+    resolved_command, schedule_task, schedule_task_in, schedule_tasks, show_no_active_file_toast,
+    task_settings::TaskSettings,
+    worktree_modal::{TaskTarget, WorktreeModal},
+    ScheduleOrigin,
+};
+
+const NO_TASK_DEFINITION_TOAST_ID: usize = 0x7a5c_4e02;
+const COPIED_COMMAND_TOAST_ID: usize = 0x7a5c_4e04;
+const COPIED_COMMAND_TOAST_DURATION: Duration = Duration::from_secs(3);
+/// Multiplier for the scores of the tasks matched by their commands or source labels,
+/// to rank them below the tasks matched by their names.
+const SECONDARY_MATCH_PENALTY: f64 = 0.9;
+/// Scheduling more matching tasks than this at once has to be confirmed first.
+const SPAWN_ALL_CONFIRMATION_THRESHOLD: usize = 5;
+/// Tasks, last run longer ago than this, are listed without the result of their last run.
+const RECENT_RUN_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Opens the tasks modal, optionally with the query given already typed in.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct Spawn {
+    #[serde(default)]
+    pub query: Option<String>,
+}
 
-use crate::schedule_task;
+/// Schedules every task matching the query of the tasks modal.
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct SpawnAllMatches {
+    /// Whether to run the tasks one after another in a single terminal, stopping at the first failed one,
+    /// instead of spawning them all at once.
+    #[serde(default)]
+    pub sequentially: bool,
+}
+
+/// Schedules the task with the exact name given, e.g. from the command palette.
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct SpawnByName {
+    pub task_name: String,
+}
+
+impl_actions!(task, [Spawn, SpawnAllMatches, SpawnByName]);
 
-actions!(task, [Spawn, Rerun]);
+actions!(
+    task,
+    [
+        Rerun,
+        ToggleWorktreeFilter,
+        ForgetTask,
+        ClearOneshotTasks,
+        ClearHistory,
+        TogglePinned,
+        OpenTaskDefinition,
+        SpawnInWorktree,
+        CopyCommand,
+        InsertLineBreak,
+        SpawnWithoutClosing,
+        RunDefaultTask,
+        ToggleDefaultTask,
+        ShowSources,
+        ToggleTaskGroup,
+        ExportTasks,
+        DisableTaskSource,
+        EnableTaskSources,
+        StopWatching,
+        RescanTasks
+    ]
+);
 
 /// A modal used to spawn new tasks.
 pub(crate) struct TasksModalDelegate {
     inventory: Model<Inventory>,
-    candidates: Vec<Arc<dyn Task>>,
+    candidates: Vec<(TaskSourceKind, Arc<dyn Task>)>,
+    /// Fuzzy match candidates for the task names, built along with the candidates and indexed the same way,
+    /// to be reused by every query.
+    match_candidates: Arc<Vec<StringMatchCandidate>>,
+    /// Fuzzy match candidates for the task commands, for the tasks whose commands differ from their names.
+    command_match_candidates: Arc<Vec<StringMatchCandidate>>,
+    /// Fuzzy match candidates for the task names, prefixed with their source labels, e.g. `Makefile build`,
+    /// to tell apart the same named tasks of different sources.
+    source_match_candidates: Arc<Vec<StringMatchCandidate>>,
+    /// Whether the candidates have to be listed from the inventory again on the next query,
+    /// otherwise the queries are matched against the ones listed before.
+    candidates_outdated: bool,
+    source_errors: Vec<TaskSourceError>,
+    loading: bool,
+    /// Number of the registered sources, to tell why there are no tasks to list.
+    source_count: usize,
     matches: Vec<StringMatch>,
+    /// Indices of the matches, starting a new group of tasks from the same source.
+    group_starts: Vec<usize>,
     selected_index: usize,
     workspace: WeakView<Workspace>,
     prompt: String,
+    active_worktree: Option<WorktreeId>,
+    only_active_worktree: bool,
+    /// The task to rerun, marked in the list while nothing is typed into the query.
+    last_scheduled_task: Option<TaskId>,
+    /// The task to run by default in the active worktree, marked in the list.
+    default_task: Option<TaskId>,
+    /// Indices of the candidates, requiring a file open in the active editor, while there is none.
+    /// Those are listed disabled and cannot be confirmed.
+    disabled_candidates: HashSet<usize>,
+    /// The separator of the namespaced task names, to list the tasks of the same namespace together.
+    name_separator: Option<String>,
+    /// Whether the matches are listed as a tree of the task namespaces, which happens while nothing is typed into the query.
+    tree_listed: bool,
+    /// Namespaces, listed as a single row, standing for all of their tasks.
+    collapsed_namespaces: HashSet<String>,
+    /// Numbers of the tasks in the collapsed namespaces, by the indices of the matches listing them.
+    collapsed_rows: HashMap<usize, usize>,
+    /// Whether the most used tasks are listed first, otherwise the tasks are listed alphanumerically.
+    reorder_by_usage: bool,
+    /// Bonuses of the pinned and the recently used candidates, by their indices, added to their match scores.
+    usage_bonuses: Arc<Vec<f64>>,
+    /// How much the usage bonuses are weighted against the match scores.
+    usage_score_weight: f64,
+    /// Maximum number of the tasks matching the query to list.
+    max_matches: usize,
+    /// Number of the tasks matching the query, including the ones beyond [`Self::max_matches`], which are not listed.
+    /// `None` if there may be more tasks matching than listed, as only the best matches are looked for, not counted.
+    total_matches: Option<usize>,
+    /// Incremented on every query, to discard the matches of the outdated ones.
+    latest_query_id: usize,
+    /// Stops fuzzy matching of the previous query, once a new one arrives.
+    cancel_matching: Arc<AtomicBool>,
 }
 
 impl TasksModalDelegate {
-    fn new(inventory: Model<Inventory>, workspace: WeakView<Workspace>) -> Self {
+    fn new(
+        inventory: Model<Inventory>,
+        workspace: WeakView<Workspace>,
+        active_worktree: Option<WorktreeId>,
+        settings: &TaskSettings,
+    ) -> Self {
         Self {
             inventory,
             workspace,
             candidates: Vec::new(),
+            match_candidates: Arc::default(),
+            command_match_candidates: Arc::default(),
+            source_match_candidates: Arc::default(),
+            candidates_outdated: true,
+            source_errors: Vec::new(),
+            loading: false,
+            source_count: 0,
             matches: Vec::new(),
+            group_starts: Vec::new(),
             selected_index: 0,
             prompt: String::default(),
+            active_worktree,
+            only_active_worktree: true,
+            last_scheduled_task: None,
+            default_task: None,
+            disabled_candidates: HashSet::default(),
+            name_separator: settings
+                .name_separator
+                .clone()
+                .filter(|separator| !separator.is_empty()),
+            tree_listed: false,
+            collapsed_namespaces: HashSet::default(),
+            collapsed_rows: HashMap::default(),
+            reorder_by_usage: settings.reorder_by_usage,
+            usage_bonuses: Arc::default(),
+            usage_score_weight: settings.usage_score_weight,
+            max_matches: settings.max_matches,
+            total_matches: Some(0),
+            latest_query_id: 0,
+            cancel_matching: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn group_label(&self, source_kind: &TaskSourceKind, cx: &AppContext) -> String {
+        let TaskSourceKind::Worktree { id, .. } = source_kind else {
+            return source_label(source_kind);
+        };
+        let worktree_name = self.workspace.upgrade().and_then(|workspace| {
+            let worktree = workspace
+                .read(cx)
+                .project()
+                .read(cx)
+                .worktree_for_id(*id, cx)?;
+            Some(worktree.read(cx).root_name().to_string())
+        });
+        match worktree_name {
+            Some(worktree_name) => format!("{worktree_name}: {}", source_kind.label()),
+            None => source_kind.label(),
+        }
+    }
+
+    fn selected_task_id(&self) -> Option<TaskId> {
+        let selected_match = self.matches.get(self.selected_index)?;
+        let (_, task) = self.candidates.get(selected_match.candidate_id)?;
+        Some(task.id().clone())
+    }
+
+    fn worktree_filter(&self) -> Option<WorktreeId> {
+        if self.only_active_worktree {
+            self.active_worktree
+        } else {
+            None
+        }
+    }
+
+    /// The namespace of the task, if the tasks are listed as a tree.
+    fn tree_namespace<'a>(&self, task: &'a dyn Task) -> Option<&'a str> {
+        if !self.tree_listed {
+            return None;
+        }
+        task_namespace(task.name(), self.name_separator.as_deref()?)
+    }
+
+    /// Collapses the namespace of the selected task, or expands it, if collapsed already.
+    /// Returns whether there was a namespace to toggle.
+    fn toggle_selected_namespace(&mut self) -> bool {
+        let Some(selected_match) = self.matches.get(self.selected_index) else {
+            return false;
+        };
+        let (_, task) = &self.candidates[selected_match.candidate_id];
+        let Some(namespace) = self.tree_namespace(task.as_ref()) else {
+            return false;
+        };
+        let namespace = namespace.to_string();
+        if !self.collapsed_namespaces.remove(&namespace) {
+            self.collapsed_namespaces.insert(namespace);
         }
+        true
     }
 
     fn spawn_oneshot(&mut self, cx: &mut AppContext) -> Option<Arc<dyn Task>> {
+        if OneshotSource::parse_command(&self.prompt).is_empty() {
+            return None;
+        }
         self.inventory
             .update(cx, |inventory, _| inventory.source::<OneshotSource>())?
-            .update(cx, |oneshot_source, _| {
+            .update(cx, |oneshot_source, cx| {
                 Some(
                     oneshot_source
                         .as_any()
                         .downcast_mut::<OneshotSource>()?
-                        .spawn(self.prompt.clone()),
+                        .spawn(self.prompt.clone(), cx),
                 )
             })
     }
@@ -55,30 +277,335 @@ impl TasksModalDelegate {
 
 pub(crate) struct TasksModal {
     picker: View<Picker<TasksModalDelegate>>,
-    _subscription: Subscription,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl TasksModal {
     pub(crate) fn new(
         inventory: Model<Inventory>,
         workspace: WeakView<Workspace>,
+        active_worktree: Option<WorktreeId>,
+        initial_query: Option<String>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
-        let picker = cx
-            .new_view(|cx| Picker::uniform_list(TasksModalDelegate::new(inventory, workspace), cx));
-        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
-            cx.emit(DismissEvent);
+        let picker = cx.new_view(|cx| {
+            let delegate = TasksModalDelegate::new(
+                inventory.clone(),
+                workspace,
+                active_worktree,
+                TaskSettings::get_global(cx),
+            );
+            let picker = Picker::list(delegate, cx);
+            // Setting the query edits the picker's editor, which matches the tasks against it right away.
+            if let Some(query) = initial_query.filter(|query| !query.is_empty()) {
+                picker.set_query(query, cx);
+            } else {
+                // The query from the last time is selected, for a new one to be typed over it.
+                let last_query = inventory.read(cx).last_query().to_string();
+                if !last_query.is_empty() {
+                    picker.set_query(last_query, cx);
+                    picker.select_query(cx);
+                }
+            }
+            picker
         });
+        let _subscriptions = vec![
+            cx.subscribe(&picker, |_, _, _, cx| {
+                cx.emit(DismissEvent);
+            }),
+            // Sources may finish loading or change their tasks while the modal is open.
+            cx.observe(&inventory, |modal, _, cx| {
+                modal.picker.update(cx, |picker, cx| {
+                    picker.delegate.candidates_outdated = true;
+                    picker.refresh(cx);
+                });
+            }),
+        ];
         Self {
             picker,
-            _subscription,
+            _subscriptions,
         }
     }
+
+    fn toggle_worktree_filter(&mut self, _: &ToggleWorktreeFilter, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            picker.delegate.only_active_worktree = !picker.delegate.only_active_worktree;
+            picker.delegate.candidates_outdated = true;
+            picker.refresh_placeholder(cx);
+            picker.refresh(cx);
+        });
+    }
+
+    fn toggle_pinned(&mut self, _: &TogglePinned, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            let delegate = &picker.delegate;
+            let Some(current_match) = delegate.matches.get(delegate.selected_index) else {
+                return;
+            };
+            let (_, task) = &delegate.candidates[current_match.candidate_id];
+            let task_id = task.id().clone();
+            delegate.inventory.update(cx, |inventory, cx| {
+                inventory.toggle_pinned(task_id, cx);
+            });
+            picker.delegate.candidates_outdated = true;
+            picker.refresh(cx);
+        });
+    }
+
+    /// Disables the source of the selected task, hiding all of its tasks, until [`EnableTaskSources`] is run.
+    fn disable_task_source(&mut self, _: &DisableTaskSource, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            let delegate = &picker.delegate;
+            let Some(current_match) = delegate.matches.get(delegate.selected_index) else {
+                return;
+            };
+            let (source_kind, _) = &delegate.candidates[current_match.candidate_id];
+            let source_kind = source_kind.clone();
+            delegate.inventory.update(cx, |inventory, cx| {
+                inventory.set_source_enabled(&source_kind, false, cx);
+            });
+            picker.delegate.candidates_outdated = true;
+            picker.refresh(cx);
+        });
+    }
+
+    /// Sets the selected task as the default one of the active worktree, or unsets it, if it is set already.
+    fn toggle_default_task(&mut self, _: &ToggleDefaultTask, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            let delegate = &picker.delegate;
+            let Some(current_match) = delegate.matches.get(delegate.selected_index) else {
+                return;
+            };
+            let (_, task) = &delegate.candidates[current_match.candidate_id];
+            let task_id = task.id().clone();
+            let worktree = delegate.active_worktree;
+            delegate.inventory.update(cx, |inventory, cx| {
+                let new_default =
+                    (!inventory.is_default_task(worktree, &task_id)).then_some(task_id);
+                inventory.set_default_task(worktree, new_default, cx);
+            });
+            picker.delegate.candidates_outdated = true;
+            picker.refresh(cx);
+        });
+    }
+
+    fn open_task_definition(&mut self, _: &OpenTaskDefinition, cx: &mut ViewContext<Self>) {
+        let delegate = &self.picker.read(cx).delegate;
+        let Some(current_match) = delegate.matches.get(delegate.selected_index) else {
+            return;
+        };
+        let (source_kind, task) = &delegate.candidates[current_match.candidate_id];
+        let definition_path = source_kind.abs_path().map(Path::to_path_buf);
+        let task_name = task.name().to_string();
+        let Some(workspace) = delegate.workspace.upgrade() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| match definition_path {
+            Some(definition_path) => {
+                workspace
+                    .open_abs_path(definition_path, true, cx)
+                    .detach_and_log_err(cx);
+            }
+            None => workspace.show_toast(
+                Toast::new(
+                    NO_TASK_DEFINITION_TOAST_ID,
+                    format!("Task {task_name:?} is not defined in a file"),
+                ),
+                cx,
+            ),
+        });
+        cx.emit(DismissEvent);
+    }
+
+    fn spawn_in_worktree(&mut self, _: &SpawnInWorktree, cx: &mut ViewContext<Self>) {
+        let delegate = &self.picker.read(cx).delegate;
+        let Some(current_match) = delegate.matches.get(delegate.selected_index) else {
+            return;
+        };
+        let (source_kind, task) = delegate.candidates[current_match.candidate_id].clone();
+        let inventory = delegate.inventory.clone();
+        let Some(workspace) = delegate.workspace.upgrade() else {
+            return;
+        };
+        let mut targets = workspace.update(cx, |workspace, cx| {
+            TaskTarget::for_task(workspace, &inventory, &source_kind, &task, cx)
+        });
+        if targets.len() > 1 {
+            // The modal cannot be replaced while it handles its own action.
+            let workspace = workspace.downgrade();
+            cx.spawn(|_, mut cx| async move {
+                workspace.update(&mut cx, |workspace, cx| {
+                    let workspace_handle = workspace.weak_handle();
+                    workspace
+                        .toggle_modal(cx, |cx| WorktreeModal::new(targets, workspace_handle, cx));
+                })
+            })
+            .detach_and_log_err(cx);
+            return;
+        }
+
+        workspace.update(cx, |workspace, cx| match targets.pop() {
+            Some(target) => schedule_task_in(
+                workspace,
+                target.source_kind,
+                &target.task,
+                target.cwd,
+                ScheduleOrigin::Modal,
+                cx,
+            ),
+            None => schedule_task(workspace, source_kind, &task, ScheduleOrigin::Modal, cx),
+        });
+        cx.emit(DismissEvent);
+    }
+
+    fn copy_command(&mut self, _: &CopyCommand, cx: &mut ViewContext<Self>) {
+        let delegate = &self.picker.read(cx).delegate;
+        let Some(current_match) = delegate.matches.get(delegate.selected_index) else {
+            return;
+        };
+        let (source_kind, task) = delegate.candidates[current_match.candidate_id].clone();
+        let Some(workspace) = delegate.workspace.upgrade() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            let Some(command) = resolved_command(workspace, &source_kind, task.as_ref(), cx) else {
+                return;
+            };
+            cx.write_to_clipboard(ClipboardItem::new(command));
+            workspace.show_toast(
+                Toast::new(
+                    COPIED_COMMAND_TOAST_ID,
+                    format!("Copied the command of task {:?}", task.name()),
+                ),
+                cx,
+            );
+            cx.spawn(|workspace, mut cx| async move {
+                cx.background_executor()
+                    .timer(COPIED_COMMAND_TOAST_DURATION)
+                    .await;
+                workspace.update(&mut cx, |workspace, cx| {
+                    workspace.dismiss_toast(COPIED_COMMAND_TOAST_ID, cx)
+                })
+            })
+            .detach_and_log_err(cx);
+        });
+        cx.emit(DismissEvent);
+    }
+
+    /// Schedules the selected task, keeping the modal open with its query, to run more tasks right after.
+    fn spawn_without_closing(&mut self, _: &SpawnWithoutClosing, cx: &mut ViewContext<Self>) {
+        let delegate = &self.picker.read(cx).delegate;
+        let Some(current_match) = delegate.matches.get(delegate.selected_index) else {
+            return;
+        };
+        let (source_kind, task) = delegate.candidates[current_match.candidate_id].clone();
+        let Some(workspace) = delegate.workspace.upgrade() else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            schedule_task(workspace, source_kind, &task, ScheduleOrigin::Modal, cx);
+        });
+        // Spawned tasks focus the terminal panel, take the focus back to keep working with the query.
+        cx.spawn(|modal, mut cx| async move {
+            modal.update(&mut cx, |modal, cx| cx.focus_view(&modal.picker))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn toggle_task_group(&mut self, _: &ToggleTaskGroup, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            if picker.delegate.toggle_selected_namespace() {
+                picker.refresh(cx);
+            }
+        });
+    }
+
+    /// Schedules every task matching the query, asking for a confirmation first if there are many of them.
+    fn spawn_all_matches(&mut self, action: &SpawnAllMatches, cx: &mut ViewContext<Self>) {
+        let delegate = &self.picker.read(cx).delegate;
+        let tasks = delegate
+            .matches
+            .iter()
+            .enumerate()
+            // Collapsed namespaces are not run, as their tasks are not listed.
+            .filter(|(ix, string_match)| {
+                !delegate
+                    .disabled_candidates
+                    .contains(&string_match.candidate_id)
+                    && !delegate.collapsed_rows.contains_key(ix)
+            })
+            .map(|(_, string_match)| delegate.candidates[string_match.candidate_id].clone())
+            .collect::<Vec<_>>();
+        if tasks.is_empty() {
+            return;
+        }
+        let Some(workspace) = delegate.workspace.upgrade() else {
+            return;
+        };
+        let sequentially = action.sequentially;
+        let confirmation = (tasks.len() > SPAWN_ALL_CONFIRMATION_THRESHOLD).then(|| {
+            cx.prompt(
+                PromptLevel::Warning,
+                &format!("Run all {} matching tasks?", tasks.len()),
+                None,
+                &["Run All", "Cancel"],
+            )
+        });
+        cx.spawn(|modal, mut cx| async move {
+            if let Some(confirmation) = confirmation {
+                if !matches!(confirmation.await, Ok(0)) {
+                    return Ok(());
+                }
+            }
+            workspace.update(&mut cx, |workspace, cx| {
+                schedule_tasks(workspace, tasks, sequentially, ScheduleOrigin::Modal, cx)
+            })?;
+            modal.update(&mut cx, |_, cx| cx.emit(DismissEvent))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Starts a new line of the query, to spawn a multi-line script as a oneshot task.
+    fn insert_line_break(&mut self, _: &InsertLineBreak, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            let query = picker.query(cx);
+            picker.set_query(format!("{query}\n"), cx);
+        });
+    }
+
+    fn forget_task(&mut self, _: &ForgetTask, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            let delegate = &picker.delegate;
+            let Some(current_match) = delegate.matches.get(delegate.selected_index) else {
+                return;
+            };
+            let (_, task) = &delegate.candidates[current_match.candidate_id];
+            let task_id = task.id().clone();
+            delegate.inventory.update(cx, |inventory, cx| {
+                inventory.forget_task(&task_id, cx);
+            });
+            picker.delegate.candidates_outdated = true;
+            picker.refresh(cx);
+        });
+    }
 }
 
 impl Render for TasksModal {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
         v_flex()
+            .key_context("TasksModal")
+            .on_action(cx.listener(Self::toggle_worktree_filter))
+            .on_action(cx.listener(Self::forget_task))
+            .on_action(cx.listener(Self::toggle_pinned))
+            .on_action(cx.listener(Self::toggle_default_task))
+            .on_action(cx.listener(Self::open_task_definition))
+            .on_action(cx.listener(Self::spawn_in_worktree))
+            .on_action(cx.listener(Self::copy_command))
+            .on_action(cx.listener(Self::insert_line_break))
+            .on_action(cx.listener(Self::spawn_without_closing))
+            .on_action(cx.listener(Self::spawn_all_matches))
+            .on_action(cx.listener(Self::toggle_task_group))
+            .on_action(cx.listener(Self::disable_task_source))
             .w(rems(34.))
             .child(self.picker.clone())
             .on_mouse_down_out(cx.listener(|modal, _, cx| {
@@ -100,12 +627,20 @@ impl FocusableView for TasksModal {
 impl ModalView for TasksModal {}
 
 impl PickerDelegate for TasksModalDelegate {
-    type ListItem = ListItem;
+    type ListItem = Div;
 
     fn match_count(&self) -> usize {
         self.matches.len()
     }
 
+    fn separators_after_indices(&self) -> Vec<usize> {
+        self.group_starts
+            .iter()
+            .skip(1)
+            .map(|group_start| group_start - 1)
+            .collect()
+    }
+
     fn selected_index(&self) -> usize {
         self.selected_index
     }
@@ -115,8 +650,21 @@ impl PickerDelegate for TasksModalDelegate {
     }
 
     fn placeholder_text(&self, cx: &mut WindowContext) -> Arc<str> {
+        let mode = if self.active_worktree.is_none() {
+            String::new()
+        } else if self.only_active_worktree {
+            format!(
+                "Worktree tasks ({} shows all), ",
+                cx.keystroke_text_for(&ToggleWorktreeFilter)
+            )
+        } else {
+            format!(
+                "All tasks ({} shows worktree only), ",
+                cx.keystroke_text_for(&ToggleWorktreeFilter)
+            )
+        };
         Arc::from(format!(
-            "{} runs the selected task, {} spawns a bash-like task from the prompt",
+            "{mode}{} runs the selected task, {} spawns a bash-like task from the prompt",
             cx.keystroke_text_for(&menu::Confirm),
             cx.keystroke_text_for(&menu::SecondaryConfirm),
         ))
@@ -127,45 +675,283 @@ impl PickerDelegate for TasksModalDelegate {
         query: String,
         cx: &mut ViewContext<picker::Picker<Self>>,
     ) -> gpui::Task<()> {
+        self.latest_query_id += 1;
+        let query_id = self.latest_query_id;
+        self.cancel_matching.store(true, atomic::Ordering::Release);
+        self.cancel_matching = Arc::new(AtomicBool::new(false));
+        let cancel_matching = self.cancel_matching.clone();
+        let max_matches = self.max_matches;
+        let usage_score_weight = self.usage_score_weight;
         cx.spawn(move |picker, mut cx| async move {
-            let Some(candidates) = picker
-                .update(&mut cx, |picker, cx| {
-                    picker.delegate.candidates = picker
-                        .delegate
-                        .inventory
-                        .update(cx, |inventory, cx| inventory.list_tasks(None, true, cx));
-                    picker
-                        .delegate
-                        .candidates
-                        .iter()
-                        .enumerate()
-                        .map(|(index, candidate)| StringMatchCandidate {
-                            id: index,
-                            char_bag: candidate.name().chars().collect(),
-                            string: candidate.name().into(),
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .ok()
+            let Some(((candidates, command_candidates, source_candidates), selected_task_id)) =
+                picker
+                    .update(&mut cx, |picker, cx| {
+                        let (tags, _) = split_tag_filters(&query);
+                        let selected_task_id = picker.delegate.selected_task_id();
+                        if picker.delegate.candidates_outdated {
+                            let worktree = picker.delegate.worktree_filter();
+                            let lru = picker.delegate.reorder_by_usage;
+                            let has_active_file = picker
+                                .delegate
+                                .workspace
+                                .upgrade()
+                                .map_or(false, |workspace| {
+                                    crate::has_active_file(workspace.read(cx), cx)
+                                });
+                            picker.delegate.candidates =
+                                picker.delegate.inventory.update(cx, |inventory, cx| {
+                                    inventory.list_tasks(None, worktree, lru, false, cx)
+                                });
+                            picker.delegate.disabled_candidates = if has_active_file {
+                                HashSet::default()
+                            } else {
+                                picker
+                                    .delegate
+                                    .candidates
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(_, (_, task))| task.requires_file())
+                                    .map(|(index, _)| index)
+                                    .collect()
+                            };
+                            picker.delegate.match_candidates = Arc::new(
+                                picker
+                                    .delegate
+                                    .candidates
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(index, (_, candidate))| StringMatchCandidate {
+                                        id: index,
+                                        char_bag: candidate.name().chars().collect(),
+                                        string: candidate.name().into(),
+                                    })
+                                    .collect(),
+                            );
+                            picker.delegate.command_match_candidates = Arc::new(
+                                picker
+                                    .delegate
+                                    .candidates
+                                    .iter()
+                                    .enumerate()
+                                    .filter_map(|(index, (_, candidate))| {
+                                        let command = candidate.command()?;
+                                        (command != candidate.name()).then(|| {
+                                            StringMatchCandidate {
+                                                id: index,
+                                                char_bag: command.chars().collect(),
+                                                string: command,
+                                            }
+                                        })
+                                    })
+                                    .collect(),
+                            );
+                            picker.delegate.source_match_candidates = Arc::new(
+                                picker
+                                    .delegate
+                                    .candidates
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(index, (source_kind, candidate))| {
+                                        let string =
+                                            source_qualified_name(source_kind, candidate.name());
+                                        StringMatchCandidate {
+                                            id: index,
+                                            char_bag: string.chars().collect(),
+                                            string,
+                                        }
+                                    })
+                                    .collect(),
+                            );
+                            let usage_bonuses = picker
+                                .delegate
+                                .inventory
+                                .read(cx)
+                                .usage_bonuses(picker.delegate.reorder_by_usage);
+                            picker.delegate.usage_bonuses = Arc::new(
+                                picker
+                                    .delegate
+                                    .candidates
+                                    .iter()
+                                    .map(|(_, candidate)| {
+                                        usage_bonuses
+                                            .get(candidate.id())
+                                            .copied()
+                                            .unwrap_or_default()
+                                    })
+                                    .collect(),
+                            );
+                            picker.delegate.last_scheduled_task =
+                                picker.delegate.inventory.update(cx, |inventory, cx| {
+                                    let (_, task) = inventory.last_scheduled_task(cx)?;
+                                    Some(task.id().clone())
+                                });
+                            let active_worktree = picker.delegate.active_worktree;
+                            picker.delegate.default_task =
+                                picker.delegate.inventory.update(cx, |inventory, cx| {
+                                    let (_, task) = inventory.default_task(active_worktree, cx)?;
+                                    Some(task.id().clone())
+                                });
+                            let inventory = picker.delegate.inventory.read(cx);
+                            picker.delegate.source_errors = inventory.source_errors(cx);
+                            picker.delegate.loading = inventory.is_loading(cx);
+                            picker.delegate.source_count = inventory.source_count();
+                            picker.delegate.candidates_outdated = false;
+                        }
+                        let delegate = &picker.delegate;
+                        let filter_by_tags = |match_candidates: &Arc<Vec<StringMatchCandidate>>| {
+                            if tags.is_empty() {
+                                return match_candidates.clone();
+                            }
+                            Arc::new(
+                                match_candidates
+                                    .iter()
+                                    .filter(|match_candidate| {
+                                        let (_, candidate) =
+                                            &delegate.candidates[match_candidate.id];
+                                        let candidate_tags = candidate.tags();
+                                        tags.iter().all(|tag| {
+                                            candidate_tags
+                                                .iter()
+                                                .any(|candidate_tag| candidate_tag == tag)
+                                        })
+                                    })
+                                    .cloned()
+                                    .collect(),
+                            )
+                        };
+                        let candidates = (
+                            filter_by_tags(&delegate.match_candidates),
+                            filter_by_tags(&delegate.command_match_candidates),
+                            filter_by_tags(&delegate.source_match_candidates),
+                        );
+                        (candidates, selected_task_id)
+                    })
+                    .ok()
             else {
                 return;
             };
-            let matches = fuzzy::match_strings(
-                &candidates,
-                &query,
-                true,
-                1000,
-                &Default::default(),
-                cx.background_executor().clone(),
-            )
-            .await;
+            let (_, name_query) = split_tag_filters(&query);
+            let numeric_query = numeric_query(name_query);
+            let usage_bonuses = picker
+                .update(&mut cx, |picker, _| picker.delegate.usage_bonuses.clone())
+                .unwrap_or_default();
+            // Only the best matches are looked for, so the matches beyond the limit are not counted, unless there is no query:
+            // then every task matches by its name already, in the order of the candidates.
+            let (matches, total_matches) = if name_query.is_empty() {
+                let matches = candidates
+                    .iter()
+                    .take(max_matches)
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        score: 0.,
+                        positions: Vec::new(),
+                        string: candidate.string.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                (matches, Some(candidates.len()))
+            } else {
+                let name_matches = fuzzy::match_strings(
+                    &candidates,
+                    name_query,
+                    true,
+                    max_matches,
+                    &cancel_matching,
+                    cx.background_executor().clone(),
+                )
+                .await;
+                let mut secondary_matches = Vec::new();
+                for secondary_candidates in [&command_candidates, &source_candidates] {
+                    secondary_matches.extend(
+                        fuzzy::match_strings(
+                            secondary_candidates,
+                            name_query,
+                            true,
+                            max_matches,
+                            &cancel_matching,
+                            cx.background_executor().clone(),
+                        )
+                        .await,
+                    );
+                }
+                let mut matches =
+                    merge_secondary_matches(name_matches, secondary_matches, max_matches);
+                for string_match in &mut matches {
+                    let usage_bonus = usage_bonuses
+                        .get(string_match.candidate_id)
+                        .copied()
+                        .unwrap_or_default();
+                    string_match.score =
+                        usage_blended_score(string_match.score, usage_bonus, usage_score_weight);
+                }
+                matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+                let total_matches = (matches.len() < max_matches).then_some(matches.len());
+                (matches, total_matches)
+            };
+            // Queries are matched against the full task names, so the matches are listed flat.
+            let tree_listed = name_query.is_empty();
             picker
-                .update(&mut cx, |picker, _| {
+                .update(&mut cx, |picker, cx| {
+                    // Candidates may have been replaced by a newer query already, so its matches are the only ones to apply.
+                    if picker.delegate.latest_query_id != query_id {
+                        return;
+                    }
+                    picker.delegate.inventory.update(cx, |inventory, _| {
+                        inventory.set_last_query(query.clone());
+                    });
                     let delegate = &mut picker.delegate;
-                    delegate.matches = matches;
+                    let groups = group_by_source_kind(matches.into_iter().map(|string_match| {
+                        let (source_kind, _) = &delegate.candidates[string_match.candidate_id];
+                        (source_kind.clone(), string_match)
+                    }));
+                    delegate.matches.clear();
+                    delegate.group_starts.clear();
+                    delegate.collapsed_rows.clear();
+                    delegate.tree_listed = tree_listed && delegate.name_separator.is_some();
+                    for (_, group_matches) in groups {
+                        delegate.group_starts.push(delegate.matches.len());
+                        if !delegate.tree_listed {
+                            delegate.matches.extend(group_matches);
+                            continue;
+                        }
+                        let rows = tree_matches(
+                            group_matches,
+                            |string_match| {
+                                let (_, task) = &delegate.candidates[string_match.candidate_id];
+                                delegate.tree_namespace(task.as_ref())
+                            },
+                            &delegate.collapsed_namespaces,
+                        );
+                        for (string_match, collapsed_tasks) in rows {
+                            if let Some(collapsed_tasks) = collapsed_tasks {
+                                delegate
+                                    .collapsed_rows
+                                    .insert(delegate.matches.len(), collapsed_tasks);
+                            }
+                            delegate.matches.push(string_match);
+                        }
+                    }
                     delegate.prompt = query;
+                    delegate.total_matches = total_matches;
 
-                    if delegate.matches.is_empty() {
+                    // A number alone selects the task with such numeric prefix, as an item of a numbered menu.
+                    let numbered_index = numeric_query.and_then(|number| {
+                        delegate.matches.iter().position(|string_match| {
+                            let (_, task) = &delegate.candidates[string_match.candidate_id];
+                            NumericPrefixWithSuffix::from_numeric_prefixed_str(task.name())
+                                .is_some_and(|name| name.prefix() == number)
+                        })
+                    });
+                    // Keep the same task selected while it matches the query, regardless of its new position.
+                    let previously_selected_index = selected_task_id.and_then(|task_id| {
+                        delegate.matches.iter().position(|string_match| {
+                            delegate.candidates[string_match.candidate_id].1.id() == &task_id
+                        })
+                    });
+                    if let Some(numbered_index) = numbered_index {
+                        delegate.selected_index = numbered_index;
+                    } else if let Some(previously_selected_index) = previously_selected_index {
+                        delegate.selected_index = previously_selected_index;
+                    } else if delegate.matches.is_empty() {
                         delegate.selected_index = 0;
                     } else {
                         delegate.selected_index =
@@ -182,6 +968,7 @@ impl PickerDelegate for TasksModalDelegate {
         let task = if secondary {
             if !self.prompt.trim().is_empty() {
                 self.spawn_oneshot(cx)
+                    .map(|task| (TaskSourceKind::UserInput, task))
             } else {
                 None
             }
@@ -192,13 +979,32 @@ impl PickerDelegate for TasksModalDelegate {
             })
         };
 
-        let Some(task) = task else {
+        let Some((source_kind, task)) = task else {
             return;
         };
+        // Confirming a collapsed namespace expands it, as it stands for several tasks.
+        if !secondary && self.collapsed_rows.contains_key(&current_match_index) {
+            if self.toggle_selected_namespace() {
+                cx.defer(|picker, cx| picker.refresh(cx));
+            }
+            return;
+        }
+        if !secondary
+            && self
+                .disabled_candidates
+                .contains(&self.matches[current_match_index].candidate_id)
+        {
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    show_no_active_file_toast(workspace, task.as_ref(), task.name(), cx);
+                })
+                .ok();
+            return;
+        }
 
         self.workspace
             .update(cx, |workspace, cx| {
-                schedule_task(workspace, task.as_ref(), cx);
+                schedule_task(workspace, source_kind, &task, ScheduleOrigin::Modal, cx);
             })
             .ok();
         cx.emit(DismissEvent);
@@ -208,20 +1014,741 @@ impl PickerDelegate for TasksModalDelegate {
         cx.emit(DismissEvent);
     }
 
+    fn render_header(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if self.source_errors.is_empty() && !self.loading {
+            return None;
+        }
+        Some(
+            v_flex()
+                .px_3()
+                .py_1()
+                .gap_1()
+                .when(self.loading, |header| {
+                    header.child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Icon::new(IconName::ArrowCircle)
+                                    .size(IconSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(
+                                Label::new("Loading tasks…")
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                })
+                .children(self.source_errors.iter().map(|error| {
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Icon::new(IconName::ExclamationTriangle)
+                                .size(IconSize::Small)
+                                .color(Color::Warning),
+                        )
+                        .child(
+                            Label::new(format!("{}: {}", error.kind.label(), error.message))
+                                .size(LabelSize::Small)
+                                .color(Color::Warning),
+                        )
+                }))
+                .into_any_element(),
+        )
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        if self.loading {
+            "Loading tasks…".into()
+        } else if self.source_count == 0 {
+            "No task sources: add a `.zed/tasks.json` file to define the tasks".into()
+        } else if self.candidates.is_empty() {
+            format!(
+                "No tasks defined in {} task source{}",
+                self.source_count,
+                if self.source_count == 1 { "" } else { "s" }
+            )
+            .into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn render_footer(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        // Tell the tasks beyond the limit from the missing ones.
+        let label = match self.total_matches {
+            Some(total_matches) if total_matches <= self.max_matches => return None,
+            Some(total_matches) => {
+                format!("Showing top {} of {total_matches} tasks", self.max_matches)
+            }
+            None => format!("Showing top {} matching tasks", self.max_matches),
+        };
+        Some(
+            h_flex()
+                .px_3()
+                .py_1()
+                .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+                .into_any_element(),
+        )
+    }
+
     fn render_match(
         &self,
         ix: usize,
         selected: bool,
-        _cx: &mut ViewContext<picker::Picker<Self>>,
+        cx: &mut ViewContext<picker::Picker<Self>>,
     ) -> Option<Self::ListItem> {
         let hit = &self.matches[ix];
         let highlights: Vec<_> = hit.positions.iter().copied().collect();
+        let (source_kind, task) = &self.candidates[hit.candidate_id];
+        let collapsed_tasks = self.collapsed_rows.get(&ix).copied();
+        // Collapsed namespaces stand for all of their tasks, so the state of their first task is not shown.
+        let task_row = collapsed_tasks.is_none();
+        let disabled = task_row && self.disabled_candidates.contains(&hit.candidate_id);
+        let name_color = if disabled {
+            Color::Disabled
+        } else {
+            Color::Default
+        };
+        let namespace = self.tree_namespace(task.as_ref());
+        // Expanded namespaces are headed by their first task.
+        let namespace_header = namespace.filter(|&namespace| {
+            task_row
+                && (self.group_starts.contains(&ix)
+                    || ix.checked_sub(1).map_or(true, |previous_ix| {
+                        let (_, previous_task) =
+                            &self.candidates[self.matches[previous_ix].candidate_id];
+                        self.tree_namespace(previous_task.as_ref()) != Some(namespace)
+                    }))
+        });
+        let source_label = source_label(source_kind);
+        let mut source_highlights = Vec::new();
+        // Tasks matched by their commands show the command below the name, highlighting the matched part of the command only.
+        // Tasks matched by their source labels highlight the matched parts of both the name and the source label.
+        let task_label = if let Some(namespace) = namespace {
+            // Nothing is typed into the query while the tasks are listed as a tree, so there is nothing to highlight.
+            let name = match collapsed_tasks {
+                Some(_) => namespace,
+                None => &task.name()[namespace.len()..],
+            };
+            let name = self
+                .name_separator
+                .as_deref()
+                .and_then(|separator| name.strip_prefix(separator))
+                .unwrap_or(name);
+            v_flex().child(Label::new(name.to_string()).color(name_color))
+        } else if hit.string == task.name() {
+            v_flex().child(HighlightedLabel::new(hit.string.clone(), highlights).color(name_color))
+        } else if hit.string == source_qualified_name(source_kind, task.name()) {
+            let name_start = source_label.len() + 1;
+            let (name_highlights, label_highlights): (Vec<_>, Vec<_>) = highlights
+                .into_iter()
+                .partition(|&position| position >= name_start);
+            source_highlights = label_highlights;
+            v_flex().child(
+                HighlightedLabel::new(
+                    task.name().to_string(),
+                    name_highlights
+                        .into_iter()
+                        .map(|position| position - name_start)
+                        .collect(),
+                )
+                .color(name_color),
+            )
+        } else {
+            v_flex()
+                .child(Label::new(task.name().to_string()).color(name_color))
+                .child(
+                    HighlightedLabel::new(hit.string.clone(), highlights)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+        };
+        let inventory = self.inventory.read(cx);
+        let pinned = task_row && inventory.is_pinned(task.id());
+        let default = task_row && self.default_task.as_ref() == Some(task.id());
+        let running = task_row && inventory.is_running(task.id());
+        let watched = task_row && inventory.is_watched(task.id());
+        let last_scheduled = task_row
+            && self.prompt.is_empty()
+            && self.last_scheduled_task.as_ref() == Some(task.id());
+        let failed = task_row
+            && !running
+            && inventory
+                .last_exit_status(task.id())
+                .is_some_and(|exit_status| !exit_status.is_success());
+        let recent_run = if running || !task_row {
+            None
+        } else {
+            inventory
+                .last_run(task.id())
+                .and_then(|run| recent_run_label(&run))
+        };
+        let task_label = task_label.when_some(recent_run, |task_label, recent_run| {
+            task_label.child(
+                Label::new(recent_run)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+        });
+        let group_header = self
+            .group_starts
+            .contains(&ix)
+            .then(|| self.group_label(source_kind, cx));
         Some(
-            ListItem::new(SharedString::from(format!("tasks-modal-{ix}")))
-                .inset(true)
-                .spacing(ListItemSpacing::Sparse)
-                .selected(selected)
-                .start_slot(HighlightedLabel::new(hit.string.clone(), highlights)),
+            v_flex()
+                .children(group_header.map(|group_header| {
+                    h_flex().px_3().pt_1().child(
+                        Label::new(group_header)
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    )
+                }))
+                .children(namespace_header.map(|namespace_header| {
+                    h_flex()
+                        .px_3()
+                        .pt_1()
+                        .gap_1()
+                        .child(
+                            Icon::new(IconName::ChevronDown)
+                                .size(IconSize::XSmall)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            Label::new(namespace_header.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                }))
+                .child(
+                    ListItem::new(SharedString::from(format!("tasks-modal-{ix}")))
+                        .inset(true)
+                        .spacing(ListItemSpacing::Sparse)
+                        .selected(selected)
+                        .when_some(collapsed_tasks, |list_item, _| list_item.toggle(false))
+                        .when(namespace.is_some() && task_row, |list_item| {
+                            list_item.indent_level(1)
+                        })
+                        .start_slot(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Icon::new(task_icon(task.as_ref(), source_kind))
+                                        .size(IconSize::Small)
+                                        .color(Color::Muted),
+                                )
+                                .child(task_label),
+                        )
+                        .end_slot(
+                            h_flex()
+                                .gap_2()
+                                .when_some(collapsed_tasks, |end_slot, collapsed_tasks| {
+                                    end_slot.child(
+                                        Label::new(format!("{collapsed_tasks} tasks"))
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                })
+                                .when(disabled, |end_slot| {
+                                    end_slot.child(
+                                        Label::new("Needs File")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Disabled),
+                                    )
+                                })
+                                .when(last_scheduled, |end_slot| {
+                                    end_slot.child(
+                                        Label::new("Last Run")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Info),
+                                    )
+                                })
+                                .when(running, |end_slot| {
+                                    end_slot.child(
+                                        Label::new("Running")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Success),
+                                    )
+                                })
+                                .when(watched, |end_slot| {
+                                    end_slot.child(
+                                        Label::new("Watching")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Info),
+                                    )
+                                })
+                                .when(failed, |end_slot| {
+                                    end_slot.child(
+                                        Label::new("Failed")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Error),
+                                    )
+                                })
+                                .when(default, |end_slot| {
+                                    end_slot.child(
+                                        Label::new("Default")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Accent),
+                                    )
+                                })
+                                .when(pinned, |end_slot| {
+                                    end_slot.child(
+                                        Label::new("Pinned")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Accent),
+                                    )
+                                })
+                                .child(
+                                    HighlightedLabel::new(source_label, source_highlights)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                        ),
+                ),
         )
     }
 }
+
+/// Adds the command and source label matches to the name matches, with the lowered scores, for the tasks not matched by their names.
+/// Tasks matched in several ways are listed once, with their best secondary match.
+/// Only the best matches, up to the limit given, are kept.
+fn merge_secondary_matches(
+    name_matches: Vec<StringMatch>,
+    mut secondary_matches: Vec<StringMatch>,
+    limit: usize,
+) -> Vec<StringMatch> {
+    let mut matched = name_matches
+        .iter()
+        .map(|name_match| name_match.candidate_id)
+        .collect::<HashSet<_>>();
+    secondary_matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut matches = name_matches;
+    matches.extend(
+        secondary_matches
+            .into_iter()
+            .filter(|secondary_match| matched.insert(secondary_match.candidate_id))
+            .map(|secondary_match| StringMatch {
+                score: secondary_match.score * SECONDARY_MATCH_PENALTY,
+                ..secondary_match
+            }),
+    );
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(limit);
+    matches
+}
+
+/// The namespace of the task name, e.g. `test` for `test:unit` with the `:` separator.
+/// Names without the separator, or with nothing before or after it, have no namespace.
+fn task_namespace<'a>(task_name: &'a str, separator: &str) -> Option<&'a str> {
+    let (namespace, rest) = task_name.split_once(separator)?;
+    (!namespace.is_empty() && !rest.is_empty()).then_some(namespace)
+}
+
+/// Lists the matches of the same namespace together, at the position of the best of them.
+/// Collapsed namespaces are listed by their best match only, along with the number of the matches it stands for.
+fn tree_matches<'a>(
+    matches: Vec<StringMatch>,
+    namespace: impl Fn(&StringMatch) -> Option<&'a str>,
+    collapsed_namespaces: &HashSet<String>,
+) -> Vec<(StringMatch, Option<usize>)> {
+    let mut namespaces = Vec::<(Option<&'a str>, Vec<StringMatch>)>::new();
+    for string_match in matches {
+        let match_namespace = namespace(&string_match);
+        let known_namespace = match_namespace.and_then(|match_namespace| {
+            namespaces
+                .iter_mut()
+                .find(|(known_namespace, _)| *known_namespace == Some(match_namespace))
+        });
+        match known_namespace {
+            Some((_, namespace_matches)) => namespace_matches.push(string_match),
+            None => namespaces.push((match_namespace, vec![string_match])),
+        }
+    }
+    namespaces
+        .into_iter()
+        .flat_map(|(namespace, mut namespace_matches)| {
+            let collapsed =
+                namespace.is_some_and(|namespace| collapsed_namespaces.contains(namespace));
+            if collapsed {
+                let collapsed_tasks = namespace_matches.len();
+                namespace_matches.truncate(1);
+                namespace_matches
+                    .into_iter()
+                    .map(|string_match| (string_match, Some(collapsed_tasks)))
+                    .collect::<Vec<_>>()
+            } else {
+                namespace_matches
+                    .into_iter()
+                    .map(|string_match| (string_match, None))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Describes the last run of the task, e.g. `passed 2m ago`, if it has finished recently enough to be of interest.
+fn recent_run_label(run: &TaskRun) -> Option<String> {
+    let elapsed = run.finished_at().elapsed().ok()?;
+    if elapsed > RECENT_RUN_MAX_AGE {
+        return None;
+    }
+    let outcome = if run.exit_status.is_success() {
+        "passed"
+    } else {
+        "failed"
+    };
+    let seconds = elapsed.as_secs();
+    let elapsed = match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m", seconds / 60),
+        _ => format!("{}h", seconds / 3600),
+    };
+    Some(format!("{outcome} {elapsed} ago"))
+}
+
+/// The task name, prefixed with its source label, to match the tasks by their sources too.
+fn source_qualified_name(source_kind: &TaskSourceKind, task_name: &str) -> String {
+    format!("{} {task_name}", source_label(source_kind))
+}
+
+/// The icon named by the task, or the default icon of its source kind, if the task names none or an unknown one.
+fn task_icon(task: &dyn Task, source_kind: &TaskSourceKind) -> IconName {
+    let named_icon = task.icon().and_then(|icon| match icon.as_str() {
+        "build" | "bolt" => Some(IconName::Bolt),
+        "test" | "check" => Some(IconName::Check),
+        "run" | "play" => Some(IconName::Play),
+        "terminal" => Some(IconName::Terminal),
+        "generate" | "magic_wand" => Some(IconName::MagicWand),
+        "update" => Some(IconName::Update),
+        "search" => Some(IconName::MagnifyingGlass),
+        "filter" => Some(IconName::Filter),
+        "file" => Some(IconName::File),
+        "folder" => Some(IconName::Folder),
+        "rust" => Some(IconName::FileRust),
+        "git" => Some(IconName::FileGit),
+        "github" => Some(IconName::Github),
+        "docs" => Some(IconName::FileDoc),
+        _ => None,
+    });
+    named_icon.unwrap_or(match source_kind {
+        TaskSourceKind::UserInput => IconName::Terminal,
+        TaskSourceKind::Global(_) => IconName::Public,
+        TaskSourceKind::AbsPath(_) => IconName::File,
+        TaskSourceKind::Worktree { .. } => IconName::FileTree,
+    })
+}
+
+/// The source label, prefixed with the directory of the task definitions for the sources outside of the worktrees.
+fn source_label(source_kind: &TaskSourceKind) -> String {
+    match source_kind.standalone_directory() {
+        Some(directory) => format!(
+            "{}: {}",
+            directory.compact().to_string_lossy(),
+            source_kind.label()
+        ),
+        None => source_kind.label(),
+    }
+}
+
+/// Splits the leading `#tag` tokens off the query, returning the tags and the rest of the query.
+fn split_tag_filters(query: &str) -> (Vec<&str>, &str) {
+    let mut tags = Vec::new();
+    let mut rest = query.trim_start();
+    while let Some(tagged) = rest.strip_prefix('#') {
+        let tag_end = tagged.find(char::is_whitespace).unwrap_or(tagged.len());
+        let (tag, after_tag) = tagged.split_at(tag_end);
+        if !tag.is_empty() {
+            tags.push(tag);
+        }
+        rest = after_tag.trim_start();
+    }
+    (tags, rest)
+}
+
+/// The number the query consists of, if it has nothing but digits, to select the task with such numeric prefix.
+fn numeric_query(query: &str) -> Option<i32> {
+    let query = query.trim();
+    if query.is_empty() || !query.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    query.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::SystemTime};
+
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use task::TaskExitStatus;
+    use workspace::AppState;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_keeping_selected_task_across_queries(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let inventory = project.update(cx, |project, _| project.task_inventory().clone());
+        let oneshot_source = cx.update(|cx| OneshotSource::new(cx));
+        oneshot_source.update(cx, |oneshot_source, cx| {
+            oneshot_source
+                .as_any()
+                .downcast_mut::<OneshotSource>()
+                .unwrap()
+                .restore(
+                    vec![
+                        "cargo build".to_string(),
+                        "cargo build --release".to_string(),
+                        "cargo clippy".to_string(),
+                    ],
+                    cx,
+                );
+        });
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(TaskSourceKind::UserInput, oneshot_source, cx);
+        });
+
+        let modal = cx.new_view(|cx| {
+            TasksModal::new(inventory.clone(), workspace.downgrade(), None, None, cx)
+        });
+        let picker = modal.update(cx, |modal, _| modal.picker.clone());
+        cx.run_until_parked();
+        let release_task_id = TaskId("cargo build --release".to_string());
+        picker.update(cx, |picker, cx| {
+            let release_task_ix = picker
+                .delegate
+                .matches
+                .iter()
+                .position(|string_match| {
+                    picker.delegate.candidates[string_match.candidate_id].1.id() == &release_task_id
+                })
+                .expect("All tasks should be listed for the empty query");
+            picker.delegate.set_selected_index(release_task_ix, cx);
+        });
+
+        set_query("build", &picker, cx);
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.matches.len(), 2);
+            assert_eq!(
+                picker.delegate.selected_task_id(),
+                Some(release_task_id.clone()),
+                "The selected task should stay selected while it matches the query"
+            );
+        });
+        let previously_selected_index =
+            picker.update(cx, |picker, _| picker.delegate.selected_index);
+
+        set_query("clippy", &picker, cx);
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.matches.len(), 1);
+            assert_eq!(
+                picker.delegate.selected_index,
+                previously_selected_index.min(picker.delegate.matches.len() - 1),
+                "The selection should be clamped to the matches once the selected task is filtered out"
+            );
+            assert_eq!(
+                picker.delegate.selected_task_id(),
+                Some(TaskId("cargo clippy".to_string()))
+            );
+        });
+    }
+
+    #[test]
+    fn test_splitting_tag_filters() {
+        assert_eq!(split_tag_filters("build"), (Vec::new(), "build"));
+        assert_eq!(
+            split_tag_filters("  #ci #slow  test unit"),
+            (vec!["ci", "slow"], "test unit")
+        );
+        assert_eq!(
+            split_tag_filters("# #ci"),
+            (vec!["ci"], ""),
+            "Empty tags should be skipped"
+        );
+        assert_eq!(
+            split_tag_filters("test #ci"),
+            (Vec::new(), "test #ci"),
+            "Only the leading tags should filter the tasks"
+        );
+    }
+
+    #[test]
+    fn test_numeric_queries() {
+        assert_eq!(numeric_query("3"), Some(3));
+        assert_eq!(numeric_query(" 12 "), Some(12));
+        assert_eq!(numeric_query(""), None);
+        assert_eq!(numeric_query("1a"), None);
+        assert_eq!(numeric_query("-1"), None);
+        assert_eq!(
+            numeric_query("99999999999"),
+            None,
+            "Numbers beyond the numeric prefixes should not select anything"
+        );
+    }
+
+    #[test]
+    fn test_task_namespaces() {
+        assert_eq!(task_namespace("test:unit", ":"), Some("test"));
+        assert_eq!(task_namespace("test:unit:fast", ":"), Some("test"));
+        assert_eq!(task_namespace("build", ":"), None);
+        assert_eq!(task_namespace(":unit", ":"), None);
+        assert_eq!(task_namespace("test:", ":"), None);
+        assert_eq!(task_namespace("docs::serve", "::"), Some("docs"));
+    }
+
+    #[test]
+    fn test_tree_matches() {
+        let matches = ["test:unit", "build", "lint:fmt", "test:e2e", "lint:clippy"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| string_match(id, name, 0.0))
+            .collect::<Vec<_>>();
+        let tree = |collapsed_namespaces: &HashSet<String>| {
+            tree_matches(
+                matches.clone(),
+                |string_match| task_namespace(&string_match.string, ":"),
+                collapsed_namespaces,
+            )
+            .into_iter()
+            .map(|(string_match, collapsed_tasks)| (string_match.string, collapsed_tasks))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            tree(&HashSet::default()),
+            vec![
+                ("test:unit".to_string(), None),
+                ("test:e2e".to_string(), None),
+                ("build".to_string(), None),
+                ("lint:fmt".to_string(), None),
+                ("lint:clippy".to_string(), None),
+            ],
+            "Tasks of the same namespace should be listed together, at the position of the first of them"
+        );
+        assert_eq!(
+            tree(&HashSet::from_iter(["lint".to_string()])),
+            vec![
+                ("test:unit".to_string(), None),
+                ("test:e2e".to_string(), None),
+                ("build".to_string(), None),
+                ("lint:fmt".to_string(), Some(2)),
+            ],
+            "Collapsed namespaces should be listed by their first task, standing for all of them"
+        );
+    }
+
+    #[test]
+    fn test_merging_secondary_matches() {
+        let name_matches = vec![string_match(0, "build", 0.8), string_match(1, "test", 0.5)];
+        let secondary_matches = vec![
+            string_match(2, "cargo check", 0.6),
+            string_match(1, "cargo test", 1.0),
+            string_match(3, "Makefile lint", 1.0),
+            string_match(3, "make lint", 0.7),
+        ];
+        let merged = |limit| {
+            merge_secondary_matches(name_matches.clone(), secondary_matches.clone(), limit)
+                .into_iter()
+                .map(|string_match| (string_match.candidate_id, string_match.score))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            merged(10),
+            vec![
+                (3, 1.0 * SECONDARY_MATCH_PENALTY),
+                (0, 0.8),
+                (2, 0.6 * SECONDARY_MATCH_PENALTY),
+                (1, 0.5),
+            ],
+            "Tasks matched by their names should keep their name matches, the others should be listed once, with their best lowered secondary score"
+        );
+        assert_eq!(
+            merged(2),
+            vec![(3, 1.0 * SECONDARY_MATCH_PENALTY), (0, 0.8)],
+            "Only the best matches should be kept"
+        );
+    }
+
+    #[test]
+    fn test_source_labels() {
+        let worktree_kind = TaskSourceKind::Worktree {
+            id: WorktreeId::from_usize(0),
+            abs_path: PathBuf::from("/project/Makefile"),
+        };
+        assert_eq!(source_label(&worktree_kind), "Makefile");
+        assert_eq!(
+            source_qualified_name(&worktree_kind, "build"),
+            "Makefile build"
+        );
+        assert_eq!(
+            source_label(&TaskSourceKind::AbsPath(PathBuf::from(
+                "/outside/tasks.json"
+            ))),
+            "/outside: tasks.json",
+            "Sources outside of the worktrees should be told apart by their directories"
+        );
+    }
+
+    #[test]
+    fn test_recent_run_labels() {
+        let run = |ago: Duration, exit_status| TaskRun {
+            started_at: SystemTime::now() - ago,
+            duration: Duration::ZERO,
+            exit_status,
+        };
+        assert_eq!(
+            recent_run_label(&run(Duration::from_secs(150), TaskExitStatus::Success)).as_deref(),
+            Some("passed 2m ago")
+        );
+        assert_eq!(
+            recent_run_label(&run(
+                Duration::from_secs(3 * 60 * 60),
+                TaskExitStatus::Failure(Some(1))
+            ))
+            .as_deref(),
+            Some("failed 3h ago")
+        );
+        assert_eq!(
+            recent_run_label(&run(
+                RECENT_RUN_MAX_AGE + Duration::from_secs(60),
+                TaskExitStatus::Success
+            )),
+            None,
+            "Old runs should not be described"
+        );
+    }
+
+    fn set_query(
+        query: &str,
+        picker: &View<Picker<TasksModalDelegate>>,
+        cx: &mut VisualTestContext,
+    ) {
+        picker.update(cx, |picker, cx| picker.set_query(query, cx));
+        cx.run_until_parked();
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            crate::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+
+    fn string_match(candidate_id: usize, string: &str, score: f64) -> StringMatch {
+        StringMatch {
+            candidate_id,
+            score,
+            positions: Vec::new(),
+            string: string.to_string(),
+        }
+    }
+}
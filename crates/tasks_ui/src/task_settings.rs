@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde_derive::{Deserialize, Serialize};
+use settings::Settings;
+use task::oneshot_source::EnvExpansion;
+
+#[derive(Deserialize, Debug)]
+pub struct TaskSettings {
+    pub reorder_by_usage: bool,
+    pub max_matches: usize,
+    pub oneshot_env_expansion: EnvExpansion,
+    pub alias_files: Vec<PathBuf>,
+    pub name_separator: Option<String>,
+    pub prefer_worktree_tasks: bool,
+    pub usage_score_weight: f64,
+    pub command_palette_tasks: usize,
+}
+
+/// Configuration of the tasks modal.
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct TaskSettingsContent {
+    /// Whether the tasks modal should list the most used tasks first.
+    /// When disabled, the tasks are listed alphanumerically, but their usage is still recorded, e.g. to rerun the last task.
+    ///
+    /// Default: true
+    pub reorder_by_usage: Option<bool>,
+    /// Maximum number of the tasks matching the query to list in the tasks modal.
+    /// The best matching tasks are listed, lower values keep the modal responsive with many tasks.
+    ///
+    /// Default: 1000
+    pub max_matches: Option<usize>,
+    /// How the `$VAR` and `${VAR}` references of the oneshot tasks, spawned from the tasks modal prompt, are expanded:
+    /// by the shell, or before spawning, against the environment of the terminals.
+    ///
+    /// Default: shell
+    pub oneshot_env_expansion: Option<EnvExpansion>,
+    /// Files with the shell aliases to list as tasks, e.g. `~/.bash_aliases`.
+    /// Both `alias name='command'` and `name = command` lines are read, the other lines are skipped.
+    ///
+    /// Default: []
+    pub alias_files: Option<Vec<PathBuf>>,
+    /// The separator of the namespaced task names, e.g. `:` for `test:unit` and `test:integration`.
+    /// When set, the tasks of the same namespace are listed together in the tasks modal, as collapsible groups,
+    /// while nothing is typed into the query.
+    ///
+    /// Default: null
+    pub name_separator: Option<String>,
+    /// Whether the worktree tasks are listed instead of the same named global and user tasks.
+    /// When disabled, the global and user tasks are listed instead of the same named worktree ones.
+    ///
+    /// Default: true
+    pub prefer_worktree_tasks: Option<bool>,
+    /// How much the pinned and the recently used tasks are favored over the others, when matching the query in the tasks modal.
+    /// The bonus is added to the fuzzy match scores, ranging from 0 to 1: with small values, the usage only breaks the ties
+    /// of the equally matching tasks, with large ones, the used tasks rank above the better matching ones. 0 disables it.
+    ///
+    /// Default: 0.1
+    pub usage_score_weight: Option<f64>,
+    /// Maximum number of the tasks to list in the command palette, as the `run task: <name>` commands.
+    /// The most used tasks are listed first, 0 keeps the tasks out of the command palette.
+    ///
+    /// Default: 100
+    pub command_palette_tasks: Option<usize>,
+}
+
+impl Settings for TaskSettings {
+    const KEY: Option<&'static str> = Some("tasks");
+
+    type FileContent = TaskSettingsContent;
+
+    fn load(
+        default_value: &Self::FileContent,
+        user_values: &[&Self::FileContent],
+        _cx: &mut AppContext,
+    ) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Self::load_via_json_merge(default_value, user_values)
+    }
+}
@@ -0,0 +1,268 @@
+use std::{path::PathBuf, sync::Arc};
+
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    rems, AppContext, DismissEvent, EventEmitter, FocusableView, InteractiveElement, Model,
+    ParentElement, Render, SharedString, Styled, Subscription, View, ViewContext, VisualContext,
+    WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::{Inventory, TaskSourceKind};
+use task::Task;
+use ui::{
+    v_flex, Color, HighlightedLabel, Label, LabelCommon, LabelSize, ListItem, ListItemSpacing,
+    Selectable, WindowContext,
+};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{schedule_task_in, ScheduleOrigin};
+
+/// A worktree to run the task in, chosen in the [`WorktreeModal`].
+#[derive(Clone)]
+pub(crate) struct TaskTarget {
+    pub worktree_name: String,
+    pub source_kind: TaskSourceKind,
+    pub task: Arc<dyn Task>,
+    /// Directory to run the task in, [`None`] to let the task decide.
+    pub cwd: Option<PathBuf>,
+}
+
+impl TaskTarget {
+    /// Worktrees to offer for running the task given.
+    /// Tasks from the worktree sources are offered in every worktree that has a task with the same name,
+    /// other tasks without their own working directory are offered in every visible local worktree.
+    pub(crate) fn for_task(
+        workspace: &Workspace,
+        inventory: &Model<Inventory>,
+        source_kind: &TaskSourceKind,
+        task: &Arc<dyn Task>,
+        cx: &mut AppContext,
+    ) -> Vec<Self> {
+        if source_kind.worktree().is_some() {
+            let same_named_tasks = inventory.update(cx, |inventory, cx| {
+                inventory
+                    .list_tasks(None, None, false, false, cx)
+                    .into_iter()
+                    .filter(|(kind, candidate)| {
+                        kind.worktree().is_some() && candidate.name() == task.name()
+                    })
+                    .collect::<Vec<_>>()
+            });
+            let project = workspace.project().read(cx);
+            same_named_tasks
+                .into_iter()
+                .filter_map(|(source_kind, task)| {
+                    let worktree = project.worktree_for_id(source_kind.worktree()?, cx)?;
+                    let worktree = worktree.read(cx);
+                    let cwd = match task.cwd() {
+                        Some(cwd) => {
+                            source_kind.resolve_cwd(cwd, Some(worktree.abs_path().as_ref()))
+                        }
+                        None => worktree.abs_path().to_path_buf(),
+                    };
+                    Some(Self {
+                        worktree_name: worktree.root_name().to_string(),
+                        source_kind,
+                        task,
+                        cwd: Some(cwd),
+                    })
+                })
+                .collect()
+        } else if task.cwd().is_some() {
+            Vec::new()
+        } else {
+            workspace
+                .project()
+                .read(cx)
+                .visible_worktrees(cx)
+                .filter(|worktree| worktree.read(cx).is_local())
+                .map(|worktree| {
+                    let worktree = worktree.read(cx);
+                    Self {
+                        worktree_name: worktree.root_name().to_string(),
+                        source_kind: source_kind.clone(),
+                        task: task.clone(),
+                        cwd: Some(worktree.abs_path().to_path_buf()),
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// A modal to choose the worktree to run a task in.
+pub(crate) struct WorktreeModal {
+    picker: View<Picker<WorktreeModalDelegate>>,
+    _subscription: Subscription,
+}
+
+impl WorktreeModal {
+    pub(crate) fn new(
+        targets: Vec<TaskTarget>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let picker = cx.new_view(|cx| {
+            Picker::uniform_list(
+                WorktreeModalDelegate {
+                    targets,
+                    matches: Vec::new(),
+                    selected_index: 0,
+                    workspace,
+                },
+                cx,
+            )
+        });
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for WorktreeModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out(cx.listener(|modal, _, cx| {
+                modal.picker.update(cx, |picker, cx| {
+                    picker.cancel(&Default::default(), cx);
+                })
+            }))
+    }
+}
+
+impl EventEmitter<DismissEvent> for WorktreeModal {}
+
+impl FocusableView for WorktreeModal {
+    fn focus_handle(&self, cx: &gpui::AppContext) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for WorktreeModal {}
+
+pub(crate) struct WorktreeModalDelegate {
+    targets: Vec<TaskTarget>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    workspace: WeakView<Workspace>,
+}
+
+impl PickerDelegate for WorktreeModalDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.targets.first() {
+            Some(target) => Arc::from(format!("Worktree to run {:?} in...", target.task.name())),
+            None => Arc::from("Worktree to run the task in..."),
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let candidates = self
+            .targets
+            .iter()
+            .enumerate()
+            .map(|(index, target)| StringMatchCandidate {
+                id: index,
+                char_bag: target.worktree_name.chars().collect(),
+                string: target.worktree_name.clone(),
+            })
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                true,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    if delegate.matches.is_empty() {
+                        delegate.selected_index = 0;
+                    } else {
+                        delegate.selected_index =
+                            delegate.selected_index.min(delegate.matches.len() - 1);
+                    }
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(target) = self
+            .matches
+            .get(self.selected_index)
+            .map(|current_match| self.targets[current_match.candidate_id].clone())
+        else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                schedule_task_in(
+                    workspace,
+                    target.source_kind,
+                    &target.task,
+                    target.cwd,
+                    ScheduleOrigin::Modal,
+                    cx,
+                );
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = &self.matches[ix];
+        let target = &self.targets[hit.candidate_id];
+        let highlights: Vec<_> = hit.positions.iter().copied().collect();
+        Some(
+            ListItem::new(SharedString::from(format!("worktree-modal-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(HighlightedLabel::new(hit.string.clone(), highlights))
+                .end_slot(
+                    Label::new(target.source_kind.label())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+}
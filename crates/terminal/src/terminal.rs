@@ -23,7 +23,10 @@ use alacritty_terminal::{
 use anyhow::{bail, Result};
 
 use futures::{
-    channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    channel::{
+        mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
     FutureExt,
 };
 
@@ -38,7 +41,7 @@ use procinfo::LocalProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 use smol::channel::{Receiver, Sender};
-use task::TaskId;
+use task::{RevealStrategy, TaskExitStatus, TaskId};
 use terminal_settings::{AlternateScroll, Shell, TerminalBlink, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use util::truncate_and_trailoff;
@@ -91,6 +94,10 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The task process ran longer than its timeout, and was terminated.
+    TaskTimedOut,
+    /// The task process has exited, with the status given.
+    TaskExited(TaskExitStatus),
 }
 
 #[derive(Clone, Debug)]
@@ -291,6 +298,11 @@ pub struct SpawnTask {
     pub command: String,
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
+    pub reuse_key: Option<String>,
+    pub timeout: Option<Duration>,
+    pub reveal: RevealStrategy,
+    pub success_message: Option<String>,
+    pub failure_message: Option<String>,
 }
 
 // https://github.com/alacritty/alacritty/blob/cb3a79dbf6472740daca8440d5166c1d4af5029e/extra/man/alacritty.5.scd?plain=1#L207-L213
@@ -300,6 +312,8 @@ const MAX_SCROLL_HISTORY_LINES: usize = 100_000;
 pub struct TerminalBuilder {
     terminal: Terminal,
     events_rx: UnboundedReceiver<AlacTermEvent>,
+    /// Resolves with the task process exit status, `None` if it could not be retrieved.
+    task_exit_rx: Option<oneshot::Receiver<Option<TaskExitStatus>>>,
 }
 
 impl TerminalBuilder {
@@ -412,6 +426,15 @@ impl TerminalBuilder {
             false,
         )?;
 
+        // Alacritty reaps the child process on its own, and the `alacritty_terminal` version used reports no exit code for it:
+        // there is no `ChildExit` event yet. So the task process is awaited separately, leaving it for Alacritty to reap,
+        // see `wait_for_exit_status` for the race this has.
+        let task_exit_rx = task.as_ref().map(|_| {
+            let (task_exit_tx, task_exit_rx) = oneshot::channel();
+            std::thread::spawn(move || task_exit_tx.send(wait_for_exit_status(shell_pid)).ok());
+            task_exit_rx
+        });
+
         //Kick things off
         let pty_tx = event_loop.channel();
         let _io_thread = event_loop.spawn(); // DANGER
@@ -446,6 +469,7 @@ impl TerminalBuilder {
         Ok(TerminalBuilder {
             terminal,
             events_rx,
+            task_exit_rx,
         })
     }
 
@@ -508,6 +532,31 @@ impl TerminalBuilder {
         })
         .detach();
 
+        if let Some(task_exit_rx) = self.task_exit_rx.take() {
+            cx.spawn(|terminal, mut cx| async move {
+                if let Ok(Some(exit_status)) = task_exit_rx.await {
+                    terminal.update(&mut cx, |terminal, cx| {
+                        terminal.task_exited(exit_status, cx);
+                    })?;
+                }
+                anyhow::Ok(())
+            })
+            .detach();
+        }
+
+        if let Some(timeout) = self.terminal.task.as_ref().and_then(|task| task.timeout) {
+            cx.spawn(|terminal, mut cx| async move {
+                cx.background_executor().timer(timeout).await;
+                terminal.update(&mut cx, |terminal, cx| {
+                    if terminal.task.as_ref().is_some_and(|task| !task.completed) {
+                        terminal.kill_task(cx);
+                        cx.emit(Event::TaskTimedOut);
+                    }
+                })
+            })
+            .detach();
+        }
+
         self.terminal
     }
 }
@@ -602,7 +651,16 @@ pub struct Terminal {
 pub struct TaskState {
     pub id: TaskId,
     pub label: String,
+    pub reuse_key: Option<String>,
+    pub timeout: Option<Duration>,
+    /// Whether the task's terminal is shown when the task is spawned, or only if the task fails.
+    pub reveal: RevealStrategy,
+    /// Messages to notify about the task completion with, instead of the generic ones.
+    pub success_message: Option<String>,
+    pub failure_message: Option<String>,
     pub completed: bool,
+    /// How the task process has exited, `None` while it runs or if its exit status could not be retrieved.
+    pub exit_status: Option<TaskExitStatus>,
     pub completion_rx: Receiver<()>,
 }
 
@@ -639,6 +697,7 @@ impl Terminal {
                 Some(task) => {
                     task.completed = true;
                     self.completion_tx.try_send(()).ok();
+                    cx.notify();
                 }
                 None => cx.emit(Event::CloseTerminal),
             },
@@ -1406,6 +1465,34 @@ impl Terminal {
         self.task.as_ref()
     }
 
+    /// Terminates the task process, if it still runs, marking the task completed.
+    pub fn kill_task(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(task) = &mut self.task else {
+            return;
+        };
+        if task.completed {
+            return;
+        }
+        self.pty_tx.0.send(Msg::Shutdown).ok();
+        task.completed = true;
+        self.completion_tx.try_send(()).ok();
+        self.task_exited(TaskExitStatus::Failure(None), cx);
+        cx.notify();
+    }
+
+    /// Records the task process exit status, unless it was already recorded.
+    fn task_exited(&mut self, exit_status: TaskExitStatus, cx: &mut ModelContext<Self>) {
+        let Some(task) = &mut self.task else {
+            return;
+        };
+        if task.exit_status.is_some() {
+            return;
+        }
+        task.exit_status = Some(exit_status);
+        cx.emit(Event::TaskExited(exit_status));
+        cx.notify();
+    }
+
     pub fn wait_for_completed_task(&self, cx: &mut AppContext) -> Task<()> {
         match self.task() {
             Some(task) => {
@@ -1562,6 +1649,48 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
     .into()
 }
 
+/// Blocks until the process given exits, without reaping it.
+/// Returns `None` if the exit status could not be retrieved, e.g. if the process was reaped already.
+///
+/// The wait races Alacritty's event loop, which reaps the process once it exits: when the process exits
+/// before the wait starts, and Alacritty reaps it in between, the wait fails and the exit status is lost.
+/// The wait starts right after the process is spawned, so this takes a process that exits immediately,
+/// and its task is then completed with no exit status, same as on the platforms with no waiting support.
+#[cfg(unix)]
+fn wait_for_exit_status(pid: u32) -> Option<TaskExitStatus> {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    loop {
+        let result = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if result == 0 {
+            break;
+        }
+        if std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted {
+            return None;
+        }
+    }
+    match info.si_code {
+        libc::CLD_EXITED => match unsafe { info.si_status() } {
+            0 => Some(TaskExitStatus::Success),
+            code => Some(TaskExitStatus::Failure(Some(code))),
+        },
+        libc::CLD_KILLED | libc::CLD_DUMPED => Some(TaskExitStatus::Failure(None)),
+        _ => None,
+    }
+}
+
+/// The exit statuses are not retrieved on Windows: the tasks are completed with no exit status there.
+#[cfg(windows)]
+fn wait_for_exit_status(_: u32) -> Option<TaskExitStatus> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
@@ -1575,6 +1704,49 @@ mod tests {
         content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
     };
 
+    #[cfg(unix)]
+    #[test]
+    fn test_waiting_for_exit_status() {
+        use crate::wait_for_exit_status;
+        use task::TaskExitStatus;
+
+        for (command, expected_status) in [
+            ("exit 0", TaskExitStatus::Success),
+            ("exit 3", TaskExitStatus::Failure(Some(3))),
+            ("kill -9 $$", TaskExitStatus::Failure(None)),
+        ] {
+            let mut child = std::process::Command::new("sh")
+                .args(["-c", command])
+                .spawn()
+                .unwrap();
+            assert_eq!(
+                wait_for_exit_status(child.id()),
+                Some(expected_status),
+                "Unexpected exit status for command {command:?}"
+            );
+            // The process is not reaped by the wait, so it is still possible to do so.
+            child.wait().unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_waiting_for_reaped_process() {
+        use crate::wait_for_exit_status;
+
+        // Same as when Alacritty wins the race, and reaps the process before the wait starts.
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "exit 3"])
+            .spawn()
+            .unwrap();
+        child.wait().unwrap();
+        assert_eq!(
+            wait_for_exit_status(child.id()),
+            None,
+            "Exit status of an already reaped process should be reported as unknown"
+        );
+    }
+
     #[test]
     fn test_rgb_for_index() {
         //Test every possible value in the color cube
@@ -14,7 +14,7 @@ use project::{Fs, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
-use task::{SpawnInTerminal, TaskId};
+use task::{RevealStrategy, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{Shell, TerminalDockPosition, TerminalSettings},
     SpawnTask,
@@ -296,39 +296,68 @@ impl TerminalPanel {
     }
 
     pub fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
-        let mut spawn_task = SpawnTask {
+        let Some((command, args)) = spawn_in_terminal.program_and_args(|| {
+            match TerminalSettings::get_global(cx).shell.clone() {
+                Shell::System => std::env::var("SHELL").ok().map(|shell| (shell, vec![])),
+                Shell::Program(shell) => Some((shell, vec![])),
+                Shell::WithArguments { program, args } => Some((program, args)),
+            }
+        }) else {
+            return;
+        };
+        let spawn_task = SpawnTask {
             id: spawn_in_terminal.id.clone(),
             label: spawn_in_terminal.label.clone(),
-            command: spawn_in_terminal.command.clone(),
-            args: spawn_in_terminal.args.clone(),
+            command,
+            args,
             env: spawn_in_terminal.env.clone(),
+            reuse_key: spawn_in_terminal.reuse_key.clone(),
+            timeout: spawn_in_terminal
+                .timeout
+                .filter(|_| !spawn_in_terminal.interactive),
+            reveal: spawn_in_terminal.reveal,
+            success_message: spawn_in_terminal.success_message.clone(),
+            failure_message: spawn_in_terminal.failure_message.clone(),
         };
-        if spawn_in_terminal.separate_shell {
-            let Some((shell, mut user_args)) = (match TerminalSettings::get_global(cx).shell.clone()
-            {
-                Shell::System => std::env::var("SHELL").ok().map(|shell| (shell, vec![])),
-                Shell::Program(shell) => Some((shell, vec![])),
-                Shell::WithArguments { program, args } => Some((program, args)),
-            }) else {
-                return;
-            };
-
-            let command = std::mem::take(&mut spawn_task.command);
-            let args = std::mem::take(&mut spawn_task.args);
-            spawn_task.command = shell;
-            user_args.extend(["-i".to_owned(), "-c".to_owned(), command]);
-            user_args.extend(args);
-            spawn_task.args = user_args;
-        }
         let working_directory = spawn_in_terminal.cwd.clone();
         let allow_concurrent_runs = spawn_in_terminal.allow_concurrent_runs;
         let use_new_terminal = spawn_in_terminal.use_new_terminal;
 
+        if spawn_in_terminal.interactive {
+            // Interactive sessions wait for the user's input, so they are never replaced by the reruns, and always get the focus.
+            self.spawn_in_new_terminal(
+                SpawnTask {
+                    reveal: RevealStrategy::Always,
+                    ..spawn_task
+                },
+                working_directory,
+                cx,
+            );
+            return;
+        }
+
         if allow_concurrent_runs && use_new_terminal {
             self.spawn_in_new_terminal(spawn_task, working_directory, cx);
             return;
         }
 
+        if let Some(reuse_key) = &spawn_in_terminal.reuse_key {
+            // Tasks with the reuse key are restarted right away, as they are usually long-running and never finish on their own.
+            match self.terminals_for_reuse_key(reuse_key, cx).pop() {
+                Some((existing_item_index, existing_terminal)) => {
+                    self.replace_terminal(
+                        working_directory,
+                        spawn_task,
+                        existing_item_index,
+                        existing_terminal,
+                        cx,
+                    );
+                }
+                None => self.spawn_in_new_terminal(spawn_task, working_directory, cx),
+            }
+            return;
+        }
+
         let terminals_for_task = self.terminals_for_task(&spawn_in_terminal.id, cx);
         if terminals_for_task.is_empty() {
             self.spawn_in_new_terminal(spawn_task, working_directory, cx);
@@ -385,7 +414,11 @@ impl TerminalPanel {
         working_directory: Option<PathBuf>,
         cx: &mut ViewContext<Self>,
     ) {
+        let reveal = spawn_task.reveal;
         self.add_terminal(working_directory, Some(spawn_task), cx);
+        if reveal != RevealStrategy::Always {
+            return;
+        }
         let task_workspace = self.workspace.clone();
         cx.spawn(|_, mut cx| async move {
             task_workspace
@@ -429,6 +462,35 @@ impl TerminalPanel {
             .collect()
     }
 
+    fn terminals_for_reuse_key(
+        &self,
+        reuse_key: &str,
+        cx: &mut AppContext,
+    ) -> Vec<(usize, View<TerminalView>)> {
+        self.pane
+            .read(cx)
+            .items()
+            .enumerate()
+            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
+            .filter(|(_, terminal_view)| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .task()
+                    .is_some_and(|task_state| task_state.reuse_key.as_deref() == Some(reuse_key))
+            })
+            .collect()
+    }
+
+    /// Shows the terminal given in the panel, e.g. for a silently run task that has failed.
+    pub fn reveal_terminal_view(&self, terminal_view: &View<TerminalView>, cx: &mut WindowContext) {
+        let item_index = self.pane.read(cx).index_for_item(terminal_view);
+        if let Some(item_index) = item_index {
+            self.activate_terminal_view(item_index, cx);
+        }
+    }
+
     fn activate_terminal_view(&self, item_index: usize, cx: &mut WindowContext) {
         self.pane.update(cx, |pane, cx| {
             pane.activate_item(item_index, true, true, cx)
@@ -540,6 +602,7 @@ impl TerminalPanel {
             .update(cx, |workspace, _| workspace.project().clone())
             .ok()?;
         let window = cx.window_handle();
+        let reveal = spawn_task.reveal;
         let new_terminal = project.update(cx, |project, cx| {
             project
                 .create_terminal(working_directory, Some(spawn_task), window, cx)
@@ -548,6 +611,9 @@ impl TerminalPanel {
         terminal_to_replace.update(cx, |terminal_to_replace, cx| {
             terminal_to_replace.set_terminal(new_terminal, cx);
         });
+        if reveal != RevealStrategy::Always {
+            return Some(());
+        }
         self.activate_terminal_view(terminal_item_index, cx);
         let task_workspace = self.workspace.clone();
         cx.spawn(|_, mut cx| async move {
@@ -13,6 +13,7 @@ use gpui::{
 use language::Bias;
 use persistence::TERMINAL_DB;
 use project::{search::SearchQuery, Fs, LocalWorktree, Metadata, Project};
+use task::{RevealStrategy, TaskExitStatus};
 use terminal::{
     alacritty_terminal::{
         index::Point,
@@ -22,6 +23,7 @@ use terminal::{
     Clear, Copy, Event, MaybeNavigationTarget, Paste, ShowCharacterPalette, Terminal,
 };
 use terminal_element::TerminalElement;
+use terminal_panel::TerminalPanel;
 use ui::{h_flex, prelude::*, ContextMenu, Icon, IconName, Label};
 use util::{paths::PathLikeWithPosition, ResultExt};
 use workspace::{
@@ -29,7 +31,7 @@ use workspace::{
     notifications::NotifyResultExt,
     register_deserializable_item,
     searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
-    CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation, Workspace,
+    CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, Toast, ToolbarItemLocation, Workspace,
     WorkspaceId,
 };
 
@@ -51,6 +53,7 @@ const REGEX_SPECIAL_CHARS: &[char] = &[
 ];
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+const TASK_STATUS_TOAST_ID: usize = 0x7e4d_1a00;
 
 ///Event to transmit the scroll from the element to the view
 #[derive(Clone, Debug, PartialEq)]
@@ -577,6 +580,63 @@ fn subscribe_for_terminal_events(
             },
             Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
             Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
+            Event::TaskTimedOut => {
+                let Some(task_label) = this
+                    .terminal()
+                    .read(cx)
+                    .task()
+                    .map(|task| task.label.clone())
+                else {
+                    return;
+                };
+                workspace
+                    .update(cx, |workspace, cx| {
+                        workspace.show_toast(
+                            Toast::new(
+                                TASK_STATUS_TOAST_ID,
+                                format!("Task {task_label:?} timed out and was terminated"),
+                            ),
+                            cx,
+                        )
+                    })
+                    .ok();
+            }
+            Event::TaskExited(exit_status) => {
+                let Some(task) = this.terminal().read(cx).task() else {
+                    return;
+                };
+                let task_label = &task.label;
+                let reveal = task.reveal;
+                let message = match exit_status {
+                    TaskExitStatus::Success => task
+                        .success_message
+                        .clone()
+                        .unwrap_or_else(|| format!("Task {task_label:?} succeeded")),
+                    TaskExitStatus::Failure(code) => {
+                        task.failure_message.clone().unwrap_or_else(|| match code {
+                            Some(code) => {
+                                format!("Task {task_label:?} failed with exit code {code}")
+                            }
+                            None => format!("Task {task_label:?} failed"),
+                        })
+                    }
+                };
+                let reveal = reveal == RevealStrategy::OnFailure && !exit_status.is_success();
+                let terminal_view = cx.view().clone();
+                workspace
+                    .update(cx, |workspace, cx| {
+                        workspace.show_toast(Toast::new(TASK_STATUS_TOAST_ID, message), cx);
+                        if reveal {
+                            if let Some(terminal_panel) = workspace.focus_panel::<TerminalPanel>(cx)
+                            {
+                                terminal_panel.update(cx, |terminal_panel, cx| {
+                                    terminal_panel.reveal_terminal_view(&terminal_view, cx)
+                                });
+                            }
+                        }
+                    })
+                    .ok();
+            }
             Event::SelectionsChanged => cx.emit(SearchEvent::ActiveMatchChanged),
         });
     vec![terminal_subscription, terminal_events_subscription]
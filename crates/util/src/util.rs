@@ -507,6 +507,11 @@ impl<'a> NumericPrefixWithSuffix<'a> {
             Err(_) => None,
         }
     }
+
+    /// The number the string starts with.
+    pub fn prefix(&self) -> i32 {
+        self.0
+    }
 }
 
 impl Ord for NumericPrefixWithSuffix<'_> {
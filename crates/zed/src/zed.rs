@@ -6,10 +6,11 @@ pub use app_menus::*;
 use assistant::AssistantPanel;
 use breadcrumbs::Breadcrumbs;
 use collections::VecDeque;
+use db::kvp::KEY_VALUE_STORE;
 use editor::{Editor, MultiBuffer};
 use gpui::{
-    actions, point, px, AppContext, Context, FocusableView, PromptLevel, TitlebarOptions, View,
-    ViewContext, VisualContext, WindowBounds, WindowKind, WindowOptions,
+    actions, point, px, AppContext, Context, FocusableView, Model, PromptLevel, TitlebarOptions,
+    View, ViewContext, VisualContext, WindowBounds, WindowKind, WindowOptions,
 };
 pub use only_instance::*;
 pub use open_listener::*;
@@ -17,17 +18,17 @@ pub use open_listener::*;
 use anyhow::{anyhow, Context as _};
 use assets::Assets;
 use futures::{channel::mpsc, select_biased, StreamExt};
+use project::TaskSourceKind;
 use project_panel::ProjectPanel;
 use quick_action_bar::QuickActionBar;
 use release_channel::{AppCommitSha, ReleaseChannel};
 use rope::Rope;
 use search::project_search::ProjectSearchBar;
 use settings::{
-    initial_local_settings_content, watch_config_file, KeymapFile, Settings, SettingsStore,
-    DEFAULT_KEYMAP_PATH,
+    initial_local_settings_content, KeymapFile, Settings, SettingsStore, DEFAULT_KEYMAP_PATH,
 };
 use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
-use task::{oneshot_source::OneshotSource, static_source::StaticSource};
+use task::{oneshot_source::OneshotSource, static_source::StaticSource, TaskSource};
 use terminal_view::terminal_panel::{self, TerminalPanel};
 use util::{
     asset_str,
@@ -44,6 +45,8 @@ use workspace::{
 };
 use zed_actions::{OpenBrowser, OpenSettings, OpenZedUrl, Quit};
 
+const ONESHOT_TASKS_KEY: &str = "OneshotTasks";
+
 actions!(
     zed,
     [
@@ -155,18 +158,20 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
 
         let project = workspace.project().clone();
         if project.read(cx).is_local() {
-            let tasks_file_rx = watch_config_file(
-                &cx.background_executor(),
-                app_state.fs.clone(),
-                paths::TASKS.clone(),
-            );
-            let static_source = StaticSource::new(tasks_file_rx, cx);
             let oneshot_source = OneshotSource::new(cx);
+            persist_oneshot_tasks(&oneshot_source, cx);
 
             project.update(cx, |project, cx| {
                 project.task_inventory().update(cx, |inventory, cx| {
-                    inventory.add_source(oneshot_source, cx);
-                    inventory.add_source(static_source, cx);
+                    inventory.add_source(TaskSourceKind::UserInput, oneshot_source, cx);
+                    inventory.add_static_source(
+                        TaskSourceKind::Global(paths::TASKS.clone()),
+                        app_state.fs.clone(),
+                        |tasks_tracker, cx| {
+                            StaticSource::new(paths::TASKS.clone(), tasks_tracker, cx)
+                        },
+                        cx,
+                    );
                 })
             });
         }
@@ -397,6 +402,52 @@ fn initialize_pane(workspace: &mut Workspace, pane: &View<Pane>, cx: &mut ViewCo
     });
 }
 
+/// Restores the oneshot tasks from the previous sessions and stores them on every change.
+fn persist_oneshot_tasks(
+    oneshot_source: &Model<Box<dyn TaskSource>>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let weak_source = oneshot_source.downgrade();
+    cx.spawn(|_, mut cx| async move {
+        let serialized_prompts = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(ONESHOT_TASKS_KEY) })
+            .await?;
+        if let Some(serialized_prompts) = serialized_prompts {
+            let prompts = serde_json::from_str::<Vec<String>>(&serialized_prompts)?;
+            weak_source.update(&mut cx, |source, cx| {
+                if let Some(oneshot_source) = source.as_any().downcast_mut::<OneshotSource>() {
+                    oneshot_source.restore(prompts, cx);
+                }
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+
+    cx.observe(oneshot_source, |_, source, cx| {
+        let Some(prompts) = source.update(cx, |source, _| {
+            let oneshot_source = source.as_any().downcast_mut::<OneshotSource>()?;
+            Some(
+                oneshot_source
+                    .prompts()
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<_>>(),
+            )
+        }) else {
+            return;
+        };
+        cx.background_executor()
+            .spawn(async move {
+                KEY_VALUE_STORE
+                    .write_kvp(ONESHOT_TASKS_KEY.into(), serde_json::to_string(&prompts)?)
+                    .await
+            })
+            .detach_and_log_err(cx);
+    })
+    .detach();
+}
+
 fn about(_: &mut Workspace, _: &About, cx: &mut gpui::ViewContext<Workspace>) {
     let release_channel = ReleaseChannel::global(cx).display_name();
     let version = env!("CARGO_PKG_VERSION");